@@ -16,13 +16,33 @@
  * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, path::PathBuf, rc::Rc};
+
+use crate::log;
+
+mod tests;
 
 pub struct Definitions {
     pub tys_kind: HashMap<TyConstructor, TyKind>,
     pub structures: Vec<Structure>,
+    pub enumerations: Vec<Enumeration>,
     pub functions: Vec<(FunctionTy, FunctionDefinition)>,
+    pub tests: Vec<Test>,
+    /**
+     * Benchmarks declared with `bench "name"` ... `end`, in the same form as
+     * tests.
+     */
+    pub benchmarks: Vec<Test>,
     pub num_global_variables: usize,
+    /**
+     * The qualified names, such as `app.log`, and the types of the functions
+     * provided by the application embedding the language.
+     */
+    pub host_functions: Vec<(String, FunctionTy)>,
+    /**
+     * Same as `host_functions`, for global variables.
+     */
+    pub host_variables: Vec<(String, TyBuilder)>,
 }
 
 impl Definitions {
@@ -60,8 +80,54 @@ impl Definitions {
                 ),
             ]),
             structures: Vec::new(),
+            enumerations: Vec::new(),
             functions: Vec::new(),
+            tests: Vec::new(),
+            benchmarks: Vec::new(),
             num_global_variables: 0,
+            host_functions: Vec::new(),
+            host_variables: Vec::new(),
+        }
+    }
+    /**
+     * Applies [`eliminate_dead_code`] to the bodies of all functions,
+     * tests and benchmarks.
+     */
+    pub fn eliminate_dead_code(&mut self) {
+        for (_, definition) in &mut self.functions {
+            eliminate_dead_code(&mut definition.body);
+        }
+        for test in self.tests.iter_mut().chain(&mut self.benchmarks) {
+            eliminate_dead_code(&mut test.definition.body);
+        }
+    }
+}
+
+/**
+ * Removes the statements never executed because of conditions known at
+ * compile time, such as `while DEBUG` or `if DEBUG` with
+ * `const DEBUG = 1 == 0`. Constants are already substituted in the frontend.
+ */
+pub fn eliminate_dead_code(statements: &mut Vec<Statement>) {
+    for statement in std::mem::take(statements) {
+        match statement {
+            Statement::While(Expression::Constant(Constant::Boolean(false)), _) => {}
+            Statement::While(condition, mut body) => {
+                eliminate_dead_code(&mut body);
+                statements.push(Statement::While(condition, body));
+            }
+            // Only the branch taken is left, in place of the `if` statement.
+            Statement::If(Expression::Constant(Constant::Boolean(condition)), body, else_body) => {
+                let mut taken = if condition { body } else { else_body };
+                eliminate_dead_code(&mut taken);
+                statements.extend(taken);
+            }
+            Statement::If(condition, mut body, mut else_body) => {
+                eliminate_dead_code(&mut body);
+                eliminate_dead_code(&mut else_body);
+                statements.push(Statement::If(condition, body, else_body));
+            }
+            statement => statements.push(statement),
         }
     }
 }
@@ -71,6 +137,21 @@ pub struct Structure {
     pub fields_ty: Vec<TyBuilder>,
 }
 
+/**
+ * An enumeration, whose value is one of the variants carrying a payload
+ * of the type of the variant, if any.
+ */
+pub struct Enumeration {
+    pub num_ty_parameters: usize,
+    pub variants: Vec<Variant>,
+}
+
+pub struct Variant {
+    pub name: String,
+    pub payload_ty: Option<TyBuilder>,
+}
+
+#[derive(Clone)]
 pub struct FunctionTy {
     pub num_ty_parameters: usize,
     pub parameters_ty: Vec<TyBuilder>,
@@ -79,9 +160,96 @@ pub struct FunctionTy {
 
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub enum Function {
+    // The builtin methods on integers, which are 64-bit two's complement.
+    // An overflow is an error.
     IAdd,
+    ISub,
+    IMul,
+    /**
+     * `/`, rounding toward zero. Division by zero is an error.
+     */
+    IDiv,
+    /**
+     * `%`, with the sign of the dividend. Division by zero is an error.
+     */
+    IRem,
+    /**
+     * `**` on integers. A negative exponent or an overflow is an error.
+     */
+    IPow,
+    /**
+     * Unary `-`.
+     */
+    INeg,
+    IEqual,
+    INotEqual,
+    ILess,
+    ILessOrEqual,
+    IGreater,
+    IGreaterOrEqual,
+    // The builtin methods on floating-point numbers, which follow IEEE 754.
+    FAdd,
+    FSub,
+    FMul,
+    FDiv,
+    FRem,
+    /**
+     * `**` on floating-point numbers.
+     */
+    FPow,
+    /**
+     * Unary `-`.
+     */
+    FNeg,
+    FEqual,
+    FNotEqual,
+    FLess,
+    FLessOrEqual,
+    FGreater,
+    FGreaterOrEqual,
+    /**
+     * `!` on the result of a comparison.
+     */
+    LogicalNot,
+    /**
+     * `+` on strings, which concatenates them.
+     */
+    SConcat,
+    /**
+     * `string * count`, which repeats `string` `count` times. A negative
+     * count is an error.
+     */
+    SRepeat,
+    /**
+     * `x in s` on strings, which tells whether `x` is a substring of `s`.
+     * The arguments are in the order of `s.contains(x)`.
+     */
+    SContains,
+    IBitAnd,
+    IBitOr,
+    IBitXor,
+    IBitNot,
+    /**
+     * `<<`, discarding the bits shifted out. A shift amount outside `0..64`
+     * is an error.
+     */
+    ILeftShift,
+    /**
+     * `>>`, which is arithmetic and fills the vacated bits with the sign
+     * bit. A shift amount outside `0..64` is an error.
+     */
+    IRightShift,
     Deref,
+    /**
+     * Stores the second argument into the reference given as the first.
+     */
+    Assign,
     UserDefined(usize),
+    /**
+     * A function provided by the application embedding the language, with
+     * the index in [`Definitions::host_functions`].
+     */
+    Host(usize),
     Field {
         structure_index: usize,
         field_index: usize,
@@ -90,6 +258,20 @@ pub enum Function {
         structure_index: usize,
         field_index: usize,
     },
+    /**
+     * Constructs a value of a structure from the fields, in the order of
+     * the definition.
+     */
+    Construct {
+        structure_index: usize,
+    },
+    /**
+     * Constructs a value of an enumeration from the payload, if any.
+     */
+    Variant {
+        enumeration_index: usize,
+        variant_index: usize,
+    },
 }
 
 pub struct FunctionDefinition {
@@ -97,6 +279,15 @@ pub struct FunctionDefinition {
     pub body: Vec<Statement>,
 }
 
+/**
+ * A test declared with `test "name"` ... `end`. The body takes no
+ * arguments and returns nothing, like a function of type `() -> ()`.
+ */
+pub struct Test {
+    pub name: String,
+    pub definition: FunctionDefinition,
+}
+
 #[derive(Clone)]
 pub enum TyBuilder {
     Constructor(TyConstructor),
@@ -115,6 +306,7 @@ pub enum TyConstructor {
     Tuple,
     Function,
     Structure(usize),
+    Enumeration(usize),
 }
 
 pub enum TyKind {
@@ -233,15 +425,163 @@ pub enum Statement {
     Empty,
     Expr(Expression),
     While(Expression, Vec<Statement>),
+    /**
+     * `if` statement with the statements executed when the condition holds
+     * and otherwise. `else if` is an `If` alone in the latter.
+     */
+    If(Expression, Vec<Statement>, Vec<Statement>),
+    /**
+     * Returns from the function, with the value if any.
+     */
+    Return(Option<Expression>),
+    /**
+     * Exits the innermost `while`.
+     */
+    Break,
+    /**
+     * Goes on to the next iteration of the innermost `while`.
+     */
+    Continue,
+    /**
+     * `assert` statement. The source text and position of the condition are
+     * kept so that a failure can be reported without the source file.
+     */
+    Assert {
+        condition: Expression,
+        condition_source: String,
+        message: Option<String>,
+        path: PathBuf,
+        pos: log::Pos,
+    },
 }
 
 pub enum Expression {
     GlobalVariable(usize),
     LocalVariable(usize),
+    /**
+     * A global variable provided by the application embedding the language,
+     * with the index in [`Definitions::host_variables`].
+     */
+    HostVariable(usize),
+    Constant(Constant),
     Function {
         candidates: Vec<Function>,
         calls: Vec<Call>,
     },
+    /**
+     * A compound assignment such as `target += value`. `target`, which is a
+     * reference, is evaluated only once. The value it refers to and `value`
+     * are passed to one of `operator`, and the result is stored back.
+     */
+    CompoundAssignment {
+        target: Box<Expression>,
+        operator: Vec<Function>,
+        value: Box<Expression>,
+    },
+    /**
+     * `a, b = x, y`. All of `values` are evaluated into temporaries before
+     * any of them is stored into `targets`, which are references, so that
+     * `a, b = b, a` swaps them.
+     */
+    MultipleAssignment {
+        targets: Vec<Expression>,
+        values: Vec<Expression>,
+    },
+    /**
+     * `a, b = t`, where `value` is a tuple with as many elements as
+     * `targets`. The elements are stored into `targets` directly, so the
+     * tuple returned by a call such as `q, r = a.divmod(b)` need not be
+     * allocated.
+     */
+    Destructuring {
+        targets: Vec<Expression>,
+        value: Box<Expression>,
+    },
+    /**
+     * A string literal with placeholders such as `"x = ${x:04}"`, which
+     * concatenates the pieces in order.
+     */
+    Interpolation(Vec<InterpolationPiece>),
+}
+
+/**
+ * A piece of [`Expression::Interpolation`].
+ */
+pub enum InterpolationPiece {
+    String(String),
+    /**
+     * A value in a placeholder, converted into a string by `spec`.
+     */
+    Value {
+        value: Expression,
+        spec: FormatSpec,
+    },
+}
+
+/**
+ * A value computed at compile time.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constant {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+}
+
+/**
+ * How a value in a placeholder `${value:spec}` is converted into a string.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FormatSpec {
+    /**
+     * Pads with `0` after the sign instead of spaces before it.
+     */
+    pub zero_padding: bool,
+    /**
+     * The minimum number of characters.
+     */
+    pub width: Option<usize>,
+    /**
+     * The number of digits after the decimal point of a float.
+     */
+    pub precision: Option<usize>,
+    /**
+     * The radix of an integer: 2, 8 or 16. Decimal if `None`.
+     */
+    pub radix: Option<u32>,
+}
+
+impl FormatSpec {
+    pub fn format_integer(&self, value: i64) -> String {
+        let magnitude = value.unsigned_abs();
+        let digits = match self.radix {
+            Some(16) => format!("{magnitude:x}"),
+            Some(8) => format!("{magnitude:o}"),
+            Some(2) => format!("{magnitude:b}"),
+            _ => magnitude.to_string(),
+        };
+        self.pad(value < 0, digits)
+    }
+    pub fn format_float(&self, value: f64) -> String {
+        let digits = match self.precision {
+            Some(precision) => format!("{:.precision$}", value.abs()),
+            None => value.abs().to_string(),
+        };
+        self.pad(value < 0., digits)
+    }
+    fn pad(&self, is_negative: bool, digits: String) -> String {
+        let sign = if is_negative { "-" } else { "" };
+        let padding = self
+            .width
+            .unwrap_or(0)
+            .saturating_sub(sign.len() + digits.len());
+        if self.zero_padding {
+            format!("{sign}{}{digits}", "0".repeat(padding))
+        } else {
+            format!("{}{sign}{digits}", " ".repeat(padding))
+        }
+    }
 }
 
 fn translate_function() {}
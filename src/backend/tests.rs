@@ -0,0 +1,51 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn format_integer() {
+    let spec = |zero_padding, width, radix| FormatSpec {
+        zero_padding,
+        width,
+        precision: None,
+        radix,
+    };
+    assert_eq!(spec(false, None, None).format_integer(-42), "-42");
+    assert_eq!(spec(false, Some(5), None).format_integer(-42), "  -42");
+    assert_eq!(spec(true, Some(5), None).format_integer(-42), "-0042");
+    assert_eq!(spec(true, Some(4), Some(16)).format_integer(255), "00ff");
+    assert_eq!(spec(false, None, Some(2)).format_integer(5), "101");
+    assert_eq!(spec(false, Some(1), Some(8)).format_integer(64), "100");
+}
+
+#[test]
+fn format_float() {
+    let spec = |zero_padding, width, precision| FormatSpec {
+        zero_padding,
+        width,
+        precision,
+        radix: None,
+    };
+    assert_eq!(spec(false, None, None).format_float(1.5), "1.5");
+    assert_eq!(spec(false, None, Some(3)).format_float(3.14159), "3.142");
+    assert_eq!(spec(true, Some(8), Some(2)).format_float(-2.5), "-0002.50");
+    assert_eq!(spec(false, Some(6), Some(0)).format_float(2.5), "     2");
+}
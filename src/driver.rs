@@ -0,0 +1,145 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/*!
+ * A single entry point to run the compiler up to a chosen stage, shared
+ * by the command line and embedding applications.
+ */
+
+mod tests;
+
+use std::path::PathBuf;
+
+use crate::frontend::{self, ast, FileSystem, SourceProvider, TokenClass};
+use crate::log;
+
+/**
+ * The stages the compiler can stop after.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /**
+     * Lexing of the root file.
+     */
+    Tokens,
+    /**
+     * Parsing of the root file.
+     */
+    Ast,
+    /**
+     * Translation of the root file and the files it imports.
+     */
+    Program,
+}
+
+/**
+ * The result of the last stage run.
+ */
+pub enum Artifact {
+    Tokens(Vec<(TokenClass, log::Pos)>),
+    Ast(ast::File),
+    Program(Box<frontend::Program>),
+}
+
+/**
+ * Configures and runs the compiler.
+ *
+ * By default, the files are read from the file system, diagnostics are
+ * printed to stderr, and all the stages are run.
+ */
+pub struct Driver<'a> {
+    root_file_path: PathBuf,
+    source_provider: &'a dyn SourceProvider,
+    sink: Option<&'a mut dyn log::DiagnosticSink>,
+    stop_after: Stage,
+    options: frontend::Options,
+}
+
+impl<'a> Driver<'a> {
+    pub fn new(root_file_path: impl Into<PathBuf>) -> Self {
+        Driver {
+            root_file_path: root_file_path.into(),
+            source_provider: &FileSystem,
+            sink: None,
+            stop_after: Stage::Program,
+            options: frontend::Options::default(),
+        }
+    }
+    pub fn source_provider(mut self, source_provider: &'a dyn SourceProvider) -> Self {
+        self.source_provider = source_provider;
+        self
+    }
+    pub fn sink(mut self, sink: &'a mut dyn log::DiagnosticSink) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+    pub fn stop_after(mut self, stage: Stage) -> Self {
+        self.stop_after = stage;
+        self
+    }
+    /**
+     * See [`frontend::Options::import_root`].
+     */
+    pub fn import_root(mut self, import_root: impl Into<PathBuf>) -> Self {
+        self.options.import_root = Some(import_root.into());
+        self
+    }
+    /**
+     * See [`frontend::Options::host_items`].
+     */
+    pub fn host_items(mut self, host_items: frontend::HostItems) -> Self {
+        self.options.host_items = host_items;
+        self
+    }
+    /**
+     * See [`frontend::Options::enabled_lints`].
+     */
+    pub fn enable_lint(mut self, lint: impl Into<String>) -> Self {
+        self.options.enabled_lints.push(lint.into());
+        self
+    }
+    /**
+     * See [`frontend::Options::max_nesting_depth`].
+     */
+    pub fn max_nesting_depth(mut self, max_nesting_depth: usize) -> Self {
+        self.options.max_nesting_depth = Some(max_nesting_depth);
+        self
+    }
+    /**
+     * Runs the stages up to the configured one. Returns `Err` if there are
+     * errors, which have been reported to the sink.
+     */
+    pub fn run(self) -> Result<Artifact, frontend::Errors> {
+        let mut stderr = log::HumanReadable::stderr();
+        let sink: &mut dyn log::DiagnosticSink = match self.sink {
+            Some(sink) => sink,
+            None => &mut stderr,
+        };
+        let path = &self.root_file_path;
+        match self.stop_after {
+            Stage::Tokens => {
+                frontend::read_tokens(path, self.source_provider, sink).map(Artifact::Tokens)
+            }
+            Stage::Ast => frontend::read_ast(path, self.source_provider, sink).map(Artifact::Ast),
+            Stage::Program => {
+                frontend::read_input_with_options(path, self.source_provider, sink, &self.options)
+                    .map(|program| Artifact::Program(Box::new(program)))
+            }
+        }
+    }
+}
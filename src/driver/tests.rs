@@ -0,0 +1,236 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use std::path::Path;
+
+use super::*;
+use crate::backend;
+use crate::frontend::InMemory;
+
+fn files() -> InMemory {
+    let mut files = InMemory::default();
+    files.insert("/project/lib.sysc", "func f()\nend\n");
+    files.insert("/project/main.sysc", "import lib\nfunc g()\nend\n");
+    files.insert("/project/broken.sysc", "x = (1]\n");
+    files
+}
+
+#[test]
+fn stop_after_each_stage() {
+    let files = files();
+    let mut sink = log::Collector::default();
+    let Ok(Artifact::Tokens(tokens)) = Driver::new("/project/main")
+        .source_provider(&files)
+        .sink(&mut sink)
+        .stop_after(Stage::Tokens)
+        .run()
+    else {
+        panic!();
+    };
+    assert_eq!(tokens.len(), 7);
+    let Ok(Artifact::Ast(ast)) = Driver::new("/project/main")
+        .source_provider(&files)
+        .sink(&mut sink)
+        .stop_after(Stage::Ast)
+        .run()
+    else {
+        panic!();
+    };
+    assert_eq!(ast.imports.len(), 1);
+    assert_eq!(ast.function_names.len(), 1);
+    let Ok(Artifact::Program(program)) = Driver::new("/project/main")
+        .source_provider(&files)
+        .sink(&mut sink)
+        .run()
+    else {
+        panic!();
+    };
+    assert_eq!(program.definitions.functions.len(), 2);
+    assert!(sink.diagnostics.is_empty());
+}
+
+#[test]
+fn errors_are_reported_to_sink() {
+    let files = files();
+    let mut sink = log::Collector::default();
+    assert!(Driver::new(Path::new("/project/broken"))
+        .source_provider(&files)
+        .sink(&mut sink)
+        .stop_after(Stage::Ast)
+        .run()
+        .is_err());
+    let [(path, diagnostic)] = &sink.diagnostics[..] else {
+        panic!("{:#?}", sink.diagnostics);
+    };
+    assert_eq!(path.as_deref(), Some(Path::new("/project/broken.sysc")));
+    assert_eq!(diagnostic.message, "Unexpected token at 1:7-1:7.");
+    // Lexing alone succeeds.
+    assert!(Driver::new("/project/broken")
+        .source_provider(&files)
+        .sink(&mut sink)
+        .stop_after(Stage::Tokens)
+        .run()
+        .is_ok());
+}
+
+#[test]
+fn imports_confined_to_root() {
+    let mut files = InMemory::default();
+    files.insert(
+        "/project/main.sysc",
+        "import lib(\"sub/lib\")\nimport secret(\"../etc/secret\")\n",
+    );
+    files.insert("/project/sub/lib.sysc", "");
+    files.insert("/etc/secret.sysc", "");
+    let mut sink = log::Collector::default();
+    assert!(Driver::new("/project/main")
+        .source_provider(&files)
+        .sink(&mut sink)
+        .import_root("/project")
+        .run()
+        .is_err());
+    let [(_, diagnostic)] = &sink.diagnostics[..] else {
+        panic!("{:#?}", sink.diagnostics);
+    };
+    assert_eq!(
+        diagnostic.message,
+        "Cannot import `/etc/secret.sysc` from outside `/project`."
+    );
+    // Without the option, the same import is allowed.
+    assert!(Driver::new("/project/main")
+        .source_provider(&files)
+        .sink(&mut log::Collector::default())
+        .run()
+        .is_ok());
+}
+
+#[test]
+fn missing_import_root() {
+    let mut files = InMemory::default();
+    files.insert("/project/main.sysc", "import lib\n");
+    files.insert("/project/lib.sysc", "");
+    let mut sink = log::Collector::default();
+    assert!(Driver::new("/project/main")
+        .source_provider(&files)
+        .sink(&mut sink)
+        .import_root("/projcet")
+        .run()
+        .is_err());
+    let [(None, diagnostic)] = &sink.diagnostics[..] else {
+        panic!("{:#?}", sink.diagnostics);
+    };
+    assert!(diagnostic
+        .message
+        .starts_with("ERROR: Cannot access import root `/projcet`."));
+}
+
+#[test]
+fn host_items() {
+    let mut files = InMemory::default();
+    files.insert("/project/lib.sysc", "app.version\n");
+    files.insert("/project/main.sysc", "import lib\nversion\napp.log\n");
+    let unit = backend::TyBuilder::Application {
+        constructor: Box::new(backend::TyBuilder::Constructor(
+            backend::TyConstructor::Tuple,
+        )),
+        arguments: vec![],
+    };
+    let log_ty = backend::FunctionTy {
+        num_ty_parameters: 0,
+        parameters_ty: vec![backend::TyBuilder::Constructor(
+            backend::TyConstructor::Integer,
+        )],
+        return_ty: unit,
+    };
+    let host_items = frontend::HostItems::new()
+        .variable(
+            "version",
+            backend::TyBuilder::Constructor(backend::TyConstructor::Integer),
+        )
+        .variable(
+            "app.version",
+            backend::TyBuilder::Constructor(backend::TyConstructor::Integer),
+        )
+        .function("app.log", log_ty.clone())
+        .function("app.log", log_ty);
+    let mut sink = log::Collector::default();
+    let Ok(Artifact::Program(program)) = Driver::new("/project/main")
+        .source_provider(&files)
+        .sink(&mut sink)
+        .host_items(host_items)
+        .run()
+    else {
+        panic!("{:#?}", sink.diagnostics);
+    };
+    let definitions = &program.definitions;
+    assert_eq!(definitions.host_variables[1].0, "app.version");
+    assert_eq!(definitions.host_functions.len(), 2);
+    // The namespace `app` is given the index 0, before the files.
+    assert!(matches!(program.items[2]["app"], frontend::Item::Import(0)));
+    let frontend::Item::Function(candidates) = &program.items[0]["log"] else {
+        panic!();
+    };
+    assert!(candidates == &[backend::Function::Host(0), backend::Function::Host(1)]);
+    let is_host_variable = |statement: &backend::Statement, index| {
+        let backend::Statement::Expr(backend::Expression::Function { calls, .. }) = statement
+        else {
+            return false;
+        };
+        matches!(
+            calls[0].arguments[..],
+            [backend::Expression::HostVariable(i)] if i == index
+        )
+    };
+    assert!(is_host_variable(&program.global_statements[1][0], 1));
+    assert!(is_host_variable(&program.global_statements[2][0], 0));
+}
+
+#[test]
+fn shadowing_is_opt_in() {
+    let mut files = InMemory::default();
+    files.insert(
+        "/project/main.sysc",
+        "func f(a: int)\n    var a\n    -- syscraws:allow(shadowing)\n    var a\nend\n",
+    );
+    let mut sink = log::Collector::default();
+    assert!(Driver::new("/project/main")
+        .source_provider(&files)
+        .sink(&mut sink)
+        .run()
+        .is_ok());
+    assert!(sink.diagnostics.is_empty());
+    assert!(Driver::new("/project/main")
+        .source_provider(&files)
+        .sink(&mut sink)
+        .enable_lint("shadowing")
+        .run()
+        .is_ok());
+    let [(_, diagnostic)] = &sink.diagnostics[..] else {
+        panic!("{:#?}", sink.diagnostics);
+    };
+    assert_eq!(
+        diagnostic.message,
+        "Warning: `a` at 2:9-2:9 shadows the variable declared at 1:8-1:8."
+    );
+    assert_eq!(
+        diagnostic.notes[0].message,
+        "The shadowed variable is declared here."
+    );
+}
@@ -0,0 +1,143 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/*!
+ * Application of the edits suggested by diagnostics, for `syscraws fix`.
+ */
+
+mod tests;
+
+use std::path::Path;
+
+use crate::frontend::{self, SourceProvider};
+use crate::log;
+
+/**
+ * The maximum number of rounds in [`fix`]. Parsing stops at the first
+ * error, so each round may reveal the next one.
+ */
+const MAX_ROUNDS: usize = 100;
+
+/**
+ * Translates `source` as the root file and applies the edits suggested for
+ * it, again and again until no more are suggested. The files it imports are
+ * read through `source_provider`, relative to `root_file_path`, and are
+ * left as they are.
+ */
+pub fn fix(source: &str, root_file_path: &Path, source_provider: &dyn SourceProvider) -> String {
+    let root_file_path = source_provider
+        .canonicalize(root_file_path)
+        .unwrap_or_else(|_| root_file_path.to_path_buf());
+    let mut source = source.to_owned();
+    for _ in 0..MAX_ROUNDS {
+        let mut sink = log::Collector::default();
+        let _ =
+            frontend::read_input_from_string(&source, &root_file_path, source_provider, &mut sink);
+        let edits = sink
+            .diagnostics
+            .into_iter()
+            .filter(|(path, _)| path.as_ref() == Some(&root_file_path))
+            .flat_map(|(_, diagnostic)| diagnostic.edits)
+            .collect();
+        let fixed = apply_edits(&source, edits);
+        if fixed == source {
+            break;
+        }
+        source = fixed;
+    }
+    source
+}
+
+/**
+ * Applies `edits` to `source`. An edit overlapping one before it is
+ * skipped, to be suggested again in the next round if still needed.
+ */
+pub fn apply_edits(source: &str, mut edits: Vec<log::Edit>) -> String {
+    let mut line_starts = vec![0];
+    line_starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+    let offset = |index: log::Index| line_starts[index.line] + index.column;
+    edits.sort_by_key(|edit| (offset(edit.pos.start), offset(edit.pos.end)));
+    let mut fixed = String::new();
+    let mut copied = 0;
+    for edit in edits {
+        let start = offset(edit.pos.start);
+        if start < copied {
+            continue;
+        }
+        fixed.push_str(&source[copied..start]);
+        fixed.push_str(&edit.replacement);
+        copied = offset(edit.pos.end);
+    }
+    fixed.push_str(&source[copied..]);
+    fixed
+}
+
+/**
+ * Describes the lines changed from `old` to `new`, in the unified format
+ * without context lines.
+ */
+pub fn diff(old: &str, new: &str) -> String {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+    // `common[i][j]`: the length of the longest common subsequence of
+    // `old[i..]` and `new[j..]`.
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut output = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+            continue;
+        }
+        // A hunk of removed and added lines.
+        let (old_start, new_start) = (i, j);
+        while i < old.len() || j < new.len() {
+            if i < old.len() && j < new.len() && old[i] == new[j] {
+                break;
+            } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        output.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            i - old_start,
+            new_start + 1,
+            j - new_start
+        ));
+        for line in &old[old_start..i] {
+            output.push_str(&format!("-{line}\n"));
+        }
+        for line in &new[new_start..j] {
+            output.push_str(&format!("+{line}\n"));
+        }
+    }
+    output
+}
@@ -0,0 +1,64 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use std::path::Path;
+
+use super::*;
+use crate::frontend::InMemory;
+use crate::log::{Edit, Index, Pos};
+
+#[test]
+fn fix_parse_errors() {
+    let source = "\
+var x
+while x = x
+    x
+";
+    let fixed = fix(source, Path::new("main.sysc"), &InMemory::default());
+    assert_eq!(fixed, "var x\nwhile x == x\n    x\nend\n");
+    assert_eq!(
+        diff(source, &fixed),
+        "@@ -2,1 +2,1 @@\n-while x = x\n+while x == x\n@@ -4,0 +4,1 @@\n+end\n"
+    );
+}
+
+#[test]
+fn overlapping_edits() {
+    let edit = |line, start, end, replacement: &str| Edit {
+        pos: Pos {
+            start: Index {
+                line,
+                column: start,
+            },
+            end: Index { line, column: end },
+        },
+        replacement: replacement.to_owned(),
+    };
+    let fixed = apply_edits(
+        "abc\ndef\n",
+        vec![
+            edit(1, 1, 2, "E"),
+            edit(0, 0, 2, "AB"),
+            edit(0, 1, 3, "x"),
+            edit(1, 3, 3, "!"),
+        ],
+    );
+    assert_eq!(fixed, "ABc\ndEf!\n");
+}
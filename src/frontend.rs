@@ -16,54 +16,366 @@
  * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
  */
 
-mod ast;
+pub mod ast;
 mod chars_peekable;
+mod constant;
+mod doc;
+mod host;
+mod source_provider;
+mod tests;
+mod timings;
 
 use std::collections::{HashMap, HashSet};
-use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use crate::{backend, log};
+pub use ast::TokenClass;
 use chars_peekable::CharsPeekable;
+pub use doc::{
+    fuzzy_match, outline, read_documentation, render_html, render_markdown, signature_help,
+    DocumentedItem, ItemKind, ModuleDocumentation, OutlineItem, SignatureHelp,
+};
+pub use host::HostItems;
+use source_provider::CachedCanonicalization;
+pub use source_provider::{FileSystem, InMemory, SourceProvider};
+pub use timings::{write_timings, write_timings_json, FileTimings};
+
+/**
+ * The result of reading the input files.
+ */
+pub struct Program {
+    /**
+     * The files read, in the order of their indices.
+     */
+    pub files: Vec<log::File>,
+    /**
+     * The items available by name in each file, which other files can also
+     * refer to after importing it.
+     */
+    pub items: Vec<HashMap<String, Item>>,
+    /**
+     * The position where each item in `items` is defined, except imports
+     * and host items: the keyword `struct`, `enum`, `func` or `const`, or the
+     * name of a global variable.
+     */
+    pub item_positions: Vec<HashMap<String, log::Pos>>,
+    /**
+     * The top-level statements of each file other than definitions.
+     */
+    pub global_statements: Vec<Vec<backend::Statement>>,
+    /**
+     * The file index and the position of the keyword `struct` of each
+     * structure in `definitions.structures`.
+     */
+    pub structure_positions: Vec<(usize, log::Pos)>,
+    /**
+     * The file index and the position of the keyword `func` or `method` of
+     * each function in `definitions.functions`.
+     */
+    pub function_positions: Vec<(usize, log::Pos)>,
+    pub definitions: backend::Definitions,
+    /**
+     * The measurements of each file, in the order the files were finished.
+     */
+    pub timings: Vec<FileTimings>,
+}
+
+/**
+ * Returned when the input has errors, which have been reported to the sink.
+ */
+#[derive(Debug, Default)]
+pub struct Errors {
+    pub num_errors: u32,
+    /**
+     * The measurements of the files read before the errors stopped the
+     * translation, which are empty if nothing is translated. See
+     * [`Program::timings`].
+     */
+    pub timings: Vec<FileTimings>,
+}
 
 /**
  * Reads the file specified by `root_file_path` and any other files it
- * imports, and passes them to `backend`.
+ * imports, and translates them for `backend`.
+ */
+pub fn read_input(root_file_path: &Path) -> Result<Program, Errors> {
+    read_input_with(
+        root_file_path,
+        &FileSystem,
+        &mut log::HumanReadable::stderr(),
+    )
+}
+
+/**
+ * Same as [`read_input`], except that the files are read through
+ * `source_provider` instead of the file system, and the diagnostics are
+ * passed to `sink` instead of being printed to stderr.
+ */
+pub fn read_input_with(
+    root_file_path: &Path,
+    source_provider: &dyn SourceProvider,
+    sink: &mut dyn log::DiagnosticSink,
+) -> Result<Program, Errors> {
+    read_input_with_options(root_file_path, source_provider, sink, &Options::default())
+}
+
+/**
+ * Settings of [`read_input_with_options`].
+ */
+#[derive(Clone, Default)]
+pub struct Options {
+    /**
+     * If set, imports of files outside this directory are rejected. The
+     * check is done after symbolic links and `..` are resolved, so that
+     * untrusted sources cannot read other files.
+     */
+    pub import_root: Option<PathBuf>,
+    /**
+     * Global variables and functions provided by the application.
+     */
+    pub host_items: HostItems,
+    /**
+     * The lints in [`log::OPT_IN_LINTS`] to enable, such as `shadowing`.
+     */
+    pub enabled_lints: Vec<String>,
+    /**
+     * How deeply blocks and terms may be nested in each file. If `None`,
+     * [`ast::DEFAULT_MAX_NESTING_DEPTH`] is used.
+     */
+    pub max_nesting_depth: Option<usize>,
+}
+
+/**
+ * Same as [`read_input_with`], with settings.
  */
-pub fn read_input(root_file_path: &Path) -> Result<backend::Definitions, ()> {
+pub fn read_input_with_options(
+    root_file_path: &Path,
+    source_provider: &dyn SourceProvider,
+    sink: &mut dyn log::DiagnosticSink,
+    options: &Options,
+) -> Result<Program, Errors> {
     let root_file_path = root_file_path.with_extension("sysc");
-    let root_file_path = match root_file_path.canonicalize() {
+    let root_file_path = match source_provider.canonicalize(&root_file_path) {
         Ok(path) => path,
         Err(err) => {
-            log::root_file_not_found(&root_file_path, err);
-            return Err(());
+            sink.emit(None, log::root_file_not_found(&root_file_path, err));
+            return Err(Errors {
+                num_errors: 1,
+                timings: Vec::new(),
+            });
         }
     };
-    let mut reader = Reader {
-        num_structures: 0,
-        num_functions: 0,
-        definitions: backend::Definitions::builtin(),
-        exported_items: Vec::new(),
-        files: Vec::new(),
-        file_indices: HashMap::new(),
-        import_chain: HashSet::from([root_file_path.clone()]),
-        num_errors: 0,
+    let import_root = match &options.import_root {
+        Some(import_root) => match source_provider.canonicalize(import_root) {
+            Ok(import_root) => Some(import_root),
+            Err(err) => {
+                // Every import would be rejected, so nothing is read.
+                sink.emit(None, log::cannot_access_import_root(import_root, err));
+                return Err(Errors {
+                    num_errors: 1,
+                    timings: Vec::new(),
+                });
+            }
+        },
+        None => None,
     };
+    let mut reader = Reader::new(root_file_path.clone(), source_provider, sink);
+    reader.import_root = import_root;
+    reader.inject_host_items(&options.host_items);
+    for lint in &options.enabled_lints {
+        reader.logger.enable_lint(lint);
+    }
+    if let Some(max_nesting_depth) = options.max_nesting_depth {
+        reader.max_nesting_depth = max_nesting_depth;
+    }
     if let Err(err) = reader.read_file(&root_file_path) {
-        log::cannot_read_root_file(&root_file_path, err);
-        reader.num_errors += 1;
+        reader
+            .logger
+            .error_without_file(log::cannot_read_root_file(&root_file_path, err));
     }
-    if reader.num_errors > 0 {
-        log::aborting(reader.num_errors);
-        return Err(());
+    reader.finish()
+}
+
+/**
+ * Translates `source` as the content of the root file, without reading it
+ * from anywhere. `root_file_path` is used in diagnostics and as the base
+ * of relative imports, which are read through `source_provider`.
+ */
+pub fn read_input_from_string(
+    source: &str,
+    root_file_path: &Path,
+    source_provider: &dyn SourceProvider,
+    sink: &mut dyn log::DiagnosticSink,
+) -> Result<Program, Errors> {
+    // The root file need not exist, but when it does, its canonical path lets
+    // circular imports back to it be detected.
+    let root_file_path = source_provider
+        .canonicalize(root_file_path)
+        .unwrap_or_else(|_| root_file_path.to_path_buf());
+    let mut reader = Reader::new(root_file_path.clone(), source_provider, sink);
+    reader.read_content(&root_file_path, source.to_owned(), Duration::ZERO);
+    reader.finish()
+}
+
+/**
+ * Reads the file specified by `root_file_path`, without the files it
+ * imports, and classifies its tokens as [`highlight`] does.
+ */
+pub fn read_tokens(
+    root_file_path: &Path,
+    source_provider: &dyn SourceProvider,
+    sink: &mut dyn log::DiagnosticSink,
+) -> Result<Vec<(TokenClass, log::Pos)>, Errors> {
+    read_single_file(root_file_path, source_provider, sink, ast::classify_tokens)
+}
+
+/**
+ * Reads the file specified by `root_file_path`, without the files it
+ * imports, and parses it.
+ */
+pub fn read_ast(
+    root_file_path: &Path,
+    source_provider: &dyn SourceProvider,
+    sink: &mut dyn log::DiagnosticSink,
+) -> Result<ast::File, Errors> {
+    read_single_file(root_file_path, source_provider, sink, ast::parse_file)
+}
+
+fn read_single_file<T>(
+    root_file_path: &Path,
+    source_provider: &dyn SourceProvider,
+    sink: &mut dyn log::DiagnosticSink,
+    read: impl FnOnce(&mut CharsPeekable) -> Result<T, log::ParseError>,
+) -> Result<T, Errors> {
+    let path = root_file_path.with_extension("sysc");
+    let mut logger = log::Logger::new(sink);
+    let content = match source_provider.read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) => {
+            logger.error_without_file(log::cannot_read_root_file(&path, err));
+            logger.aborting();
+            return Err(Errors {
+                num_errors: logger.num_errors(),
+                timings: Vec::new(),
+            });
+        }
+    };
+    let mut chars_peekable = CharsPeekable::new(&content);
+    match read(&mut chars_peekable) {
+        Ok(value) => Ok(value),
+        Err(err) => {
+            let file = log::File {
+                path,
+                lines: chars_peekable.lines(),
+                content,
+                allowed_lints: Vec::new(),
+            };
+            logger.error(&file, log::parse_error(err, &file));
+            logger.aborting();
+            Err(Errors {
+                num_errors: logger.num_errors(),
+                timings: Vec::new(),
+            })
+        }
+    }
+}
+
+/**
+ * Reads all the tokens in `source`. The entry point for fuzzing the lexer.
+ */
+pub fn tokenize(source: &str) -> Result<(), log::ParseError> {
+    let mut chars_peekable = CharsPeekable::new(source);
+    ast::read_all_tokens(&mut chars_peekable)
+}
+
+/**
+ * Classifies the tokens and comments in `source` for syntax highlighting.
+ * A string literal is a single token, including its placeholders.
+ */
+pub fn highlight(source: &str) -> Result<Vec<(TokenClass, log::Pos)>, log::ParseError> {
+    let mut chars_peekable = CharsPeekable::new(source);
+    ast::classify_tokens(&mut chars_peekable)
+}
+
+/**
+ * Lists the blocks in `source`, such as the bodies of functions, as pairs
+ * of the zero-based lines of the start and `end`, for folding in editors.
+ */
+pub fn folding_ranges(source: &str) -> Result<Vec<(usize, usize)>, log::ParseError> {
+    let mut chars_peekable = CharsPeekable::new(source);
+    ast::parse_file(&mut chars_peekable).map(|file| file.blocks)
+}
+
+/**
+ * Finds where the item referred to at `cursor` in the file `file_index` of
+ * `program` is defined. A reference `module.member` leads to the file
+ * imported as `module`. Returns the index of the file and the position in
+ * [`Program::item_positions`].
+ */
+pub fn find_definition(
+    program: &Program,
+    file_index: usize,
+    cursor: log::Index,
+) -> Option<(usize, log::Pos)> {
+    let source = &program.files[file_index].content;
+    let names = ast::find_reference(&mut CharsPeekable::new(source), cursor).ok()??;
+    let mut file_index = file_index;
+    let (last_name, module_names) = names.split_last().unwrap();
+    for name in module_names {
+        let Some(Item::Import(index)) = program.items[file_index].get(name) else {
+            return None;
+        };
+        file_index = *index;
+    }
+    let pos = program.item_positions[file_index].get(last_name)?;
+    Some((file_index, pos.clone()))
+}
+
+/**
+ * Suggests inserting `end` when a line break has just been typed after
+ * `line` of `source`, if `line` opens a block such as `while x` and
+ * `source` does not parse without another `end`. The `end` goes where the
+ * indentation says the block ends.
+ */
+pub fn end_on_new_line(source: &str, line: usize) -> Option<log::Edit> {
+    let mut chars_peekable = CharsPeekable::new(source);
+    let result = ast::parse_file(&mut chars_peekable);
+    if !matches!(
+        result,
+        Err(log::ParseError::UnclosedBlock { .. } | log::ParseError::UnexpectedTokenInBlock { .. })
+    ) {
+        return None;
+    }
+    let file = log::File {
+        path: PathBuf::new(),
+        lines: chars_peekable.lines(),
+        content: source.to_owned(),
+        allowed_lints: Vec::new(),
+    };
+    match file.missing_end(line)? {
+        (open_line, end_line) if open_line == line => Some(file.insert_end(line, end_line)),
+        _ => None,
     }
-    Ok(reader.definitions)
+}
+
+/**
+ * Parses `source` as the content of a file, without reading the files it
+ * imports. The entry point for fuzzing the parser.
+ */
+pub fn parse(source: &str) -> Result<(), log::ParseError> {
+    let mut chars_peekable = CharsPeekable::new(source);
+    ast::parse_file(&mut chars_peekable).map(|_| ())
 }
 
 /**
  * A structure used in [`read_input`].
  */
-struct Reader {
+struct Reader<'provider, 'sink> {
+    /**
+     * Where the files are read from.
+     */
+    source_provider: CachedCanonicalization<'provider>,
     /**
      * Total number of structures defined in all files. Used and updated by
      * [`register_structure_name`].
@@ -82,10 +394,30 @@ struct Reader {
      * Items exported from each file.
      */
     exported_items: Vec<HashMap<String, Item>>,
+    /**
+     * See [`Program::item_positions`].
+     */
+    item_positions: Vec<HashMap<String, log::Pos>>,
     /**
      * Debug information of each file.
      */
     files: Vec<log::File>,
+    /**
+     * See [`Program::global_statements`].
+     */
+    global_statements: Vec<Vec<backend::Statement>>,
+    /**
+     * See [`Program::structure_positions`].
+     */
+    structure_positions: Vec<(usize, log::Pos)>,
+    /**
+     * See [`Program::function_positions`].
+     */
+    function_positions: Vec<(usize, log::Pos)>,
+    /**
+     * See [`Program::timings`].
+     */
+    timings: Vec<FileTimings>,
     /**
      * Used in [`Reader::read_file`] to avoid reading the same file multiple
      * times.
@@ -96,12 +428,90 @@ struct Reader {
      */
     import_chain: HashSet<PathBuf>,
     /**
-     * Number of errors while reading files.
+     * See [`Options::import_root`].
+     */
+    import_root: Option<PathBuf>,
+    /**
+     * Items added to every file by [`Reader::inject_host_items`].
+     */
+    host_items: HashMap<String, Item>,
+    /**
+     * See [`Options::max_nesting_depth`].
      */
-    num_errors: u32,
+    max_nesting_depth: usize,
+    /**
+     * Methods defined in all files, together with the builtin ones. Used for
+     * method calls and operators.
+     */
+    methods: HashMap<String, Vec<backend::Function>>,
+    /**
+     * The structure index and the field index of each field of the
+     * structures defined in all files, by the name of the field. Used for
+     * field accesses.
+     */
+    fields: HashMap<String, Vec<(usize, usize)>>,
+    /**
+     * Where the errors while reading files are reported.
+     */
+    logger: log::Logger<'sink>,
 }
 
-impl Reader {
+impl<'provider, 'sink> Reader<'provider, 'sink> {
+    fn new(
+        root_file_path: PathBuf,
+        source_provider: &'provider dyn SourceProvider,
+        sink: &'sink mut dyn log::DiagnosticSink,
+    ) -> Self {
+        Reader {
+            source_provider: CachedCanonicalization::new(source_provider),
+            num_structures: 0,
+            num_functions: 0,
+            definitions: backend::Definitions::builtin(),
+            exported_items: Vec::new(),
+            item_positions: Vec::new(),
+            files: Vec::new(),
+            global_statements: Vec::new(),
+            structure_positions: Vec::new(),
+            function_positions: Vec::new(),
+            timings: Vec::new(),
+            file_indices: HashMap::new(),
+            import_chain: HashSet::from([root_file_path]),
+            import_root: None,
+            host_items: HashMap::new(),
+            max_nesting_depth: ast::DEFAULT_MAX_NESTING_DEPTH,
+            methods: builtin_methods(),
+            fields: HashMap::new(),
+            logger: log::Logger::new(sink),
+        }
+    }
+
+    /**
+     * Returns the results, or reports the number of errors if any.
+     */
+    fn finish(mut self) -> Result<Program, Errors> {
+        if self.logger.num_errors() > 0 {
+            self.logger.aborting();
+            return Err(Errors {
+                num_errors: self.logger.num_errors(),
+                timings: self.timings,
+            });
+        }
+        self.definitions.eliminate_dead_code();
+        for statements in &mut self.global_statements {
+            backend::eliminate_dead_code(statements);
+        }
+        Ok(Program {
+            files: self.files,
+            items: self.exported_items,
+            item_positions: self.item_positions,
+            global_statements: self.global_statements,
+            structure_positions: self.structure_positions,
+            function_positions: self.function_positions,
+            definitions: self.definitions,
+            timings: self.timings,
+        })
+    }
+
     fn read_file(&mut self, path: &Path) -> Result<usize, std::io::Error> {
         if let Some(&index) = self.file_indices.get(path) {
             // The file specified by `path` was already read.
@@ -109,19 +519,34 @@ impl Reader {
             // this is not circular imports but diamond imports.
             return Ok(index);
         }
-        let mut file = std::fs::File::open(path)?;
-        let mut content = String::new();
-        file.read_to_string(&mut content)?;
+        let start = Instant::now();
+        let content = self.source_provider.read_to_string(path)?;
+        let read_time = start.elapsed();
+        Ok(self.read_content(path, content, read_time))
+    }
+
+    fn read_content(&mut self, path: &Path, content: String, read_time: Duration) -> usize {
+        let start = Instant::now();
         let mut chars_peekable = CharsPeekable::new(&content);
-        let result = ast::parse_file(&mut chars_peekable);
-        let file = log::File {
+        let result =
+            ast::parse_file_with_max_nesting_depth(&mut chars_peekable, self.max_nesting_depth);
+        let parse_time = start.elapsed();
+        let num_tokens = chars_peekable.num_tokens();
+        let mut file = log::File {
             path: path.to_path_buf(),
             lines: chars_peekable.lines(),
             content,
+            allowed_lints: Vec::new(),
         };
         match result {
-            Ok(ast) => {
-                let mut named_items = HashMap::new();
+            Ok(mut ast) => {
+                file.allowed_lints = std::mem::take(&mut ast.allowed_lints);
+                let num_items = ast.imports.len()
+                    + ast.enumeration_definitions.len()
+                    + ast.constant_definitions.len()
+                    + ast.top_level_statements.len();
+                let mut item_positions = item_positions(&ast);
+                let mut named_items = self.host_items.clone();
                 for import in ast.imports {
                     if let Ok((name, index)) =
                         self.import_file(import, path.parent().unwrap(), &file)
@@ -129,13 +554,29 @@ impl Reader {
                         named_items.insert(name, Item::Import(index));
                     }
                 }
+                // All the imported files have been given indices, so this file gets the next.
+                let file_index = self.file_indices.len();
+                // The time spent on the imported files is excluded.
+                let start = Instant::now();
+                let mut structure_positions = ast
+                    .structure_names
+                    .iter()
+                    .map(|name| name.keyword_struct_pos.clone())
+                    .collect::<Vec<_>>()
+                    .into_iter();
+                let mut function_positions = ast
+                    .function_names
+                    .iter()
+                    .map(|name| name.keyword_func_pos.clone())
+                    .collect::<Vec<_>>()
+                    .into_iter();
                 for name in ast.structure_names {
                     register_structure_name(
                         name,
                         &mut self.num_structures,
                         &mut named_items,
                         &file,
-                        &mut self.num_errors,
+                        &mut self.logger,
                     );
                 }
                 for name in ast.function_names {
@@ -143,228 +584,407 @@ impl Reader {
                         name,
                         &mut self.num_functions,
                         &mut named_items,
+                        &mut self.methods,
+                        &file,
+                        &mut self.logger,
+                    );
+                }
+                // Enumerations are translated before functions and global statements,
+                // since their variants are used as functions.
+                let first_enumeration_index = self.definitions.enumerations.len();
+                for (i, definition) in ast.enumeration_definitions.iter().enumerate() {
+                    register_enumeration_name(
+                        definition,
+                        first_enumeration_index + i,
+                        &mut named_items,
+                        &file,
+                        &mut self.logger,
+                    );
+                }
+                for definition in ast.enumeration_definitions {
+                    let new_index = self.definitions.enumerations.len();
+                    let (kind, enumeration) = translate_enumeration_definition(
+                        definition,
+                        new_index,
+                        &mut named_items,
+                        &self.exported_items,
+                        &file,
+                        &mut self.logger,
+                    );
+                    self.definitions
+                        .tys_kind
+                        .insert(backend::TyConstructor::Enumeration(new_index), kind);
+                    self.definitions.enumerations.push(enumeration);
+                }
+                // Constants are evaluated in order, so that each can refer to the ones
+                // defined before it.
+                for definition in ast.constant_definitions {
+                    register_constant(
+                        definition,
+                        &mut named_items,
+                        &self.exported_items,
                         &file,
-                        &mut self.num_errors,
+                        &mut self.logger,
                     );
                 }
-                let mut global_variables = HashMap::new();
-                let mut num_global_variables = 0;
+                let mut global_variables = Variables {
+                    first_index: self.definitions.num_global_variables,
+                    ..Variables::default()
+                };
                 let mut global_scope = Vec::new();
                 let global_ty_parameters = HashMap::new();
-                let mut global_statements = Ok(Vec::new());
+                let mut global_statements = Vec::new();
                 for statement in ast.top_level_statements {
                     match statement {
                         ast::TopLevelStatement::StructureDefinition(structure_definition) => {
-                            let (kind, definition) = translate_structure_definition(
+                            let (kind, definition, fields_name) = translate_structure_definition(
                                 structure_definition,
                                 &mut named_items,
                                 &self.exported_items,
                                 &file,
-                                &mut self.num_errors,
+                                &mut self.logger,
                             );
                             let new_index = self.definitions.structures.len();
+                            for (field_index, name) in fields_name.into_iter().enumerate() {
+                                self.fields
+                                    .entry(name)
+                                    .or_default()
+                                    .push((new_index, field_index));
+                            }
                             self.definitions
                                 .tys_kind
                                 .insert(backend::TyConstructor::Structure(new_index), kind);
                             self.definitions.structures.push(definition);
+                            self.structure_positions
+                                .push((file_index, structure_positions.next().unwrap()));
                         }
                         ast::TopLevelStatement::FunctionDefinition(function_definition) => {
+                            let pos = function_positions.next().unwrap();
                             if let Some((ty, definition)) = translate_function_definition(
                                 function_definition,
-                                &global_variables,
-                                &named_items,
-                                &self.exported_items,
-                                &file,
-                                &mut self.num_errors,
+                                &global_variables.indices,
+                                &mut Context {
+                                    named_items: &named_items,
+                                    methods: &self.methods,
+                                    fields: &self.fields,
+                                    ty_parameters: &global_ty_parameters,
+                                    exported_items: &self.exported_items,
+                                    file: &file,
+                                    logger: &mut self.logger,
+                                },
                             ) {
                                 self.definitions.functions.push((ty, definition));
+                                self.function_positions.push((file_index, pos));
+                            }
+                        }
+                        ast::TopLevelStatement::TestDefinition(test_definition) => {
+                            let is_benchmark = test_definition.is_benchmark;
+                            if let Some(test) = translate_test_definition(
+                                test_definition,
+                                &global_variables.indices,
+                                &mut Context {
+                                    named_items: &named_items,
+                                    methods: &self.methods,
+                                    fields: &self.fields,
+                                    ty_parameters: &global_ty_parameters,
+                                    exported_items: &self.exported_items,
+                                    file: &file,
+                                    logger: &mut self.logger,
+                                },
+                            ) {
+                                if is_benchmark {
+                                    self.definitions.benchmarks.push(test);
+                                } else {
+                                    self.definitions.tests.push(test);
+                                }
                             }
                         }
                         ast::TopLevelStatement::Statement(statement) => {
-                            match translate_statement(
+                            // A statement with errors is left out, and the rest are still
+                            // translated.
+                            if let Some(stmt) = translate_statement(
                                 statement,
                                 &mut global_variables,
-                                &mut num_global_variables,
                                 &mut global_scope,
-                                &global_ty_parameters,
                                 None,
-                                &named_items,
-                                &self.exported_items,
-                                &file,
-                                &mut self.num_errors,
+                                false,
+                                false,
+                                &mut Context {
+                                    named_items: &named_items,
+                                    methods: &self.methods,
+                                    fields: &self.fields,
+                                    ty_parameters: &global_ty_parameters,
+                                    exported_items: &self.exported_items,
+                                    file: &file,
+                                    logger: &mut self.logger,
+                                },
                             ) {
-                                Some(stmt) => {
-                                    if let Some(stmt) = stmt {
-                                        if let Ok(global_statements) = &mut global_statements {
-                                            global_statements.push(stmt);
-                                        }
-                                    }
-                                }
-                                None => global_statements = Err(()),
+                                global_statements.extend(stmt);
                             }
                         }
                     }
                 }
-                for (name, index) in global_variables {
+                for (name, index) in global_variables.indices {
+                    item_positions.entry(name.clone()).or_insert_with(|| {
+                        global_variables.pos[index - global_variables.first_index].clone()
+                    });
                     named_items.insert(name, Item::GlobalVariable(index));
                 }
+                self.definitions.num_global_variables += global_variables.pos.len();
+                self.global_statements.push(global_statements);
                 self.exported_items.push(named_items);
+                self.item_positions.push(item_positions);
+                self.timings.push(FileTimings {
+                    path: path.to_path_buf(),
+                    read: read_time,
+                    parse: parse_time,
+                    translate: start.elapsed(),
+                    bytes: file.content.len(),
+                    lines: file.content.lines().count(),
+                    tokens: num_tokens,
+                    items: num_items,
+                });
                 self.files.push(file);
             }
             Err(err) => {
-                err.eprint(&file);
-                self.num_errors += 1;
+                self.logger.error(&file, log::parse_error(err, &file));
+                self.timings.push(FileTimings {
+                    path: path.to_path_buf(),
+                    read: read_time,
+                    parse: parse_time,
+                    translate: Duration::ZERO,
+                    bytes: file.content.len(),
+                    lines: file.content.lines().count(),
+                    tokens: num_tokens,
+                    items: 0,
+                });
             }
         };
         let new_index = self.file_indices.len();
         self.file_indices.insert(path.to_path_buf(), new_index);
-        Ok(new_index)
+        new_index
     }
 
     fn import_file(
         &mut self,
-        ast::Import {
-            keyword_import_pos,
-            target,
-            extra_tokens_pos,
-        }: ast::Import,
+        import: ast::Import,
         parent_directory: &Path,
         file: &log::File,
     ) -> Result<(String, usize), ()> {
-        let Some(target) = target else {
-            eprintln!("Missing import target after `import` at {keyword_import_pos}.");
-            file.quote_pos(keyword_import_pos);
-            self.num_errors += 1;
-            return Err(());
-        };
-        let (name, path) = match target.term {
-            ast::Term::Identifier(name) => {
-                let path = parent_directory.join(&name);
-                (name, path)
-            }
-            ast::Term::FunctionCall {
-                function,
-                arguments,
-            } => {
-                let name = match function.term {
-                    ast::Term::Identifier(name) => name,
-                    _ => {
-                        eprintln!("Invalid import target at {}.", target.pos);
-                        file.quote_pos(target.pos);
-                        self.num_errors += 1;
-                        return Err(());
-                    }
-                };
-                let path = match arguments.into_iter().next() {
-                    Some(ast::ListElement::NonEmpty(argument)) => match argument.term {
-                        ast::Term::StringLiteral(components) => {
-                            let mut path = String::new();
-                            for component in components {
-                                match component {
-                                    ast::StringLiteralComponent::PlaceHolder { .. } => {
-                                        eprintln!("Import path must not contain a placeholder.");
-                                        file.quote_pos(argument.pos);
-                                        self.num_errors += 1;
-                                        return Err(());
-                                    }
-                                    ast::StringLiteralComponent::String(value) => {
-                                        path.push_str(&value);
-                                    }
-                                }
-                            }
-                            parent_directory.join(&path)
-                        }
-                        _ => {
-                            eprintln!("Invalid import target at {}.", target.pos);
-                            file.quote_pos(target.pos);
-                            self.num_errors += 1;
-                            return Err(());
-                        }
-                    },
-                    Some(ast::ListElement::Empty { comma_pos }) => {
-                        eprintln!("Empty argument before comma at {comma_pos}.");
-                        file.quote_pos(comma_pos);
-                        self.num_errors += 1;
-                        return Err(());
-                    }
-                    None => {
-                        eprintln!("Missing import path at {}.", target.pos);
-                        file.quote_pos(target.pos);
-                        self.num_errors += 1;
-                        return Err(());
-                    }
-                };
-                (name, path)
-            }
-            _ => {
-                eprintln!("Invalid import target at {}.", target.pos);
-                file.quote_pos(target.pos);
-                self.num_errors += 1;
+        let keyword_import_pos = import.keyword_import_pos.clone();
+        let (name, path) = resolve_import(
+            import,
+            parent_directory,
+            &self.source_provider,
+            file,
+            &mut self.logger,
+        )?;
+        if let Some(import_root) = &self.import_root {
+            if !path.starts_with(import_root) {
+                self.logger.error(
+                    file,
+                    log::Diagnostic::new(format!(
+                        "Cannot import `{}` from outside `{}`.",
+                        path.display(),
+                        import_root.display()
+                    ))
+                    .quote_line(keyword_import_pos.line()),
+                );
                 return Err(());
             }
-        };
-        if let Some(extra_tokens_pos) = extra_tokens_pos {
-            eprintln!("Extra tokens at {}.", extra_tokens_pos);
-            file.quote_pos(extra_tokens_pos);
-            self.num_errors += 1;
-            return Err(());
         }
-        let path = path.with_extension("sysc");
-        let path = match path.canonicalize() {
-            Ok(path) => path,
-            Err(err) => {
-                eprintln!("Cannot read file `{}`. {}", path.display(), err);
-                file.quote_line(keyword_import_pos.line());
-                self.num_errors += 1;
-                return Err(());
-            }
-        };
         if self.import_chain.insert(path.clone()) {
             let result = self.read_file(&path);
             self.import_chain.remove(&path);
             match result {
                 Ok(n) => Ok((name, n)),
                 Err(err) => {
-                    eprintln!("Cannot read file `{}`. {}", path.display(), err);
-                    file.quote_line(keyword_import_pos.line());
-                    self.num_errors += 1;
+                    self.logger.error(
+                        file,
+                        log::Diagnostic::new(format!(
+                            "Cannot read file `{}`. {}",
+                            path.display(),
+                            err
+                        ))
+                        .quote_line(keyword_import_pos.line()),
+                    );
                     Err(())
                 }
             }
         } else {
-            eprintln!("Circular imports of `{}`.", path.display());
-            file.quote_line(keyword_import_pos.line());
-            self.num_errors += 1;
+            self.logger.error(
+                file,
+                log::Diagnostic::new(format!("Circular imports of `{}`.", path.display()))
+                    .quote_line(keyword_import_pos.line()),
+            );
             Err(())
         }
     }
 }
 
+/**
+ * Determines the name and the canonical path of the file imported by
+ * `import`, relative to `parent_directory`.
+ */
+fn resolve_import(
+    ast::Import {
+        keyword_import_pos,
+        target,
+        extra_tokens_pos,
+    }: ast::Import,
+    parent_directory: &Path,
+    source_provider: &dyn SourceProvider,
+    file: &log::File,
+    logger: &mut log::Logger,
+) -> Result<(String, PathBuf), ()> {
+    let Some(target) = target else {
+        logger.error(
+            file,
+            log::Diagnostic::new(format!(
+                "Missing import target after `import` at {keyword_import_pos}."
+            ))
+            .quote_pos(keyword_import_pos),
+        );
+        return Err(());
+    };
+    let (name, path) = match target.term {
+        ast::Term::Identifier(name) => {
+            let path = parent_directory.join(&name);
+            (name, path)
+        }
+        ast::Term::FunctionCall {
+            function,
+            arguments,
+        } => {
+            let name = match function.term {
+                ast::Term::Identifier(name) => name,
+                _ => {
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!("Invalid import target at {}.", target.pos))
+                            .quote_pos(target.pos),
+                    );
+                    return Err(());
+                }
+            };
+            let path = match arguments.into_iter().next() {
+                Some(ast::ListElement::NonEmpty(argument)) => match argument.term {
+                    ast::Term::StringLiteral(components) => {
+                        let mut path = String::new();
+                        for component in components {
+                            match component {
+                                ast::StringLiteralComponent::PlaceHolder { .. } => {
+                                    logger.error(
+                                        file,
+                                        log::Diagnostic::new(
+                                            "Import path must not contain a placeholder.",
+                                        )
+                                        .quote_pos(argument.pos),
+                                    );
+                                    return Err(());
+                                }
+                                ast::StringLiteralComponent::String(value) => {
+                                    path.push_str(&value);
+                                }
+                            }
+                        }
+                        parent_directory.join(&path)
+                    }
+                    _ => {
+                        logger.error(
+                            file,
+                            log::Diagnostic::new(format!(
+                                "Invalid import target at {}.",
+                                target.pos
+                            ))
+                            .quote_pos(target.pos),
+                        );
+                        return Err(());
+                    }
+                },
+                Some(ast::ListElement::Empty { comma_pos }) => {
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!(
+                            "Empty argument before comma at {comma_pos}."
+                        ))
+                        .quote_pos(comma_pos),
+                    );
+                    return Err(());
+                }
+                None => {
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!("Missing import path at {}.", target.pos))
+                            .quote_pos(target.pos),
+                    );
+                    return Err(());
+                }
+            };
+            (name, path)
+        }
+        _ => {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Invalid import target at {}.", target.pos))
+                    .quote_pos(target.pos),
+            );
+            return Err(());
+        }
+    };
+    if let Some(extra_tokens_pos) = extra_tokens_pos {
+        logger.error(
+            file,
+            log::Diagnostic::new(format!("Extra tokens at {}.", extra_tokens_pos))
+                .quote_pos(extra_tokens_pos),
+        );
+        return Err(());
+    }
+    let path = path.with_extension("sysc");
+    let path = match source_provider.canonicalize(&path) {
+        Ok(path) => path,
+        Err(err) => {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Cannot read file `{}`. {}", path.display(), err))
+                    .quote_line(keyword_import_pos.line()),
+            );
+            return Err(());
+        }
+    };
+    Ok((name, path))
+}
+
 fn register_structure_name(
     ast::StructureName {
         keyword_struct_pos,
         name,
         extra_tokens_pos,
+        doc_comment: _,
     }: ast::StructureName,
     num_structures: &mut usize,
     named_items: &mut HashMap<String, Item>,
     file: &log::File,
-    num_errors: &mut u32,
+    logger: &mut log::Logger,
 ) {
     let Some(name) = name else {
-        eprintln!(
-            "Missing structure name after `struct` at {}.",
-            keyword_struct_pos
+        logger.error(
+            file,
+            log::Diagnostic::new(format!(
+                "Missing structure name after `struct` at {}.",
+                keyword_struct_pos
+            ))
+            .quote_pos(keyword_struct_pos),
         );
-        file.quote_pos(keyword_struct_pos);
-        *num_errors += 1;
         return;
     };
     match named_items.entry(name) {
         std::collections::hash_map::Entry::Occupied(entry) => {
-            eprintln!("Duplicate definition of `{}`.", entry.key());
-            file.quote_line(keyword_struct_pos.line());
-            *num_errors += 1;
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Duplicate definition of `{}`.", entry.key()))
+                    .quote_line(keyword_struct_pos.line()),
+            );
         }
         std::collections::hash_map::Entry::Vacant(entry) => {
             entry.insert(Item::Ty(backend::TyBuilder::Constructor(
@@ -374,67 +994,383 @@ fn register_structure_name(
         }
     }
     if let Some(extra_tokens_pos) = extra_tokens_pos {
-        eprintln!("Extra tokens at {}.", extra_tokens_pos);
-        file.quote_pos(extra_tokens_pos);
-        *num_errors += 1;
+        logger.error(
+            file,
+            log::Diagnostic::new(format!("Extra tokens at {}.", extra_tokens_pos))
+                .quote_pos(extra_tokens_pos),
+        );
     }
 }
 
 fn register_function_name(
     ast::FunctionName {
         keyword_func_pos,
+        is_method,
         name,
         extra_tokens_pos,
+        doc_comment: _,
     }: ast::FunctionName,
     num_functions: &mut usize,
     named_items: &mut HashMap<String, Item>,
+    methods: &mut HashMap<String, Vec<backend::Function>>,
     file: &log::File,
-    num_errors: &mut u32,
+    logger: &mut log::Logger,
 ) {
     let Some(name) = name else {
-        eprintln!(
-            "Missing structure name after `func` at {}.",
-            keyword_func_pos
+        let message = if is_method {
+            format!(
+                "Missing method name after `method` at {}.",
+                keyword_func_pos
+            )
+        } else {
+            format!(
+                "Missing function name after `func` at {}.",
+                keyword_func_pos
+            )
+        };
+        logger.error(
+            file,
+            log::Diagnostic::new(message).quote_pos(keyword_func_pos),
         );
-        file.quote_pos(keyword_func_pos);
-        *num_errors += 1;
         return;
     };
-    match named_items.entry(name) {
-        std::collections::hash_map::Entry::Occupied(mut entry) => {
-            if let Item::Function(functions) = entry.get_mut() {
-                functions.push(backend::Function::UserDefined(*num_functions));
-            } else {
-                eprintln!("Duplicate definition of `{}`.", entry.key());
-                file.quote_line(keyword_func_pos.line());
-                *num_errors += 1;
+    if is_method {
+        // Methods never conflict with other items, since they are looked up only by
+        // method calls and operators.
+        methods
+            .entry(name)
+            .or_default()
+            .push(backend::Function::UserDefined(*num_functions));
+    } else {
+        match named_items.entry(name) {
+            std::collections::hash_map::Entry::Occupied(mut entry) => {
+                if let Item::Function(functions) = entry.get_mut() {
+                    functions.push(backend::Function::UserDefined(*num_functions));
+                } else {
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!("Duplicate definition of `{}`.", entry.key()))
+                            .quote_line(keyword_func_pos.line()),
+                    );
+                }
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(Item::Function(vec![backend::Function::UserDefined(
+                    *num_functions,
+                )]));
             }
-        }
-        std::collections::hash_map::Entry::Vacant(entry) => {
-            entry.insert(Item::Function(vec![backend::Function::UserDefined(
-                *num_functions,
-            )]));
         }
     }
     *num_functions += 1;
     if let Some(extra_tokens_pos) = extra_tokens_pos {
-        eprintln!("Extra tokens at {}.", extra_tokens_pos);
-        file.quote_pos(extra_tokens_pos);
-        *num_errors += 1;
+        logger.error(
+            file,
+            log::Diagnostic::new(format!("Extra tokens at {}.", extra_tokens_pos))
+                .quote_pos(extra_tokens_pos),
+        );
     }
 }
 
-fn translate_structure_definition(
-    ast::StructureDefinition {
+/**
+ * Collects the positions of the items defined in `ast` other than global
+ * variables. See [`Program::item_positions`].
+ */
+fn item_positions(ast: &ast::File) -> HashMap<String, log::Pos> {
+    let mut positions = HashMap::new();
+    for structure in &ast.structure_names {
+        if let Some(name) = &structure.name {
+            positions
+                .entry(name.clone())
+                .or_insert_with(|| structure.keyword_struct_pos.clone());
+        }
+    }
+    for enumeration in &ast.enumeration_definitions {
+        if let Some(name) = &enumeration.name {
+            positions
+                .entry(name.clone())
+                .or_insert_with(|| enumeration.keyword_enum_pos.clone());
+        }
+    }
+    for function in &ast.function_names {
+        if let (false, Some(name)) = (function.is_method, &function.name) {
+            positions
+                .entry(name.clone())
+                .or_insert_with(|| function.keyword_func_pos.clone());
+        }
+    }
+    for constant in &ast.constant_definitions {
+        if let Some(ast::TermWithPos {
+            term:
+                ast::Term::Assignment {
+                    left_hand_side: Some(left_hand_side),
+                    ..
+                },
+            ..
+        }) = &constant.term
+        {
+            if let ast::Term::Identifier(name) = &left_hand_side.term {
+                positions
+                    .entry(name.clone())
+                    .or_insert_with(|| constant.keyword_const_pos.clone());
+            }
+        }
+    }
+    positions
+}
+
+fn register_enumeration_name(
+    ast::EnumerationDefinition {
+        keyword_enum_pos,
+        name,
+        extra_tokens_after_name_pos,
+        ..
+    }: &ast::EnumerationDefinition,
+    index: usize,
+    named_items: &mut HashMap<String, Item>,
+    file: &log::File,
+    logger: &mut log::Logger,
+) {
+    if let Some(extra_tokens_pos) = extra_tokens_after_name_pos {
+        logger.error(
+            file,
+            log::Diagnostic::new(format!("Extra tokens at {}.", extra_tokens_pos))
+                .quote_pos(extra_tokens_pos.clone()),
+        );
+    }
+    let Some(name) = name else {
+        logger.error(
+            file,
+            log::Diagnostic::new(format!(
+                "Missing enumeration name after `enum` at {}.",
+                keyword_enum_pos
+            ))
+            .quote_pos(keyword_enum_pos.clone()),
+        );
+        return;
+    };
+    match named_items.entry(name.clone()) {
+        std::collections::hash_map::Entry::Occupied(entry) => {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Duplicate definition of `{}`.", entry.key()))
+                    .quote_line(keyword_enum_pos.line()),
+            );
+        }
+        std::collections::hash_map::Entry::Vacant(entry) => {
+            entry.insert(Item::Ty(backend::TyBuilder::Constructor(
+                backend::TyConstructor::Enumeration(index),
+            )));
+        }
+    }
+}
+
+fn translate_enumeration_definition(
+    ast::EnumerationDefinition {
+        keyword_enum_pos: _,
+        name: _,
         ty_parameters,
-        fields,
+        extra_tokens_after_name_pos: _,
+        variants,
         extra_tokens_pos,
-    }: ast::StructureDefinition,
-    named_items: &HashMap<String, Item>,
+        doc_comment: _,
+    }: ast::EnumerationDefinition,
+    index: usize,
+    named_items: &mut HashMap<String, Item>,
+    exported_items: &Vec<HashMap<String, Item>>,
+    file: &log::File,
+    logger: &mut log::Logger,
+) -> (backend::TyKind, backend::Enumeration) {
+    let (ty_parameters_name, kind) = translate_ty_parameters(ty_parameters, file, logger);
+    let mut translated_variants: Vec<backend::Variant> = Vec::new();
+    for ast::EnumerationVariant {
+        variant,
+        extra_tokens_pos,
+    } in variants
+    {
+        let (name, payload_ty) = match variant.term {
+            ast::Term::Identifier(name) => (Some(name), None),
+            ast::Term::TypeAnnotation {
+                term_left,
+                colon_pos: _,
+                term_right: Some(payload_ty),
+            } => match term_left.term {
+                ast::Term::Identifier(name) => (Some(name), Some(*payload_ty)),
+                _ => (None, None),
+            },
+            _ => (None, None),
+        };
+        let Some(name) = name else {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Invalid enumeration variant at {}.", variant.pos))
+                    .quote_pos(variant.pos),
+            );
+            continue;
+        };
+        let payload_ty = payload_ty.and_then(|payload_ty| {
+            translate_ty(
+                payload_ty,
+                named_items,
+                &ty_parameters_name,
+                exported_items,
+                file,
+                logger,
+            )
+        });
+        if let Some(extra_tokens_pos) = extra_tokens_pos {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Extra tokens at {}.", extra_tokens_pos))
+                    .quote_pos(extra_tokens_pos),
+            );
+        }
+        if translated_variants
+            .iter()
+            .any(|variant| variant.name == name)
+        {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Duplicate variant `{name}`."))
+                    .quote_line(variant.pos.line()),
+            );
+            continue;
+        }
+        let constructor = backend::Function::Variant {
+            enumeration_index: index,
+            variant_index: translated_variants.len(),
+        };
+        match named_items.entry(name.clone()) {
+            std::collections::hash_map::Entry::Occupied(mut entry) => {
+                if let Item::Function(functions) = entry.get_mut() {
+                    functions.push(constructor);
+                } else {
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!("Duplicate definition of `{}`.", entry.key()))
+                            .quote_line(variant.pos.line()),
+                    );
+                }
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(Item::Function(vec![constructor]));
+            }
+        }
+        translated_variants.push(backend::Variant { name, payload_ty });
+    }
+    if let Some(extra_tokens_pos) = extra_tokens_pos {
+        logger.error(
+            file,
+            log::Diagnostic::new(format!("Extra tokens at {}.", extra_tokens_pos))
+                .quote_pos(extra_tokens_pos),
+        );
+    }
+    (
+        kind,
+        backend::Enumeration {
+            num_ty_parameters: ty_parameters_name.len(),
+            variants: translated_variants,
+        },
+    )
+}
+
+fn register_constant(
+    ast::ConstantDefinition {
+        keyword_const_pos,
+        term,
+        doc_comment: _,
+    }: ast::ConstantDefinition,
+    named_items: &mut HashMap<String, Item>,
     exported_items: &Vec<HashMap<String, Item>>,
     file: &log::File,
-    num_errors: &mut u32,
-) -> (backend::TyKind, backend::Structure) {
+    logger: &mut log::Logger,
+) {
+    let Some(term) = term else {
+        logger.error(
+            file,
+            log::Diagnostic::new(format!(
+                "Missing constant name after `const` at {}.",
+                keyword_const_pos
+            ))
+            .quote_pos(keyword_const_pos),
+        );
+        return;
+    };
+    let (name, value) = match term.term {
+        ast::Term::Assignment {
+            left_hand_side,
+            operator,
+            right_hand_side,
+        } if operator.term == ast::Term::MethodName(String::from("assign")) => {
+            (left_hand_side, right_hand_side)
+        }
+        _ => {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Expected `name = value` at {}.", term.pos))
+                    .quote_pos(term.pos),
+            );
+            return;
+        }
+    };
+    let name = match name.map(|name| *name) {
+        Some(ast::TermWithPos {
+            term: ast::Term::Identifier(name),
+            pos: _,
+        }) => name,
+        Some(name) => {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Expected a constant name at {}.", name.pos))
+                    .quote_pos(name.pos),
+            );
+            return;
+        }
+        None => {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!(
+                    "Missing constant name after `const` at {}.",
+                    keyword_const_pos
+                ))
+                .quote_pos(keyword_const_pos),
+            );
+            return;
+        }
+    };
+    let Some(value) = value else {
+        logger.error(
+            file,
+            log::Diagnostic::new(format!("Missing value of `{name}` at {}.", term.pos))
+                .quote_pos(term.pos),
+        );
+        return;
+    };
+    let Some(value) = constant::evaluate(*value, named_items, exported_items, file, logger) else {
+        return;
+    };
+    match named_items.entry(name) {
+        std::collections::hash_map::Entry::Occupied(entry) => {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Duplicate definition of `{}`.", entry.key()))
+                    .quote_line(keyword_const_pos.line()),
+            );
+        }
+        std::collections::hash_map::Entry::Vacant(entry) => {
+            entry.insert(Item::Constant(value));
+        }
+    }
+}
+
+/**
+ * Translates the type parameters of a structure or an enumeration. Returns
+ * the indices of their names and the kind of the type constructor.
+ */
+fn translate_ty_parameters(
+    ty_parameters: Option<Vec<ast::ListElement>>,
+    file: &log::File,
+    logger: &mut log::Logger,
+) -> (HashMap<String, usize>, backend::TyKind) {
     let mut ty_parameters_name = HashMap::new();
     let kind = if let Some(ty_parameters) = ty_parameters {
         for ty_parameter in ty_parameters {
@@ -445,15 +1381,25 @@ fn translate_structure_definition(
                         ty_parameters_name.insert(name, new_index);
                     }
                     _ => {
-                        eprintln!("Invalid type parameter at {}.", name.pos);
-                        file.quote_pos(name.pos);
-                        *num_errors += 1;
+                        logger.error(
+                            file,
+                            log::Diagnostic::new(format!(
+                                "Invalid type parameter at {}.",
+                                name.pos
+                            ))
+                            .quote_pos(name.pos),
+                        );
                     }
                 },
                 ast::ListElement::Empty { comma_pos } => {
-                    eprintln!("Empty type parameter before comma at {}.", comma_pos);
-                    file.quote_pos(comma_pos);
-                    *num_errors += 1;
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!(
+                            "Empty type parameter before comma at {}.",
+                            comma_pos
+                        ))
+                        .quote_pos(comma_pos),
+                    );
                 }
             }
         }
@@ -466,7 +1412,23 @@ fn translate_structure_definition(
     } else {
         backend::TyKind::Ty
     };
+    (ty_parameters_name, kind)
+}
+
+fn translate_structure_definition(
+    ast::StructureDefinition {
+        ty_parameters,
+        fields,
+        extra_tokens_pos,
+    }: ast::StructureDefinition,
+    named_items: &HashMap<String, Item>,
+    exported_items: &Vec<HashMap<String, Item>>,
+    file: &log::File,
+    logger: &mut log::Logger,
+) -> (backend::TyKind, backend::Structure, Vec<String>) {
+    let (ty_parameters_name, kind) = translate_ty_parameters(ty_parameters, file, logger);
     let mut translated_fields_ty = Vec::new();
+    let mut fields_name = Vec::new();
     for ast::StructureField {
         field,
         extra_tokens_pos,
@@ -474,36 +1436,56 @@ fn translate_structure_definition(
     {
         match field.term {
             ast::Term::TypeAnnotation {
-                term_left: _,
+                term_left,
                 colon_pos: _,
                 term_right: Some(field_ty),
             } => {
+                let ast::Term::Identifier(name) = term_left.term else {
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!(
+                            "Invalid structure field at {}.",
+                            term_left.pos
+                        ))
+                        .quote_pos(term_left.pos),
+                    );
+                    continue;
+                };
                 if let Some(ty) = translate_ty(
                     *field_ty,
                     named_items,
                     &ty_parameters_name,
                     &exported_items,
                     file,
-                    num_errors,
+                    logger,
                 ) {
                     translated_fields_ty.push(ty);
+                    // Only the fields whose type is known get an index.
+                    fields_name.push(name);
                 }
             }
             _ => {
-                eprintln!("Invalid structure field at {}.", field.pos);
-                file.quote_pos(field.pos);
+                logger.error(
+                    file,
+                    log::Diagnostic::new(format!("Invalid structure field at {}.", field.pos))
+                        .quote_pos(field.pos),
+                );
             }
         }
         if let Some(extra_tokens_pos) = extra_tokens_pos {
-            eprintln!("Extra tokens at {}.", extra_tokens_pos);
-            file.quote_pos(extra_tokens_pos);
-            *num_errors += 1;
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Extra tokens at {}.", extra_tokens_pos))
+                    .quote_pos(extra_tokens_pos),
+            );
         }
     }
     if let Some(extra_tokens_pos) = extra_tokens_pos {
-        eprintln!("Extra tokens at {}.", extra_tokens_pos);
-        file.quote_pos(extra_tokens_pos);
-        *num_errors += 1;
+        logger.error(
+            file,
+            log::Diagnostic::new(format!("Extra tokens at {}.", extra_tokens_pos))
+                .quote_pos(extra_tokens_pos),
+        );
     }
     (
         kind,
@@ -511,6 +1493,7 @@ fn translate_structure_definition(
             num_ty_parameters: ty_parameters_name.len(),
             fields_ty: translated_fields_ty,
         },
+        fields_name,
     )
 }
 
@@ -523,10 +1506,7 @@ fn translate_function_definition(
         extra_tokens_pos,
     }: ast::FunctionDefinition,
     global_variables: &HashMap<String, usize>,
-    named_items: &HashMap<String, Item>,
-    exported_items: &Vec<HashMap<String, Item>>,
-    file: &log::File,
-    num_errors: &mut u32,
+    context: &mut Context,
 ) -> Option<(backend::FunctionTy, backend::FunctionDefinition)> {
     let mut ty_parameters_name = HashMap::new();
     if let Some(ty_parameters) = ty_parameters {
@@ -536,22 +1516,30 @@ fn translate_function_definition(
                     if let ast::Term::Identifier(name) = ty_parameter.term {
                         ty_parameters_name.insert(name, i);
                     } else {
-                        eprintln!("Invalid type parameter at {}.", ty_parameter.pos);
-                        file.quote_pos(ty_parameter.pos);
-                        *num_errors += 1;
+                        context.logger.error(
+                            context.file,
+                            log::Diagnostic::new(format!(
+                                "Invalid type parameter at {}.",
+                                ty_parameter.pos
+                            ))
+                            .quote_pos(ty_parameter.pos),
+                        );
                     }
                 }
                 ast::ListElement::Empty { comma_pos } => {
-                    eprintln!("Empty type parameter before comma at {}.", comma_pos);
-                    file.quote_pos(comma_pos);
-                    *num_errors += 1;
+                    context.logger.error(
+                        context.file,
+                        log::Diagnostic::new(format!(
+                            "Empty type parameter before comma at {}.",
+                            comma_pos
+                        ))
+                        .quote_pos(comma_pos),
+                    );
                 }
             }
         }
     }
-    let mut local_variables = HashMap::new();
-    let mut num_local_variables = 0;
-    let mut local_scope = Vec::new();
+    let mut local_variables = Variables::default();
     let mut parameters_ty = Vec::new();
     if let Some(parameters) = parameters {
         for parameter in parameters {
@@ -564,81 +1552,107 @@ fn translate_function_definition(
                     } => {
                         match parameter_name.term {
                             ast::Term::Identifier(name) => {
-                                match local_variables.entry(name.clone()) {
+                                match local_variables.indices.entry(name) {
                                     std::collections::hash_map::Entry::Occupied(_) => {
-                                        eprintln!(
-                                            "Duplicate parameter name at {}.",
-                                            parameter_name.pos
+                                        context.logger.error(
+                                            context.file,
+                                            log::Diagnostic::new(format!(
+                                                "Duplicate parameter name at {}.",
+                                                parameter_name.pos
+                                            ))
+                                            .quote_pos(parameter_name.pos),
                                         );
-                                        file.quote_pos(parameter_name.pos);
                                     }
                                     std::collections::hash_map::Entry::Vacant(entry) => {
-                                        entry.insert(num_local_variables);
-                                        local_scope.push((name, None));
-                                        num_local_variables += 1;
+                                        entry.insert(local_variables.pos.len());
+                                        local_variables.pos.push(parameter_name.pos.clone());
                                     }
                                 }
                             }
                             _ => {
-                                eprintln!("Invalid parameter name at {}.", parameter_name.pos);
-                                file.quote_pos(parameter_name.pos);
-                                *num_errors += 1;
+                                context.logger.error(
+                                    context.file,
+                                    log::Diagnostic::new(format!(
+                                        "Invalid parameter name at {}.",
+                                        parameter_name.pos
+                                    ))
+                                    .quote_pos(parameter_name.pos),
+                                );
                             }
                         }
                         if let Some(parameter_ty) = parameter_ty {
                             if let Some(ty) = translate_ty(
                                 *parameter_ty,
-                                named_items,
+                                context.named_items,
                                 &ty_parameters_name,
-                                &exported_items,
-                                file,
-                                num_errors,
+                                context.exported_items,
+                                context.file,
+                                context.logger,
                             ) {
                                 parameters_ty.push(ty);
                             }
                         } else {
-                            eprintln!("Missing type after colon at {}.", colon_pos);
-                            file.quote_pos(colon_pos);
-                            *num_errors += 1;
+                            context.logger.error(
+                                context.file,
+                                log::Diagnostic::new(format!(
+                                    "Missing type after colon at {}.",
+                                    colon_pos
+                                ))
+                                .quote_pos(colon_pos),
+                            );
                         }
                     }
                     _ => {
-                        eprintln!("Invalid parameter at {}.", parameter.pos);
-                        file.quote_pos(parameter.pos);
-                        *num_errors += 1;
+                        context.logger.error(
+                            context.file,
+                            log::Diagnostic::new(format!(
+                                "Invalid parameter at {}.",
+                                parameter.pos
+                            ))
+                            .quote_pos(parameter.pos),
+                        );
                     }
                 },
                 ast::ListElement::Empty { comma_pos } => {
-                    eprintln!("Empty parameter before comma at {}.", comma_pos);
-                    file.quote_pos(comma_pos);
-                    *num_errors += 1;
+                    context.logger.error(
+                        context.file,
+                        log::Diagnostic::new(format!(
+                            "Empty parameter before comma at {}.",
+                            comma_pos
+                        ))
+                        .quote_pos(comma_pos),
+                    );
                 }
             }
         }
     } else {
-        eprintln!("Missing parameter list.");
-        *num_errors += 1;
+        context.logger.error(
+            context.file,
+            log::Diagnostic::new("Missing parameter list."),
+        );
     }
     let return_ty = if let Some(return_ty) = return_ty {
         if let Some(return_ty) = return_ty.ty {
             match translate_ty(
                 return_ty,
-                named_items,
+                context.named_items,
                 &ty_parameters_name,
-                &exported_items,
-                file,
-                num_errors,
+                context.exported_items,
+                context.file,
+                context.logger,
             ) {
                 Some(ty) => ty,
                 None => return None,
             }
         } else {
-            eprintln!(
-                "Missing return type after colon at {}.",
-                return_ty.colon_pos
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!(
+                    "Missing return type after colon at {}.",
+                    return_ty.colon_pos
+                ))
+                .quote_pos(return_ty.colon_pos),
             );
-            file.quote_pos(return_ty.colon_pos);
-            *num_errors += 1;
             return None;
         }
     } else {
@@ -650,34 +1664,29 @@ fn translate_function_definition(
         }
     };
     if let Some(extra_tokens_pos) = extra_tokens_pos {
-        eprintln!("Extra tokens at {}.", extra_tokens_pos);
-        file.quote_pos(extra_tokens_pos);
-        *num_errors += 1;
-    }
-    let mut translated_body = Some(Vec::new());
-    for statement in body {
-        let translated_statement = translate_statement(
-            statement,
-            &mut local_variables,
-            &mut num_local_variables,
-            &mut local_scope,
-            &ty_parameters_name,
-            Some(global_variables),
-            named_items,
-            exported_items,
-            file,
-            num_errors,
+        context.logger.error(
+            context.file,
+            log::Diagnostic::new(format!("Extra tokens at {}.", extra_tokens_pos))
+                .quote_pos(extra_tokens_pos),
         );
-        match translated_statement {
-            Some(Some(statement)) => {
-                if let Some(translated_body) = &mut translated_body {
-                    translated_body.push(statement);
-                }
-            }
-            Some(None) => {}
-            None => translated_body = None,
-        }
     }
+    let mut body_context = Context {
+        named_items: context.named_items,
+        methods: context.methods,
+        fields: context.fields,
+        ty_parameters: &ty_parameters_name,
+        exported_items: context.exported_items,
+        file: context.file,
+        logger: context.logger,
+    };
+    let translated_body = translate_block(
+        body,
+        &mut local_variables,
+        Some(global_variables),
+        true,
+        false,
+        &mut body_context,
+    );
     Some((
         backend::FunctionTy {
             num_ty_parameters: ty_parameters_name.len(),
@@ -685,49 +1694,227 @@ fn translate_function_definition(
             return_ty,
         },
         backend::FunctionDefinition {
-            num_local_variables,
+            num_local_variables: local_variables.pos.len(),
             body: translated_body?,
         },
     ))
 }
 
+fn translate_test_definition(
+    ast::TestDefinition {
+        keyword_test_pos,
+        is_benchmark,
+        name,
+        extra_tokens_after_name_pos,
+        body,
+        extra_tokens_pos,
+    }: ast::TestDefinition,
+    global_variables: &HashMap<String, usize>,
+    context: &mut Context,
+) -> Option<backend::Test> {
+    let (kind, keyword) = if is_benchmark {
+        ("Benchmark", "bench")
+    } else {
+        ("Test", "test")
+    };
+    let translated_name = match name {
+        Some(ast::TermWithPos {
+            term: ast::Term::StringLiteral(components),
+            pos,
+        }) => {
+            let mut translated_name = Some(String::new());
+            for component in components {
+                match component {
+                    ast::StringLiteralComponent::String(value) => {
+                        if let Some(translated_name) = &mut translated_name {
+                            translated_name.push_str(&value);
+                        }
+                    }
+                    ast::StringLiteralComponent::PlaceHolder { .. } => {
+                        context.logger.error(
+                            context.file,
+                            log::Diagnostic::new(format!(
+                                "{kind} name must not contain a placeholder."
+                            ))
+                            .quote_pos(pos),
+                        );
+                        translated_name = None;
+                        break;
+                    }
+                }
+            }
+            translated_name
+        }
+        Some(name) => {
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!(
+                    "Expected a string literal as a {} name at {}.",
+                    kind.to_lowercase(),
+                    name.pos
+                ))
+                .quote_pos(name.pos),
+            );
+            None
+        }
+        None => {
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!(
+                    "Missing {} name after `{keyword}` at {}.",
+                    kind.to_lowercase(),
+                    keyword_test_pos
+                ))
+                .quote_pos(keyword_test_pos),
+            );
+            None
+        }
+    };
+    if let Some(extra_tokens_pos) = extra_tokens_after_name_pos {
+        context.logger.error(
+            context.file,
+            log::Diagnostic::new(format!("Extra tokens at {}.", extra_tokens_pos))
+                .quote_pos(extra_tokens_pos),
+        );
+    }
+    let mut local_variables = Variables::default();
+    let mut body_context = Context {
+        named_items: context.named_items,
+        methods: context.methods,
+        fields: context.fields,
+        ty_parameters: &HashMap::new(),
+        exported_items: context.exported_items,
+        file: context.file,
+        logger: context.logger,
+    };
+    // A test is a function of type `() -> ()`, so `return` ends it early.
+    let translated_body = translate_block(
+        body,
+        &mut local_variables,
+        Some(global_variables),
+        true,
+        false,
+        &mut body_context,
+    );
+    if let Some(extra_tokens_pos) = extra_tokens_pos {
+        context.logger.error(
+            context.file,
+            log::Diagnostic::new(format!("Extra tokens at {}.", extra_tokens_pos))
+                .quote_pos(extra_tokens_pos),
+        );
+    }
+    Some(backend::Test {
+        name: translated_name?,
+        definition: backend::FunctionDefinition {
+            num_local_variables: local_variables.pos.len(),
+            body: translated_body?,
+        },
+    })
+}
+
+/**
+ * The variables declared so far at the top level of a file, or in a
+ * function or a test.
+ */
+#[derive(Default)]
+struct Variables {
+    /**
+     * The index of each variable in scope.
+     */
+    indices: HashMap<String, usize>,
+    /**
+     * Where each variable is declared. The length is the number of the
+     * variables, including the ones out of scope.
+     */
+    pos: Vec<log::Pos>,
+    /**
+     * The index of the first variable. Global variables of all files share
+     * the indices, so this is the number of the ones in the files read
+     * before.
+     */
+    first_index: usize,
+}
+
+/**
+ * What the statements and the expressions in a body refer to, other than
+ * the variables.
+ */
+struct Context<'a, 'sink> {
+    /**
+     * The items visible in the file, including the imported files.
+     */
+    named_items: &'a HashMap<String, Item>,
+    /**
+     * See [`Reader::methods`].
+     */
+    methods: &'a HashMap<String, Vec<backend::Function>>,
+    /**
+     * See [`Reader::fields`].
+     */
+    fields: &'a HashMap<String, Vec<(usize, usize)>>,
+    /**
+     * The type parameters of the function, which are empty for the top level
+     * of a file and tests.
+     */
+    ty_parameters: &'a HashMap<String, usize>,
+    /**
+     * See [`Reader::exported_items`].
+     */
+    exported_items: &'a Vec<HashMap<String, Item>>,
+    file: &'a log::File,
+    logger: &'a mut log::Logger<'sink>,
+}
+
 fn translate_statement(
     statement: ast::Statement,
-    variables: &mut HashMap<String, usize>,
-    num_variables: &mut usize,
+    variables: &mut Variables,
     scope: &mut Vec<(String, Option<usize>)>,
-    ty_parameters: &HashMap<String, usize>,
     global_variables: Option<&HashMap<String, usize>>,
-    named_items: &HashMap<String, Item>,
-    exported_items: &Vec<HashMap<String, Item>>,
-    file: &log::File,
-    num_errors: &mut u32,
+    in_function: bool,
+    in_loop: bool,
+    context: &mut Context,
 ) -> Option<Option<backend::Statement>> {
     match statement {
         ast::Statement::Term(term) => {
             let term_pos = term.pos.clone();
+            let (term, discarded_value_kind) = match term.term {
+                // `_ = value` discards the value on purpose.
+                ast::Term::Assignment {
+                    operator,
+                    left_hand_side: Some(left_hand_side),
+                    right_hand_side: Some(right_hand_side),
+                } if matches!(left_hand_side.term, ast::Term::Identity)
+                    && matches!(&operator.term, ast::Term::MethodName(name) if name == "assign") =>
+                {
+                    (*right_hand_side, None)
+                }
+                term => {
+                    let kind = discarded_value_kind(&term);
+                    let term = ast::TermWithPos {
+                        term,
+                        pos: term_pos.clone(),
+                    };
+                    (term, kind)
+                }
+            };
             let expr = match global_variables {
-                Some(global_variables) => translate_expression(
-                    term,
-                    named_items,
-                    ty_parameters,
-                    Some(&variables),
-                    global_variables,
-                    exported_items,
-                    file,
-                    num_errors,
-                ),
-                None => translate_expression(
-                    term,
-                    named_items,
-                    ty_parameters,
-                    None,
-                    &variables,
-                    exported_items,
-                    file,
-                    num_errors,
-                ),
+                Some(global_variables) => {
+                    translate_expression(term, Some(&variables.indices), global_variables, context)
+                }
+                None => translate_expression(term, None, &variables.indices, context),
             };
+            if let (Some(_), Some(kind)) = (&expr, discarded_value_kind) {
+                context.logger.warning(
+                    context.file,
+                    "unused_result",
+                    term_pos.start.line,
+                    log::Diagnostic::new(format!(
+                        "The value of the {kind} at {term_pos} is unused."
+                    ))
+                    .quote_pos(term_pos)
+                    .note("Write `_ = ...` to discard it on purpose."),
+                );
+            }
             Some(expr.map(backend::Statement::Expr))
         }
         ast::Statement::VariableDeclaration {
@@ -735,21 +1922,146 @@ fn translate_statement(
             term,
         } => {
             let Some(name) = term else {
-                eprintln!("Missing variable name after `var` at {}.", keyword_var_pos);
-                file.quote_pos(keyword_var_pos);
+                context.logger.error(
+                    context.file,
+                    log::Diagnostic::new(format!(
+                        "Missing variable name after `var` at {}.",
+                        keyword_var_pos
+                    ))
+                    .quote_pos(keyword_var_pos),
+                );
                 return None;
             };
-            match name.term {
-                ast::Term::Identifier(name) => {
-                    let prev_index = variables.insert(name.clone(), *num_variables);
-                    scope.push((name, prev_index));
-                    *num_variables += 1;
-                    Some(None)
+            let name_pos = name.pos;
+            // `var a`, `var a = x` or `var a, b = x, y`.
+            let (targets, value) = match name.term {
+                ast::Term::Assignment {
+                    operator,
+                    left_hand_side: Some(left_hand_side),
+                    right_hand_side,
+                } if matches!(&operator.term, ast::Term::MethodName(name) if name == "assign") => {
+                    let Some(right_hand_side) = right_hand_side else {
+                        let pos = operator.pos;
+                        context.logger.error(
+                            context.file,
+                            log::Diagnostic::new(format!(
+                                "Missing right-hand side of `assign` at {pos}."
+                            ))
+                            .quote_pos(pos),
+                        );
+                        return None;
+                    };
+                    match left_hand_side.term {
+                        ast::Term::Tuple { elements } => (
+                            elements
+                                .into_iter()
+                                .map(|element| match element {
+                                    ast::ListElement::NonEmpty(target) => target,
+                                    ast::ListElement::Empty { comma_pos } => ast::TermWithPos {
+                                        term: ast::Term::Identity,
+                                        pos: comma_pos,
+                                    },
+                                })
+                                .collect(),
+                            Some(*right_hand_side),
+                        ),
+                        term => (
+                            vec![ast::TermWithPos {
+                                term,
+                                pos: left_hand_side.pos,
+                            }],
+                            Some(*right_hand_side),
+                        ),
+                    }
                 }
-                _ => {
-                    eprintln!("Expected a variable name at {}.", name.pos);
-                    file.quote_pos(name.pos);
-                    return None;
+                term => (
+                    vec![ast::TermWithPos {
+                        term,
+                        pos: name_pos,
+                    }],
+                    None,
+                ),
+            };
+            let mut names = Some(Vec::new());
+            for target in targets {
+                match target.term {
+                    ast::Term::Identifier(name) => {
+                        if let Some(names) = &mut names {
+                            names.push((name, target.pos));
+                        }
+                    }
+                    _ => {
+                        let pos = target.pos;
+                        context.logger.error(
+                            context.file,
+                            log::Diagnostic::new(format!("Expected a variable name at {pos}."))
+                                .quote_pos(pos),
+                        );
+                        names = None;
+                    }
+                }
+            }
+            // The value is evaluated before the variables are declared.
+            let num_targets = names.as_ref().map_or(0, Vec::len);
+            let value = value.map(|value| match (global_variables, num_targets) {
+                (Some(global_variables), 1) => {
+                    translate_expression(value, Some(&variables.indices), global_variables, context)
+                        .map(|value| AssignedValues::Each(vec![value]))
+                }
+                (None, 1) => translate_expression(value, None, &variables.indices, context)
+                    .map(|value| AssignedValues::Each(vec![value])),
+                (Some(global_variables), _) => translate_assigned_values(
+                    value,
+                    num_targets,
+                    Some(&variables.indices),
+                    global_variables,
+                    context,
+                ),
+                (None, _) => {
+                    translate_assigned_values(value, num_targets, None, &variables.indices, context)
+                }
+            });
+            let mut targets = Vec::new();
+            for (name, name_pos) in names? {
+                let index = variables.first_index + variables.pos.len();
+                let prev_index = variables.indices.insert(name.clone(), index);
+                if let Some(prev_index) = prev_index {
+                    let prev_pos = variables.pos[prev_index - variables.first_index].clone();
+                    context.logger.warning(
+                        context.file,
+                        "shadowing",
+                        name_pos.start.line,
+                        log::Diagnostic::new(format!(
+                            "`{name}` at {name_pos} shadows the variable declared at {prev_pos}."
+                        ))
+                        .quote_pos(name_pos.clone())
+                        .note("The shadowed variable is declared here.")
+                        .quote_pos(prev_pos),
+                    );
+                }
+                scope.push((name, prev_index));
+                variables.pos.push(name_pos);
+                targets.push(if global_variables.is_some() {
+                    backend::Expression::LocalVariable(index)
+                } else {
+                    backend::Expression::GlobalVariable(index)
+                });
+            }
+            match value {
+                None => Some(None),
+                Some(value) => {
+                    let expr = match value? {
+                        AssignedValues::Each(mut values) if values.len() == 1 => {
+                            backend::Expression::Function {
+                                candidates: vec![backend::Function::Assign],
+                                calls: vec![backend::Call {
+                                    arguments: vec![targets.pop().unwrap(), values.pop().unwrap()],
+                                }],
+                            }
+                        }
+                        values => values.assign_to(targets),
+                    };
+                    Some(Some(backend::Statement::Expr(expr)))
                 }
             }
         }
@@ -758,95 +2070,290 @@ fn translate_statement(
             condition,
             body,
         } => {
-            let condition = if let Some(condition) = condition {
-                let condition_pos = condition.pos.clone();
-                match global_variables {
-                    Some(global_variables) => translate_expression(
-                        condition,
-                        named_items,
-                        ty_parameters,
-                        Some(&variables),
-                        global_variables,
-                        exported_items,
-                        file,
-                        num_errors,
-                    ),
-                    None => translate_expression(
-                        condition,
-                        named_items,
-                        ty_parameters,
-                        None,
-                        &variables,
-                        exported_items,
-                        file,
-                        num_errors,
-                    ),
-                }
+            let condition = translate_condition(
+                condition,
+                "while",
+                keyword_while_pos,
+                &variables.indices,
+                global_variables,
+                context,
+            );
+            let translated_stmts = translate_block(
+                body,
+                variables,
+                global_variables,
+                in_function,
+                true,
+                context,
+            );
+            (|| {
+                Some(Some(backend::Statement::While(
+                    condition?,
+                    translated_stmts?,
+                )))
+            })()
+        }
+        ast::Statement::If {
+            keyword_if_pos,
+            condition,
+            body,
+            else_body,
+        } => {
+            let condition = translate_condition(
+                condition,
+                "if",
+                keyword_if_pos,
+                &variables.indices,
+                global_variables,
+                context,
+            );
+            let translated_body = translate_block(
+                body,
+                variables,
+                global_variables,
+                in_function,
+                in_loop,
+                context,
+            );
+            let translated_else_body = match else_body {
+                Some((_, else_body)) => translate_block(
+                    else_body,
+                    variables,
+                    global_variables,
+                    in_function,
+                    in_loop,
+                    context,
+                ),
+                None => Some(Vec::new()),
+            };
+            (|| {
+                Some(Some(backend::Statement::If(
+                    condition?,
+                    translated_body?,
+                    translated_else_body?,
+                )))
+            })()
+        }
+        ast::Statement::Return {
+            keyword_return_pos,
+            value,
+        } => {
+            if !in_function {
+                context.logger.error(
+                    context.file,
+                    log::Diagnostic::new(format!(
+                        "`return` outside of a function at {}.",
+                        keyword_return_pos
+                    ))
+                    .quote_pos(keyword_return_pos),
+                );
+                return None;
+            }
+            let Some(value) = value else {
+                return Some(Some(backend::Statement::Return(None)));
+            };
+            // Functions have their own variables, and see the global ones.
+            translate_expression(
+                value,
+                Some(&variables.indices),
+                global_variables.unwrap(),
+                context,
+            )
+            .map(|value| Some(backend::Statement::Return(Some(value))))
+        }
+        ast::Statement::Break { keyword_break_pos } => {
+            if in_loop {
+                Some(Some(backend::Statement::Break))
+            } else {
+                context.logger.error(
+                    context.file,
+                    log::Diagnostic::new(format!(
+                        "`break` outside of a loop at {}.",
+                        keyword_break_pos
+                    ))
+                    .quote_pos(keyword_break_pos),
+                );
+                None
+            }
+        }
+        ast::Statement::Continue {
+            keyword_continue_pos,
+        } => {
+            if in_loop {
+                Some(Some(backend::Statement::Continue))
             } else {
-                eprintln!("Missing condition after `while` at {}", keyword_while_pos);
-                file.quote_pos(keyword_while_pos);
+                context.logger.error(
+                    context.file,
+                    log::Diagnostic::new(format!(
+                        "`continue` outside of a loop at {}.",
+                        keyword_continue_pos
+                    ))
+                    .quote_pos(keyword_continue_pos),
+                );
                 None
+            }
+        }
+        ast::Statement::Assert {
+            keyword_assert_pos,
+            condition,
+            message,
+        } => {
+            let Some(condition) = condition else {
+                context.logger.error(
+                    context.file,
+                    log::Diagnostic::new(format!(
+                        "Missing condition after `assert` at {}.",
+                        keyword_assert_pos
+                    ))
+                    .quote_pos(keyword_assert_pos),
+                );
+                return None;
             };
-            let mut body_scope = Vec::new();
-            let mut translated_stmts = Some(Vec::new());
-            for stmt in body {
-                match translate_statement(
-                    stmt,
-                    variables,
-                    num_variables,
-                    &mut body_scope,
-                    ty_parameters,
+            let condition_pos = condition.pos.clone();
+            let translated_condition = match global_variables {
+                Some(global_variables) => translate_expression(
+                    condition,
+                    Some(&variables.indices),
                     global_variables,
-                    named_items,
-                    exported_items,
-                    file,
-                    num_errors,
-                ) {
-                    Some(stmt) => {
-                        if let Some(stmt) = stmt {
-                            if let Some(translated_stmts) = &mut translated_stmts {
-                                translated_stmts.push(stmt);
+                    context,
+                ),
+                None => translate_expression(condition, None, &variables.indices, context),
+            };
+            let translated_message = match message {
+                None => Some(None),
+                Some(ast::TermWithPos {
+                    term: ast::Term::StringLiteral(components),
+                    pos,
+                }) => {
+                    let mut translated_message = Some(String::new());
+                    for component in components {
+                        match component {
+                            ast::StringLiteralComponent::String(value) => {
+                                if let Some(translated_message) = &mut translated_message {
+                                    translated_message.push_str(&value);
+                                }
+                            }
+                            ast::StringLiteralComponent::PlaceHolder { .. } => {
+                                context.logger.error(
+                                    context.file,
+                                    log::Diagnostic::new(
+                                        "Assertion message must not contain a placeholder.",
+                                    )
+                                    .quote_pos(pos),
+                                );
+                                translated_message = None;
+                                break;
                             }
                         }
                     }
-                    None => translated_stmts = None,
+                    translated_message.map(Some)
+                }
+                Some(message) => {
+                    context.logger.error(
+                        context.file,
+                        log::Diagnostic::new(format!(
+                            "Expected a string literal as an assertion message at {}.",
+                            message.pos
+                        ))
+                        .quote_pos(message.pos),
+                    );
+                    None
+                }
+            };
+            Some(Some(backend::Statement::Assert {
+                condition: translated_condition?,
+                condition_source: context.file.source(&condition_pos).to_owned(),
+                message: translated_message?,
+                path: context.file.path.clone(),
+                pos: condition_pos,
+            }))
+        }
+    }
+}
+
+/**
+ * Translates the condition of `while` or `if`, reporting it if missing.
+ */
+fn translate_condition(
+    condition: Option<ast::TermWithPos>,
+    keyword: &str,
+    keyword_pos: log::Pos,
+    variables: &HashMap<String, usize>,
+    global_variables: Option<&HashMap<String, usize>>,
+    context: &mut Context,
+) -> Option<backend::Expression> {
+    let Some(condition) = condition else {
+        context.logger.error(
+            context.file,
+            log::Diagnostic::new(format!(
+                "Missing condition after `{keyword}` at {keyword_pos}."
+            ))
+            .quote_pos(keyword_pos),
+        );
+        return None;
+    };
+    match global_variables {
+        Some(global_variables) => {
+            translate_expression(condition, Some(variables), global_variables, context)
+        }
+        None => translate_expression(condition, None, variables, context),
+    }
+}
+
+/**
+ * Translates the body of a function, a test, `while` or `if` in a new
+ * scope, restoring the variables shadowed in it afterwards. `in_function` and `in_loop` tell
+ * whether `return`, and `break` and `continue`, are allowed in it.
+ */
+fn translate_block(
+    body: Vec<ast::Statement>,
+    variables: &mut Variables,
+    global_variables: Option<&HashMap<String, usize>>,
+    in_function: bool,
+    in_loop: bool,
+    context: &mut Context,
+) -> Option<Vec<backend::Statement>> {
+    let mut body_scope = Vec::new();
+    let mut translated_stmts = Some(Vec::new());
+    for stmt in body {
+        match translate_statement(
+            stmt,
+            variables,
+            &mut body_scope,
+            global_variables,
+            in_function,
+            in_loop,
+            context,
+        ) {
+            Some(stmt) => {
+                if let Some(stmt) = stmt {
+                    if let Some(translated_stmts) = &mut translated_stmts {
+                        translated_stmts.push(stmt);
+                    }
                 }
             }
-            for (name, prev_index) in body_scope.into_iter().rev() {
-                match prev_index {
-                    Some(prev_index) => variables.insert(name, prev_index),
-                    None => variables.remove(&name),
-                };
-            }
-            (|| {
-                Some(Some(backend::Statement::While(
-                    condition?,
-                    translated_stmts?,
-                )))
-            })()
+            None => translated_stmts = None,
         }
     }
+    for (name, prev_index) in body_scope.into_iter().rev() {
+        match prev_index {
+            Some(prev_index) => variables.indices.insert(name, prev_index),
+            None => variables.indices.remove(&name),
+        };
+    }
+    translated_stmts
 }
 
 fn translate_import(
-    import: ast::TermWithPos,
+    import: &ast::TermWithPos,
     named_items: &HashMap<String, Item>,
     exported_items: &Vec<HashMap<String, Item>>,
-    file: &log::File,
-    num_errors: &mut u32,
 ) -> Option<usize> {
-    let item = match import.term {
-        ast::Term::Identifier(name) => match named_items.get(&name) {
-            Some(item) => item,
-            None => return None,
-        },
+    let item = match &import.term {
+        ast::Term::Identifier(name) => named_items.get(name)?,
         ast::Term::FieldByName { term_left, name } => {
-            let file_index =
-                translate_import(*term_left, named_items, exported_items, file, num_errors)?;
-            match exported_items[file_index].get(&name) {
-                Some(item) => item,
-                None => return None,
-            }
+            let file_index = translate_import(term_left, named_items, exported_items)?;
+            exported_items[file_index].get(name)?
         }
         _ => return None,
     };
@@ -862,7 +2369,7 @@ fn translate_ty(
     ty_parameters: &HashMap<String, usize>,
     exported_items: &Vec<HashMap<String, Item>>,
     file: &log::File,
-    num_errors: &mut u32,
+    logger: &mut log::Logger,
 ) -> Option<backend::TyBuilder> {
     let item = match ty.term {
         ast::Term::IntegerTy => {
@@ -881,15 +2388,30 @@ fn translate_ty(
             }
             match named_items.get(&name) {
                 Some(item) => item,
-                None => return None,
+                None => {
+                    let pos = ty.pos;
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!("Undefined name `{name}` at {pos}."))
+                            .quote_pos(pos),
+                    );
+                    return None;
+                }
             }
         }
         ast::Term::FieldByName { term_left, name } => {
-            let file_index =
-                translate_import(*term_left, named_items, exported_items, file, num_errors)?;
-            match exported_items[file_index].get(&name) {
+            let item = translate_import(&term_left, named_items, exported_items)
+                .and_then(|file_index| exported_items[file_index].get(&name));
+            match item {
                 Some(item) => item,
-                None => return None,
+                None => {
+                    let pos = ty.pos;
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!("Invalid type at {pos}.")).quote_pos(pos),
+                    );
+                    return None;
+                }
             }
         }
         ast::Term::TypeParameters {
@@ -902,7 +2424,7 @@ fn translate_ty(
                 ty_parameters,
                 exported_items,
                 file,
-                num_errors,
+                logger,
             );
             let mut translated_parameters = Some(Vec::new());
             for parameter in parameters {
@@ -913,10 +2435,16 @@ fn translate_ty(
                         ty_parameters,
                         exported_items,
                         file,
-                        num_errors,
+                        logger,
                     ),
                     ast::ListElement::Empty { comma_pos } => {
-                        eprintln!("Empty type parameter before comma at {comma_pos}");
+                        logger.error(
+                            file,
+                            log::Diagnostic::new(format!(
+                                "Empty type parameter before comma at {comma_pos}"
+                            ))
+                            .quote_pos(comma_pos),
+                        );
                         None
                     }
                 };
@@ -936,25 +2464,58 @@ fn translate_ty(
                 })
             })();
         }
-        _ => return None,
+        _ => {
+            let pos = ty.pos;
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Invalid type at {pos}.")).quote_pos(pos),
+            );
+            return None;
+        }
     };
     match item {
         Item::Ty(ty) => Some(ty.clone()),
-        _ => None,
+        _ => {
+            let pos = ty.pos;
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Expected a type at {pos}.")).quote_pos(pos),
+            );
+            None
+        }
     }
 }
 
 fn translate_expression(
     expression: ast::TermWithPos,
-    named_items: &HashMap<String, Item>,
-    ty_parameters: &HashMap<String, usize>,
     local_variables: Option<&HashMap<String, usize>>,
     global_variables: &HashMap<String, usize>,
-    exported_items: &Vec<HashMap<String, Item>>,
-    file: &log::File,
-    num_errors: &mut u32,
+    context: &mut Context,
 ) -> Option<backend::Expression> {
     let item = match expression.term {
+        ast::Term::NumericLiteral(value) => {
+            return constant::numeric_literal(&value, expression.pos, context.file, context.logger)
+                .map(backend::Expression::Constant);
+        }
+        ast::Term::IntegerLiteral { radix, digits } => {
+            return constant::integer_literal(
+                radix,
+                &digits,
+                expression.pos,
+                context.file,
+                context.logger,
+            )
+            .map(backend::Expression::Constant);
+        }
+        ast::Term::StringLiteral(components) => {
+            return translate_string_literal(
+                components,
+                expression.pos,
+                local_variables,
+                global_variables,
+                context,
+            );
+        }
         ast::Term::Identifier(name) => {
             if let Some(local_variables) = local_variables {
                 if let Some(&index) = local_variables.get(&name) {
@@ -974,249 +2535,973 @@ fn translate_expression(
                     }],
                 });
             }
-            match named_items.get(&name) {
+            match context.named_items.get(&name) {
                 Some(item) => item,
-                None => return None,
+                None => {
+                    let pos = expression.pos;
+                    context.logger.error(
+                        context.file,
+                        log::Diagnostic::new(format!("Undefined name `{name}` at {pos}."))
+                            .quote_pos(pos),
+                    );
+                    return None;
+                }
+            }
+        }
+        ast::Term::FieldByName { term_left, name } => {
+            // An item of an imported file or a namespace of the host items,
+            // unless `term_left` is a value, whose field is accessed.
+            let file_index =
+                translate_qualifier(&term_left, local_variables, global_variables, context);
+            match file_index {
+                Some(file_index) => match context.exported_items[file_index].get(&name) {
+                    Some(item) => item,
+                    None => {
+                        let pos = expression.pos;
+                        context.logger.error(
+                            context.file,
+                            log::Diagnostic::new(format!("Undefined name `{name}` at {pos}."))
+                                .quote_pos(pos),
+                        );
+                        return None;
+                    }
+                },
+                None => {
+                    let candidates = field_candidates(
+                        &name,
+                        expression.pos,
+                        |structure_index, field_index| backend::Function::Field {
+                            structure_index,
+                            field_index,
+                        },
+                        context,
+                    );
+                    let structure = translate_expression(
+                        *term_left,
+                        local_variables,
+                        global_variables,
+                        context,
+                    );
+                    return Some(backend::Expression::Function {
+                        candidates: candidates?,
+                        calls: vec![backend::Call {
+                            arguments: vec![structure?],
+                        }],
+                    });
+                }
             }
         }
         ast::Term::FunctionCall {
             function,
             arguments,
         } => {
-            if let ast::Term::FieldByName { term_left, name } = function.term {
-                let function_pos = term_left.pos.clone();
-                let translated_function = translate_expression(
-                    *term_left,
-                    named_items,
-                    ty_parameters,
+            // `lib.f(x, ...)` calls the function `f` of the file imported as
+            // `lib`, like `f(x, ...)`.
+            let is_method_call = match &function.term {
+                ast::Term::FieldByName { term_left, .. } => {
+                    translate_qualifier(term_left, local_variables, global_variables, context)
+                        .is_none()
+                }
+                _ => false,
+            };
+            if is_method_call {
+                let ast::Term::FieldByName { term_left, name } = function.term else {
+                    unreachable!();
+                };
+                // `x.name(y, ...)` calls the method `name` with `x, y, ...`.
+                let mut operands = vec![Some(*term_left)];
+                let mut has_empty_argument = false;
+                for argument in arguments {
+                    match argument {
+                        ast::ListElement::NonEmpty(argument) => operands.push(Some(argument)),
+                        ast::ListElement::Empty { comma_pos } => {
+                            context.logger.error(
+                                context.file,
+                                log::Diagnostic::new(format!(
+                                    "Empty argument before comma at {comma_pos}"
+                                ))
+                                .quote_pos(comma_pos),
+                            );
+                            has_empty_argument = true;
+                        }
+                    }
+                }
+                let ret = translate_method_call(
+                    &name,
+                    function.pos,
+                    operands,
                     local_variables,
                     global_variables,
-                    exported_items,
-                    file,
-                    num_errors,
+                    context,
                 );
-                let mut translated_arguments = Vec::new();
+                return if has_empty_argument { None } else { ret };
+            } else {
+                // `f(x, ...)` adds a call to the candidates of `f`, which are
+                // narrowed down later by the types of the arguments.
+                let function_pos = function.pos.clone();
+                let translated_function =
+                    translate_expression(*function, local_variables, global_variables, context);
+                let mut translated_arguments = Some(Vec::new());
                 for argument in arguments {
-                    match argument {
-                        ast::ListElement::NonEmpty(argument) => {
-                            if let Some(expression) = translate_expression(
-                                argument,
-                                named_items,
-                                ty_parameters,
-                                local_variables,
-                                global_variables,
-                                exported_items,
-                                file,
-                                num_errors,
-                            ) {
-                                translated_arguments.push(expression);
-                            }
-                        }
+                    let translated_argument = match argument {
+                        ast::ListElement::NonEmpty(argument) => translate_expression(
+                            argument,
+                            local_variables,
+                            global_variables,
+                            context,
+                        ),
                         ast::ListElement::Empty { comma_pos } => {
-                            eprintln!("Empty argument before comma at {comma_pos}");
+                            context.logger.error(
+                                context.file,
+                                log::Diagnostic::new(format!(
+                                    "Empty argument before comma at {comma_pos}"
+                                ))
+                                .quote_pos(comma_pos),
+                            );
+                            None
                         }
+                    };
+                    match translated_argument {
+                        Some(translated_argument) => {
+                            if let Some(translated_arguments) = &mut translated_arguments {
+                                translated_arguments.push(translated_argument);
+                            }
+                        }
+                        None => translated_arguments = None,
                     }
                 }
-                let mut ret = translated_function?;
-                if let backend::Expression::Function {
-                    candidates: _,
-                    calls,
-                } = &mut ret
-                {
-                    calls.push(backend::Call {
-                        arguments: translated_arguments,
-                    });
-                    return Some(ret);
-                } else {
-                    eprintln!("Not a function");
-                    file.quote_pos(function_pos);
-                    return None;
-                }
-            } else {
-                todo!();
+                return match translated_function? {
+                    backend::Expression::Function {
+                        candidates,
+                        mut calls,
+                    } => {
+                        calls.push(backend::Call {
+                            arguments: translated_arguments?,
+                        });
+                        Some(backend::Expression::Function { candidates, calls })
+                    }
+                    _ => {
+                        context.logger.error(
+                            context.file,
+                            log::Diagnostic::new(format!(
+                                "Calling a value which is not a function at {function_pos}."
+                            ))
+                            .quote_pos(function_pos),
+                        );
+                        None
+                    }
+                };
             }
         }
+        ast::Term::UnaryOperation { operator, operand } => {
+            let ast::Term::MethodName(name) = operator.term else {
+                unreachable!();
+            };
+            return translate_method_call(
+                &name,
+                operator.pos,
+                vec![operand.map(|operand| *operand)],
+                local_variables,
+                global_variables,
+                context,
+            );
+        }
+        ast::Term::BinaryOperation {
+            left_operand,
+            operator,
+            right_operand,
+        } => {
+            let ast::Term::MethodName(name) = operator.term else {
+                unreachable!();
+            };
+            let mut operands = vec![
+                left_operand.map(|operand| *operand),
+                right_operand.map(|operand| *operand),
+            ];
+            if name == "contains" {
+                // `x in s` is `s.contains(x)`, where the receiver comes first.
+                operands.reverse();
+            }
+            return translate_method_call(
+                &name,
+                operator.pos,
+                operands,
+                local_variables,
+                global_variables,
+                context,
+            );
+        }
+        ast::Term::Assignment {
+            left_hand_side,
+            operator,
+            right_hand_side,
+        } => {
+            let ast::Term::MethodName(name) = operator.term else {
+                unreachable!();
+            };
+            return translate_assignment(
+                &name,
+                operator.pos,
+                left_hand_side.map(|target| *target),
+                right_hand_side.map(|value| *value),
+                local_variables,
+                global_variables,
+                context,
+            );
+        }
         ast::Term::TypeAnnotation {
             term_left,
             colon_pos,
             term_right,
         } => {
-            translate_expression(
-                *term_left,
-                named_items,
-                ty_parameters,
-                local_variables,
-                global_variables,
-                exported_items,
-                file,
-                num_errors,
-            );
+            translate_expression(*term_left, local_variables, global_variables, context);
             if let Some(ty) = term_right {
                 translate_ty(
                     *ty,
-                    named_items,
-                    ty_parameters,
-                    exported_items,
-                    file,
-                    num_errors,
+                    context.named_items,
+                    context.ty_parameters,
+                    context.exported_items,
+                    context.file,
+                    context.logger,
                 );
             } else {
-                eprintln!("Missing type after colon at {colon_pos}");
+                context.logger.error(
+                    context.file,
+                    log::Diagnostic::new(format!("Missing type after colon at {colon_pos}"))
+                        .quote_pos(colon_pos),
+                );
                 return None;
             }
-            todo!();
+            return report_unsupported("Type annotation", expression.pos, context);
+        }
+        ast::Term::Parenthesized { inner } => {
+            return translate_expression(*inner, local_variables, global_variables, context);
+        }
+        ast::Term::Conjunction { .. } => {
+            return report_unsupported("`&&`", expression.pos, context);
+        }
+        ast::Term::Disjunction { .. } => {
+            return report_unsupported("`||`", expression.pos, context);
+        }
+        ast::Term::Tuple { .. } => {
+            return report_unsupported("Tuple", expression.pos, context);
+        }
+        ast::Term::FieldByNumber { .. } => {
+            return report_unsupported("Tuple element access", expression.pos, context);
+        }
+        ast::Term::TypeParameters { .. } => {
+            return report_unsupported("Explicit type argument", expression.pos, context);
+        }
+        ast::Term::IntegerTy | ast::Term::FloatTy | ast::Term::ReturnType { .. } => {
+            let pos = expression.pos;
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!("Type at {pos} is used as a value.")).quote_pos(pos),
+            );
+            return None;
+        }
+        ast::Term::Identity | ast::Term::MethodName(_) => {
+            let pos = expression.pos;
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!("Expected a value at {pos}.")).quote_pos(pos),
+            );
+            return None;
         }
-        _ => todo!(),
     };
     match item {
         Item::Function(candidates) => Some(backend::Expression::Function {
             candidates: candidates.clone(),
             calls: vec![],
         }),
-        _ => todo!(),
+        Item::HostVariable(index) => Some(backend::Expression::Function {
+            candidates: vec![backend::Function::Deref],
+            calls: vec![backend::Call {
+                arguments: vec![backend::Expression::HostVariable(*index)],
+            }],
+        }),
+        Item::Constant(value) => Some(backend::Expression::Constant(value.clone())),
+        // The name of a structure is its constructor, like a variant of an
+        // enumeration.
+        Item::Ty(backend::TyBuilder::Constructor(backend::TyConstructor::Structure(
+            structure_index,
+        ))) => Some(backend::Expression::Function {
+            candidates: vec![backend::Function::Construct {
+                structure_index: *structure_index,
+            }],
+            calls: vec![],
+        }),
+        Item::Ty(_) => {
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!("Type at {} is used as a value.", expression.pos))
+                    .quote_pos(expression.pos),
+            );
+            None
+        }
+        Item::Import(_) => {
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!(
+                    "Imported file at {} is used as a value.",
+                    expression.pos
+                ))
+                .quote_pos(expression.pos),
+            );
+            None
+        }
+        // A global variable of an imported file.
+        Item::GlobalVariable(index) => Some(backend::Expression::Function {
+            candidates: vec![backend::Function::Deref],
+            calls: vec![backend::Call {
+                arguments: vec![backend::Expression::GlobalVariable(*index)],
+            }],
+        }),
     }
 }
 
-fn translate_reference(
-    expression: ast::TermWithPos,
-    named_items: &HashMap<String, Item>,
-    ty_parameters: &HashMap<String, usize>,
+/**
+ * Reports that `description` at `pos` is parsed but cannot be translated
+ * yet.
+ */
+fn report_unsupported(
+    description: &str,
+    pos: log::Pos,
+    context: &mut Context,
+) -> Option<backend::Expression> {
+    context.logger.error(
+        context.file,
+        log::Diagnostic::new(format!("{description} at {pos} is not supported yet."))
+            .quote_pos(pos),
+    );
+    None
+}
+
+/**
+ * Translates a string literal at `pos`. One without placeholders is a
+ * constant.
+ */
+fn translate_string_literal(
+    components: Vec<ast::StringLiteralComponent>,
+    pos: log::Pos,
     local_variables: Option<&HashMap<String, usize>>,
     global_variables: &HashMap<String, usize>,
-    exported_items: &Vec<HashMap<String, Item>>,
-    file: &log::File,
-    num_errors: &mut u32,
+    context: &mut Context,
 ) -> Option<backend::Expression> {
-    let item = match expression.term {
-        ast::Term::Identifier(name) => {
-            if let Some(local_variables) = local_variables {
-                if let Some(&index) = local_variables.get(&name) {
-                    return Some(backend::Expression::LocalVariable(index));
-                }
+    let mut pieces = Some(Vec::new());
+    for component in components {
+        let piece = match component {
+            ast::StringLiteralComponent::String(string) => {
+                Some(backend::InterpolationPiece::String(string))
             }
-            if let Some(&index) = global_variables.get(&name) {
-                return Some(backend::Expression::GlobalVariable(index));
+            ast::StringLiteralComponent::PlaceHolder {
+                format,
+                value,
+                spec,
+            } => {
+                if !format.is_empty() {
+                    context.logger.error(
+                        context.file,
+                        log::Diagnostic::new(format!(
+                            "Unknown format `{format}` before a placeholder in the string literal at {pos}."
+                        ))
+                        .quote_pos(pos.clone()),
+                    );
+                }
+                let value = match value {
+                    Some(value) => {
+                        translate_expression(value, local_variables, global_variables, context)
+                    }
+                    None => {
+                        context.logger.error(
+                            context.file,
+                            log::Diagnostic::new(format!(
+                                "Missing value in a placeholder in the string literal at {pos}."
+                            ))
+                            .quote_pos(pos.clone()),
+                        );
+                        None
+                    }
+                };
+                value.filter(|_| format.is_empty()).map(|value| {
+                    backend::InterpolationPiece::Value {
+                        value,
+                        spec: spec.unwrap_or_default(),
+                    }
+                })
             }
-            match named_items.get(&name) {
-                Some(item) => item,
-                None => return None,
+        };
+        match piece {
+            Some(piece) => {
+                if let Some(pieces) = &mut pieces {
+                    pieces.push(piece);
+                }
             }
+            None => pieces = None,
         }
-        ast::Term::FieldByName { term_left, name } => {
-            todo!();
-        }
-        _ => todo!(),
-    };
-    todo!();
+    }
+    let mut pieces = pieces?;
+    match &mut pieces[..] {
+        [] => Some(backend::Expression::Constant(backend::Constant::String(
+            String::new(),
+        ))),
+        [backend::InterpolationPiece::String(string)] => Some(backend::Expression::Constant(
+            backend::Constant::String(std::mem::take(string)),
+        )),
+        _ => Some(backend::Expression::Interpolation(pieces)),
+    }
 }
 
-enum ImportOrExpression {
-    Import(usize),
-    Expression(backend::Expression),
+/**
+ * Translates a call of the method `name`, either by a method call
+ * `x.name(y, ...)` or by an operator at `pos`. The candidates are all the
+ * methods with the name, to be narrowed down by the types of `operands`.
+ */
+fn translate_method_call(
+    name: &str,
+    pos: log::Pos,
+    operands: Vec<Option<ast::TermWithPos>>,
+    local_variables: Option<&HashMap<String, usize>>,
+    global_variables: &HashMap<String, usize>,
+    context: &mut Context,
+) -> Option<backend::Expression> {
+    let candidates = context.methods.get(name).cloned();
+    if candidates.is_none() {
+        context.logger.error(
+            context.file,
+            log::Diagnostic::new(format!("No method `{name}` is defined, used at {pos}."))
+                .quote_pos(pos.clone()),
+        );
+    }
+    let mut arguments = Some(Vec::new());
+    for operand in operands {
+        let translated_operand = match operand {
+            Some(operand) => {
+                translate_expression(operand, local_variables, global_variables, context)
+            }
+            None => {
+                context.logger.error(
+                    context.file,
+                    log::Diagnostic::new(format!("Missing operand of `{name}` at {pos}."))
+                        .quote_pos(pos.clone()),
+                );
+                None
+            }
+        };
+        match translated_operand {
+            Some(translated_operand) => {
+                if let Some(arguments) = &mut arguments {
+                    arguments.push(translated_operand);
+                }
+            }
+            None => arguments = None,
+        }
+    }
+    Some(backend::Expression::Function {
+        candidates: candidates?,
+        calls: vec![backend::Call {
+            arguments: arguments?,
+        }],
+    })
 }
 
-fn translate_import_or_expression(
-    expression: ast::TermWithPos,
-    named_items: &HashMap<String, Item>,
-    ty_parameters: &HashMap<String, usize>,
+/**
+ * Translates `a, b = x, y`, `(a, b) = (x, y)` or `a, b = t`.
+ */
+fn translate_multiple_assignment(
+    targets: Vec<ast::ListElement>,
+    pos: log::Pos,
+    value: Option<ast::TermWithPos>,
     local_variables: Option<&HashMap<String, usize>>,
     global_variables: &HashMap<String, usize>,
-    exported_items: &Vec<HashMap<String, Item>>,
-    file: &log::File,
-    num_errors: &mut u32,
-) -> Option<ImportOrExpression> {
-    let item = match expression.term {
-        ast::Term::Identifier(name) => {
-            if let Some(local_variables) = local_variables {
-                if let Some(&index) = local_variables.get(&name) {
-                    return Some(ImportOrExpression::Expression(
-                        backend::Expression::LocalVariable(index),
-                    ));
-                }
+    context: &mut Context,
+) -> Option<backend::Expression> {
+    let num_targets = targets.len();
+    let mut translated_targets = Some(Vec::new());
+    for target in targets {
+        let target = match target {
+            ast::ListElement::NonEmpty(target) => {
+                translate_reference(target, local_variables, global_variables, context)
             }
-            if let Some(&index) = global_variables.get(&name) {
-                return Some(ImportOrExpression::Expression(
-                    backend::Expression::GlobalVariable(index),
-                ));
+            ast::ListElement::Empty { comma_pos } => {
+                context.logger.error(
+                    context.file,
+                    log::Diagnostic::new(format!("Missing target next to `,` at {comma_pos}."))
+                        .quote_pos(comma_pos),
+                );
+                None
             }
-            match named_items.get(&name) {
-                Some(item) => item,
-                None => return None,
+        };
+        match target {
+            Some(target) => {
+                if let Some(translated_targets) = &mut translated_targets {
+                    translated_targets.push(target);
+                }
             }
+            None => translated_targets = None,
         }
-        ast::Term::FieldByName { term_left, name } => {
-            todo!();
+    }
+    let values = match value {
+        Some(value) => translate_assigned_values(
+            value,
+            num_targets,
+            local_variables,
+            global_variables,
+            context,
+        ),
+        None => {
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!("Missing right-hand side of `assign` at {pos}."))
+                    .quote_pos(pos),
+            );
+            None
         }
-        _ => todo!(),
     };
-    match item {
-        Item::Import(index) => Some(ImportOrExpression::Import(*index)),
-        Item::Function(candidates) => Some(ImportOrExpression::Expression(
-            backend::Expression::Function {
-                candidates: candidates.clone(),
-                calls: vec![],
+    Some(values?.assign_to(translated_targets?))
+}
+
+/**
+ * The right-hand side of `a, b = ...`.
+ */
+enum AssignedValues {
+    /**
+     * `a, b = x, y`.
+     */
+    Each(Vec<backend::Expression>),
+    /**
+     * `a, b = t`, where `t` is a tuple.
+     */
+    Destructured(backend::Expression),
+}
+
+impl AssignedValues {
+    fn assign_to(self, targets: Vec<backend::Expression>) -> backend::Expression {
+        match self {
+            AssignedValues::Each(values) => {
+                backend::Expression::MultipleAssignment { targets, values }
+            }
+            AssignedValues::Destructured(value) => backend::Expression::Destructuring {
+                targets,
+                value: Box::new(value),
             },
-        )),
-        Item::GlobalVariable(index) => Some(ImportOrExpression::Expression(
-            backend::Expression::GlobalVariable(*index),
-        )),
-        _ => todo!(),
+        }
+    }
+}
+
+/**
+ * Translates the right-hand side of an assignment to `num_targets` targets.
+ * Values separated by `,` must be as many as the targets, while a single
+ * value is destructured.
+ */
+fn translate_assigned_values(
+    value: ast::TermWithPos,
+    num_targets: usize,
+    local_variables: Option<&HashMap<String, usize>>,
+    global_variables: &HashMap<String, usize>,
+    context: &mut Context,
+) -> Option<AssignedValues> {
+    match value {
+        ast::TermWithPos {
+            term: ast::Term::Tuple { elements },
+            pos,
+        } => {
+            if elements.len() != num_targets {
+                context.logger.error(
+                    context.file,
+                    log::Diagnostic::new(format!(
+                        "Expected {num_targets} values at {pos}, found {}.",
+                        elements.len()
+                    ))
+                    .quote_pos(pos),
+                );
+                return None;
+            }
+            let mut values = Some(Vec::new());
+            for element in elements {
+                let value = match element {
+                    ast::ListElement::NonEmpty(value) => {
+                        translate_expression(value, local_variables, global_variables, context)
+                    }
+                    ast::ListElement::Empty { comma_pos } => {
+                        context.logger.error(
+                            context.file,
+                            log::Diagnostic::new(format!(
+                                "Missing value next to `,` at {comma_pos}."
+                            ))
+                            .quote_pos(comma_pos),
+                        );
+                        None
+                    }
+                };
+                match value {
+                    Some(value) => {
+                        if let Some(values) = &mut values {
+                            values.push(value);
+                        }
+                    }
+                    None => values = None,
+                }
+            }
+            values.map(AssignedValues::Each)
+        }
+        // The number of the elements is checked with the type.
+        value => translate_expression(value, local_variables, global_variables, context)
+            .map(AssignedValues::Destructured),
     }
 }
 
-fn translate_import_or_reference(
+/**
+ * Returns the index of the file imported as `term` in `term.name`, unless
+ * `term` is the name of a local or global variable, which hides the items
+ * with the same name.
+ */
+fn translate_qualifier(
+    term: &ast::TermWithPos,
+    local_variables: Option<&HashMap<String, usize>>,
+    global_variables: &HashMap<String, usize>,
+    context: &Context,
+) -> Option<usize> {
+    if let ast::Term::Identifier(name) = &term.term {
+        if local_variables.is_some_and(|local_variables| local_variables.contains_key(name))
+            || global_variables.contains_key(name)
+        {
+            return None;
+        }
+    }
+    translate_import(term, context.named_items, context.exported_items)
+}
+
+/**
+ * Returns the candidates for the field `name` of any structure, made with
+ * `function` (`Field` or `FieldRef`), which are narrowed down later by the
+ * type of the structure.
+ */
+fn field_candidates(
+    name: &str,
+    pos: log::Pos,
+    function: impl Fn(usize, usize) -> backend::Function,
+    context: &mut Context,
+) -> Option<Vec<backend::Function>> {
+    match context.fields.get(name) {
+        Some(fields) => Some(
+            fields
+                .iter()
+                .map(|&(structure_index, field_index)| function(structure_index, field_index))
+                .collect(),
+        ),
+        None => {
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!("No field `{name}` is defined, used at {pos}."))
+                    .quote_pos(pos),
+            );
+            None
+        }
+    }
+}
+
+/**
+ * Translates `expression` into a reference to the variable it names, such
+ * as the target of an assignment.
+ */
+fn translate_reference(
     expression: ast::TermWithPos,
-    named_items: &HashMap<String, Item>,
-    ty_parameters: &HashMap<String, usize>,
     local_variables: Option<&HashMap<String, usize>>,
     global_variables: &HashMap<String, usize>,
-    exported_items: &Vec<HashMap<String, Item>>,
-    file: &log::File,
-    num_errors: &mut u32,
-) -> Option<ImportOrExpression> {
+    context: &mut Context,
+) -> Option<backend::Expression> {
     let item = match expression.term {
         ast::Term::Identifier(name) => {
             if let Some(local_variables) = local_variables {
                 if let Some(&index) = local_variables.get(&name) {
-                    return Some(ImportOrExpression::Expression(
-                        backend::Expression::LocalVariable(index),
-                    ));
+                    return Some(backend::Expression::LocalVariable(index));
                 }
             }
             if let Some(&index) = global_variables.get(&name) {
-                return Some(ImportOrExpression::Expression(
-                    backend::Expression::GlobalVariable(index),
-                ));
-            }
-            match named_items.get(&name) {
-                Some(item) => item,
-                None => return None,
+                return Some(backend::Expression::GlobalVariable(index));
             }
+            context.named_items.get(&name)
         }
+        // A variable of an imported file or a namespace of the host items,
+        // or a field of a structure.
         ast::Term::FieldByName { term_left, name } => {
-            todo!();
+            let file_index =
+                translate_qualifier(&term_left, local_variables, global_variables, context);
+            match file_index {
+                Some(file_index) => context.exported_items[file_index].get(&name),
+                None => {
+                    let candidates = field_candidates(
+                        &name,
+                        expression.pos,
+                        |structure_index, field_index| backend::Function::FieldRef {
+                            structure_index,
+                            field_index,
+                        },
+                        context,
+                    );
+                    let structure =
+                        translate_reference(*term_left, local_variables, global_variables, context);
+                    return Some(backend::Expression::Function {
+                        candidates: candidates?,
+                        calls: vec![backend::Call {
+                            arguments: vec![structure?],
+                        }],
+                    });
+                }
+            }
         }
-        _ => todo!(),
+        _ => None,
     };
     match item {
-        Item::Import(index) => Some(ImportOrExpression::Import(*index)),
-        Item::Function(candidates) => Some(ImportOrExpression::Expression(
-            backend::Expression::Function {
-                candidates: candidates.clone(),
-                calls: vec![],
-            },
-        )),
-        Item::GlobalVariable(index) => Some(ImportOrExpression::Expression(
-            backend::Expression::GlobalVariable(*index),
-        )),
-        _ => todo!(),
+        Some(Item::GlobalVariable(index)) => Some(backend::Expression::GlobalVariable(*index)),
+        Some(Item::HostVariable(index)) => Some(backend::Expression::HostVariable(*index)),
+        _ => {
+            let pos = expression.pos;
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!("Expected a variable at {pos}.")).quote_pos(pos),
+            );
+            None
+        }
+    }
+}
+
+/**
+ * Translates `target = value`, or a compound assignment such as
+ * `target += value` if `name` is not `assign`.
+ */
+fn translate_assignment(
+    name: &str,
+    pos: log::Pos,
+    target: Option<ast::TermWithPos>,
+    value: Option<ast::TermWithPos>,
+    local_variables: Option<&HashMap<String, usize>>,
+    global_variables: &HashMap<String, usize>,
+    context: &mut Context,
+) -> Option<backend::Expression> {
+    let target = match target {
+        Some(ast::TermWithPos {
+            term: ast::Term::Tuple { elements },
+            ..
+        }) if name == "assign" => {
+            return translate_multiple_assignment(
+                elements,
+                pos,
+                value,
+                local_variables,
+                global_variables,
+                context,
+            );
+        }
+        target => target,
+    };
+    // `add_assign` applies `add`, for example.
+    let operator = name.strip_suffix("_assign").map(|operator| {
+        let candidates = context.methods.get(operator).cloned();
+        if candidates.is_none() {
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!("No method `{operator}` is defined, used at {pos}."))
+                    .quote_pos(pos.clone()),
+            );
+        }
+        candidates
+    });
+    let target = match target {
+        Some(target) => translate_reference(target, local_variables, global_variables, context),
+        None => {
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!("Missing left-hand side of `{name}` at {pos}."))
+                    .quote_pos(pos.clone()),
+            );
+            None
+        }
+    };
+    let value = match value {
+        Some(value) => translate_expression(value, local_variables, global_variables, context),
+        None => {
+            context.logger.error(
+                context.file,
+                log::Diagnostic::new(format!("Missing right-hand side of `{name}` at {pos}."))
+                    .quote_pos(pos),
+            );
+            None
+        }
+    };
+    let (target, value) = (target?, value?);
+    match operator {
+        Some(operator) => Some(backend::Expression::CompoundAssignment {
+            target: Box::new(target),
+            operator: operator?,
+            value: Box::new(value),
+        }),
+        None => Some(backend::Expression::Function {
+            candidates: vec![backend::Function::Assign],
+            calls: vec![backend::Call {
+                arguments: vec![target, value],
+            }],
+        }),
     }
 }
 
+/**
+ * Describes `term` if it is used as a statement but has no effect other
+ * than producing a value, which is likely a mistake. Operators are assumed
+ * to have no side effects, while calls of functions and methods may have.
+ */
+fn discarded_value_kind(term: &ast::Term) -> Option<&'static str> {
+    match term {
+        ast::Term::BinaryOperation { operator, .. } => match &operator.term {
+            ast::Term::MethodName(name)
+                if matches!(
+                    name.as_str(),
+                    "equal"
+                        | "not_equal"
+                        | "less"
+                        | "less_or_equal"
+                        | "greater"
+                        | "greater_or_equal"
+                        | "contains"
+                ) =>
+            {
+                Some("comparison")
+            }
+            _ => Some("operation"),
+        },
+        ast::Term::UnaryOperation { .. }
+        | ast::Term::Conjunction { .. }
+        | ast::Term::Disjunction { .. } => Some("operation"),
+        ast::Term::NumericLiteral(_)
+        | ast::Term::IntegerLiteral { .. }
+        | ast::Term::StringLiteral(_) => Some("literal"),
+        ast::Term::Identifier(_) | ast::Term::FieldByName { .. } => Some("expression"),
+        ast::Term::Parenthesized { inner } => discarded_value_kind(&inner.term),
+        _ => None,
+    }
+}
+
+/**
+ * Methods available without definition, for the builtin types.
+ */
+fn builtin_methods() -> HashMap<String, Vec<backend::Function>> {
+    HashMap::from([
+        (
+            String::from("add"),
+            vec![
+                backend::Function::IAdd,
+                backend::Function::FAdd,
+                backend::Function::SConcat,
+            ],
+        ),
+        (
+            String::from("sub"),
+            vec![backend::Function::ISub, backend::Function::FSub],
+        ),
+        (
+            String::from("mul"),
+            vec![
+                backend::Function::IMul,
+                backend::Function::FMul,
+                backend::Function::SRepeat,
+            ],
+        ),
+        (String::from("contains"), vec![backend::Function::SContains]),
+        (
+            String::from("div"),
+            vec![backend::Function::IDiv, backend::Function::FDiv],
+        ),
+        (
+            String::from("rem"),
+            vec![backend::Function::IRem, backend::Function::FRem],
+        ),
+        (
+            String::from("pow"),
+            vec![backend::Function::IPow, backend::Function::FPow],
+        ),
+        (
+            String::from("minus"),
+            vec![backend::Function::INeg, backend::Function::FNeg],
+        ),
+        (
+            String::from("equal"),
+            vec![backend::Function::IEqual, backend::Function::FEqual],
+        ),
+        (
+            String::from("not_equal"),
+            vec![backend::Function::INotEqual, backend::Function::FNotEqual],
+        ),
+        (
+            String::from("less"),
+            vec![backend::Function::ILess, backend::Function::FLess],
+        ),
+        (
+            String::from("less_or_equal"),
+            vec![
+                backend::Function::ILessOrEqual,
+                backend::Function::FLessOrEqual,
+            ],
+        ),
+        (
+            String::from("greater"),
+            vec![backend::Function::IGreater, backend::Function::FGreater],
+        ),
+        (
+            String::from("greater_or_equal"),
+            vec![
+                backend::Function::IGreaterOrEqual,
+                backend::Function::FGreaterOrEqual,
+            ],
+        ),
+        (
+            String::from("logical_not"),
+            vec![backend::Function::LogicalNot],
+        ),
+        (
+            String::from("bitwise_and"),
+            vec![backend::Function::IBitAnd],
+        ),
+        (String::from("bitwise_or"), vec![backend::Function::IBitOr]),
+        (
+            String::from("bitwise_xor"),
+            vec![backend::Function::IBitXor],
+        ),
+        (
+            String::from("bitwise_not"),
+            vec![backend::Function::IBitNot],
+        ),
+        (
+            String::from("left_shift"),
+            vec![backend::Function::ILeftShift],
+        ),
+        (
+            String::from("right_shift"),
+            vec![backend::Function::IRightShift],
+        ),
+    ])
+}
+
+/**
+ * What a name refers to.
+ */
 #[derive(Clone)]
-enum Item {
+pub enum Item {
+    /**
+     * An imported file, with its index.
+     */
     Import(usize),
     Ty(backend::TyBuilder),
+    /**
+     * Functions or enumeration variants, one of which is chosen by the types
+     * of the arguments.
+     */
     Function(Vec<backend::Function>),
     GlobalVariable(usize),
+    /**
+     * A global variable provided by the application, with the index in
+     * [`backend::Definitions::host_variables`].
+     */
+    HostVariable(usize),
+    Constant(backend::Constant),
 }
@@ -20,14 +20,18 @@
  * Defines the Abstract Syntax Tree (AST) and its parser.
  */
 
+pub mod grammar;
 mod tests;
+use std::collections::VecDeque;
+
 use super::CharsPeekable;
+use crate::backend::FormatSpec;
 use crate::log::{Index, ParseError, Pos};
-use enum_iterator::Sequence;
 
 /**
  * The Abstract Syntax Tree (AST) for the entire file.
  */
+#[derive(Debug)]
 pub struct File {
     /**
      * List of import statements in the file.
@@ -37,19 +41,39 @@ pub struct File {
      * List of structure names defined in the file.
      */
     pub structure_names: Vec<StructureName>,
+    /**
+     * List of enumeration definitions in the file.
+     */
+    pub enumeration_definitions: Vec<EnumerationDefinition>,
     /**
      * List of function names defined in the file.
      */
     pub function_names: Vec<FunctionName>,
+    /**
+     * List of constant definitions in the file.
+     */
+    pub constant_definitions: Vec<ConstantDefinition>,
     /**
      * Top-level statements in the file (includes function definitions).
      */
     pub top_level_statements: Vec<TopLevelStatement>,
+    /**
+     * Lints allowed by `-- syscraws:allow(...)` comments, with the
+     * zero-based line following each comment.
+     */
+    pub allowed_lints: Vec<(usize, String)>,
+    /**
+     * The zero-based lines of the start and `end` of each block, in the
+     * order of the `end`s, e.g. for folding them in editors. A block ended
+     * by `else` ends at the line before it.
+     */
+    pub blocks: Vec<(usize, usize)>,
 }
 
 /**
  * An import statement in the AST.
  */
+#[derive(Debug)]
 pub struct Import {
     /**
      * Position of the keyword `import` at the beginning.
@@ -62,27 +86,65 @@ pub struct Import {
     pub extra_tokens_pos: Option<Pos>,
 }
 
+/**
+ * A constant definition `const NAME = value` in the AST.
+ */
+#[derive(Debug)]
+pub struct ConstantDefinition {
+    /**
+     * Position of the keyword `const` at the beginning.
+     */
+    pub keyword_const_pos: Pos,
+    /**
+     * The name and value, in the form of an assignment.
+     */
+    pub term: Option<TermWithPos>,
+    /**
+     * Text of the doc comments (`---`) just before the item.
+     */
+    pub doc_comment: Option<String>,
+}
+
 /**
  * A structure name in the AST.
  */
+#[derive(Debug)]
 pub struct StructureName {
     pub keyword_struct_pos: Pos,
     pub name: Option<String>,
     pub extra_tokens_pos: Option<Pos>,
+    /**
+     * Text of the doc comments (`---`) just before the item.
+     */
+    pub doc_comment: Option<String>,
 }
 
 /**
  * A function name in the AST.
  */
+#[derive(Debug)]
 pub struct FunctionName {
+    /**
+     * Position of the keyword `func` or `method`.
+     */
     pub keyword_func_pos: Pos,
+    /**
+     * Whether defined with `method`, in which case the name is used for
+     * method calls and operators instead of function calls.
+     */
+    pub is_method: bool,
     pub name: Option<String>,
     pub extra_tokens_pos: Option<Pos>,
+    /**
+     * Text of the doc comments (`---`) just before the item.
+     */
+    pub doc_comment: Option<String>,
 }
 
 /**
  * A top-level statement in the AST.
  */
+#[derive(Debug)]
 pub enum TopLevelStatement {
     /**
      * A structure definition.
@@ -92,6 +154,10 @@ pub enum TopLevelStatement {
      * A function definition.
      */
     FunctionDefinition(FunctionDefinition),
+    /**
+     * A test or benchmark definition.
+     */
+    TestDefinition(TestDefinition),
     /**
      * A regular statement.
      */
@@ -101,6 +167,7 @@ pub enum TopLevelStatement {
 /**
  * A structure definition in the AST.
  */
+#[derive(Debug)]
 pub struct StructureDefinition {
     /**
      * List of type parameters.
@@ -119,17 +186,58 @@ pub struct StructureDefinition {
 /**
  * A structure field in the AST.
  */
+#[derive(Debug)]
 pub struct StructureField {
     pub field: TermWithPos,
     pub extra_tokens_pos: Option<Pos>,
 }
 
+/**
+ * An enumeration definition in the AST.
+ */
+#[derive(Debug)]
+pub struct EnumerationDefinition {
+    pub keyword_enum_pos: Pos,
+    pub name: Option<String>,
+    /**
+     * List of type parameters.
+     */
+    pub ty_parameters: Option<Vec<ListElement>>,
+    /**
+     * [`Pos`] of extra tokens after the name and type parameters.
+     */
+    pub extra_tokens_after_name_pos: Option<Pos>,
+    /**
+     * List of variants of the enumeration.
+     */
+    pub variants: Vec<EnumerationVariant>,
+    /**
+     * [`Pos`] of extra tokens after `end`.
+     */
+    pub extra_tokens_pos: Option<Pos>,
+    /**
+     * Text of the doc comments (`---`) just before the item.
+     */
+    pub doc_comment: Option<String>,
+}
+
+/**
+ * A variant of an enumeration in the AST: either a name, or a name
+ * followed by `:` and the type of the payload.
+ */
+#[derive(Debug)]
+pub struct EnumerationVariant {
+    pub variant: TermWithPos,
+    pub extra_tokens_pos: Option<Pos>,
+}
+
 /**
  * A function definition in the AST.
  *
  * The function name is stored in [`File::function_names`], so it is not
  * included here.
  */
+#[derive(Debug)]
 pub struct FunctionDefinition {
     /**
      * List of type parameters.
@@ -153,9 +261,42 @@ pub struct FunctionDefinition {
     pub extra_tokens_pos: Option<Pos>,
 }
 
+/**
+ * A test definition (`test "name"` ... `end`) or a benchmark definition
+ * (`bench "name"` ... `end`) in the AST.
+ */
+#[derive(Debug)]
+pub struct TestDefinition {
+    /**
+     * Position of the keyword `test` or `bench`.
+     */
+    pub keyword_test_pos: Pos,
+    /**
+     * Whether the keyword is `bench`.
+     */
+    pub is_benchmark: bool,
+    /**
+     * The name of the test, which should be a string literal.
+     */
+    pub name: Option<TermWithPos>,
+    /**
+     * [`Pos`] of extra tokens after the name.
+     */
+    pub extra_tokens_after_name_pos: Option<Pos>,
+    /**
+     * Body of the test.
+     */
+    pub body: Vec<Statement>,
+    /**
+     * [`Pos`] of extra tokens after `end`.
+     */
+    pub extra_tokens_pos: Option<Pos>,
+}
+
 /**
  * Return type of a function in the AST.
  */
+#[derive(Debug)]
 pub struct ReturnType {
     /**
      * Position of `:`.
@@ -170,6 +311,7 @@ pub struct ReturnType {
 /**
  * A statement in the AST.
  */
+#[derive(Debug)]
 pub enum Statement {
     /**
      * Declaration of a variable.
@@ -205,12 +347,83 @@ pub enum Statement {
          */
         body: Vec<Statement>,
     },
+    /**
+     * If statement. `else if` is an `else` whose body is another if
+     * statement, sharing the `end`.
+     */
+    If {
+        /**
+         * Position of the keyword `if`.
+         */
+        keyword_if_pos: Pos,
+        /**
+         * The condition.
+         */
+        condition: Option<TermWithPos>,
+        /**
+         * The body executed when the condition holds.
+         */
+        body: Vec<Statement>,
+        /**
+         * Position of the keyword `else` and the body executed otherwise,
+         * if any.
+         */
+        else_body: Option<(Pos, Vec<Statement>)>,
+    },
+    /**
+     * `break`, which exits the innermost loop.
+     */
+    Break {
+        /**
+         * Position of the keyword `break`.
+         */
+        keyword_break_pos: Pos,
+    },
+    /**
+     * `continue`, which goes on to the next iteration of the innermost loop.
+     */
+    Continue {
+        /**
+         * Position of the keyword `continue`.
+         */
+        keyword_continue_pos: Pos,
+    },
+    /**
+     * `return`, with the returned value if any.
+     */
+    Return {
+        /**
+         * Position of the keyword `return`.
+         */
+        keyword_return_pos: Pos,
+        /**
+         * The returned value.
+         */
+        value: Option<TermWithPos>,
+    },
+    /**
+     * Assertion.
+     */
+    Assert {
+        /**
+         * Position of the keyword `assert`.
+         */
+        keyword_assert_pos: Pos,
+        /**
+         * The condition.
+         */
+        condition: Option<TermWithPos>,
+        /**
+         * The message after a comma, if any.
+         */
+        message: Option<TermWithPos>,
+    },
 }
 
 /**
  * Pair of a [`Term`] and its [`Pos`].
  */
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct TermWithPos {
     pub term: Term,
     pub pos: Pos,
@@ -219,12 +432,20 @@ pub struct TermWithPos {
 /**
  * A term in the AST, representing an expression, a type, or an import name.
  */
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Term {
     /**
      * A numeric literal, either integer or floating-point number.
      */
     NumericLiteral(String),
+    /**
+     * An integer literal with a radix prefix (`0x`, `0b` or `0o`). The
+     * digits are not validated yet.
+     */
+    IntegerLiteral {
+        radix: u32,
+        digits: String,
+    },
     /**
      * A string literal.
      */
@@ -326,48 +547,94 @@ pub enum Term {
 /**
  * A component of a string literal in the AST.
  */
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum StringLiteralComponent {
     String(String),
     PlaceHolder {
         format: String,
         value: Option<TermWithPos>,
+        /**
+         * The format spec after `:` (e.g. `04` in `${x:04}`).
+         */
+        spec: Option<FormatSpec>,
     },
 }
 
 /**
  * An element of a list in the AST.
  */
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum ListElement {
     NonEmpty(TermWithPos),
     Empty { comma_pos: Pos },
 }
 
+/**
+ * The default of how deeply blocks and terms may be nested in
+ * [`parse_file`], which keeps the recursive descent from overflowing the
+ * stack.
+ */
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 64;
+
 /**
  * Parses a file.
  */
 pub fn parse_file(chars_peekable: &mut CharsPeekable) -> Result<File, ParseError> {
+    parse_file_with_max_nesting_depth(chars_peekable, DEFAULT_MAX_NESTING_DEPTH)
+}
+
+/**
+ * Same as [`parse_file`], with a limit other than
+ * [`DEFAULT_MAX_NESTING_DEPTH`].
+ *
+ * # Errors
+ * - [`ParseError::TooDeeplyNested`]\: Blocks or terms are nested more
+ *   deeply than `max_nesting_depth`.
+ */
+pub fn parse_file_with_max_nesting_depth(
+    chars_peekable: &mut CharsPeekable,
+    max_nesting_depth: usize,
+) -> Result<File, ParseError> {
     let mut parser = Parser::new(chars_peekable)?;
+    parser.max_nesting_depth = max_nesting_depth;
     let mut file = File {
         imports: Vec::new(),
         structure_names: Vec::new(),
+        enumeration_definitions: Vec::new(),
         function_names: Vec::new(),
+        constant_definitions: Vec::new(),
         top_level_statements: Vec::new(),
+        allowed_lints: Vec::new(),
+        blocks: Vec::new(),
     };
     while let Some(item_start_token) = &mut parser.current.token {
+        let doc_comment = parser.current.doc_comment.take();
         if let Token::KeywordImport = item_start_token {
             file.imports.push(parser.parse_import()?);
         } else if let Token::KeywordStruct = item_start_token {
-            let (name, definition) = parser.parse_structure_definition()?;
+            let (mut name, definition) = parser.parse_structure_definition()?;
+            name.doc_comment = doc_comment;
             file.structure_names.push(name);
             file.top_level_statements
                 .push(TopLevelStatement::StructureDefinition(definition));
-        } else if let Token::KeywordFunc = item_start_token {
-            let (name, definition) = parser.parse_function_definition()?;
+        } else if let Token::KeywordEnum = item_start_token {
+            let mut definition = parser.parse_enumeration_definition()?;
+            definition.doc_comment = doc_comment;
+            file.enumeration_definitions.push(definition);
+        } else if let Token::KeywordFunc | Token::KeywordMethod = item_start_token {
+            let (mut name, definition) = parser.parse_function_definition()?;
+            name.doc_comment = doc_comment;
             file.function_names.push(name);
             file.top_level_statements
                 .push(TopLevelStatement::FunctionDefinition(definition));
+        } else if let Token::KeywordConst = item_start_token {
+            let mut definition = parser.parse_constant_definition()?;
+            definition.doc_comment = doc_comment;
+            file.constant_definitions.push(definition);
+        } else if parser.is_test_definition_start()? {
+            let definition = parser.parse_test_definition()?;
+            file.top_level_statements
+                .push(TopLevelStatement::TestDefinition(definition));
         } else if let Some(statement) = parser.parse_statement(&mut Vec::new())? {
             file.top_level_statements
                 .push(TopLevelStatement::Statement(statement));
@@ -375,9 +642,171 @@ pub fn parse_file(chars_peekable: &mut CharsPeekable) -> Result<File, ParseError
             return Err(ParseError::UnexpectedToken(parser.current_pos()));
         }
     }
+    file.allowed_lints = parser.allowed_lints;
+    file.blocks = parser.blocks;
     Ok(file)
 }
 
+/**
+ * Reads tokens until EOF, discarding them.
+ */
+pub fn read_all_tokens(chars_peekable: &mut CharsPeekable) -> Result<(), ParseError> {
+    let mut parser = Parser::new(chars_peekable)?;
+    while parser.current.token.is_some() {
+        parser.consume_token()?;
+    }
+    Ok(())
+}
+
+/**
+ * The class of a token, for syntax highlighting.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenClass {
+    Keyword,
+    Identifier,
+    /**
+     * A numeric or string literal.
+     */
+    Literal,
+    /**
+     * An operator or a punctuation, such as `+` and `(`.
+     */
+    Operator,
+    Comment,
+}
+
+/**
+ * Reads tokens until EOF, and returns the class and the position of each
+ * token and comment in order of appearance.
+ */
+pub fn classify_tokens(
+    chars_peekable: &mut CharsPeekable,
+) -> Result<Vec<(TokenClass, Pos)>, ParseError> {
+    let mut parser = Parser::new(chars_peekable)?;
+    let mut tokens = Vec::new();
+    loop {
+        for pos in std::mem::take(&mut parser.current.comments) {
+            tokens.push((TokenClass::Comment, pos));
+        }
+        let Some(ref token) = parser.current.token else {
+            break;
+        };
+        let class = token.class();
+        let start = parser.current.start;
+        parser.consume_token()?;
+        tokens.push((
+            class,
+            Pos {
+                start,
+                end: parser.prev_end,
+            },
+        ));
+    }
+    Ok(tokens)
+}
+
+/**
+ * A call left open before a cursor, found by [`find_enclosing_call`].
+ */
+#[derive(Debug, PartialEq, Eq)]
+pub struct EnclosingCall {
+    /**
+     * The name of the function called as `name(...)`.
+     */
+    pub name: String,
+    /**
+     * The number of `,` between `(` and the cursor, which is the
+     * zero-based index of the argument at the cursor.
+     */
+    pub active_parameter: usize,
+}
+
+/**
+ * Reads the tokens before `cursor` and finds the innermost call `name(`
+ * whose `)` is not among them. Unlike [`parse_file`], this works on
+ * incomplete input such as `f(x, `.
+ */
+pub fn find_enclosing_call(
+    chars_peekable: &mut CharsPeekable,
+    cursor: Index,
+) -> Result<Option<EnclosingCall>, ParseError> {
+    let mut parser = Parser::new(chars_peekable)?;
+    // The brackets left open, with the call if it is `(` right after a function name.
+    let mut open_brackets: Vec<Option<EnclosingCall>> = Vec::new();
+    // The last two tokens, to tell a call `f(` from a definition `func f(`.
+    let mut prev_tokens = [None, None];
+    while let Some(token) = &parser.current.token {
+        if (parser.current.start.line, parser.current.start.column) >= (cursor.line, cursor.column)
+        {
+            break;
+        }
+        match token {
+            Token::OpeningParenthesis => open_brackets.push(match &prev_tokens {
+                [Some(Token::KeywordFunc | Token::KeywordMethod | Token::Dot), _] => None,
+                [_, Some(Token::Identifier(name))] => Some(EnclosingCall {
+                    name: name.clone(),
+                    active_parameter: 0,
+                }),
+                _ => None,
+            }),
+            Token::OpeningBracket | Token::OpeningBrace => open_brackets.push(None),
+            Token::ClosingParenthesis | Token::ClosingBracket | Token::ClosingBrace => {
+                open_brackets.pop();
+            }
+            Token::Comma => {
+                if let Some(Some(call)) = open_brackets.last_mut() {
+                    call.active_parameter += 1;
+                }
+            }
+            _ => {}
+        }
+        prev_tokens = [prev_tokens[1].take(), parser.current.token.clone()];
+        parser.consume_token()?;
+    }
+    Ok(open_brackets.into_iter().rev().find_map(|call| call))
+}
+
+/**
+ * Reads tokens up to `cursor` and returns the names in the reference
+ * `a.b.c` whose last name the cursor is in or right after, e.g. `["a",
+ * "b"]` if the cursor is in `b`.
+ */
+pub fn find_reference(
+    chars_peekable: &mut CharsPeekable,
+    cursor: Index,
+) -> Result<Option<Vec<String>>, ParseError> {
+    let mut parser = Parser::new(chars_peekable)?;
+    let cursor = (cursor.line, cursor.column);
+    let mut names = Vec::new();
+    let mut after_dot = false;
+    while let Some(token) = &parser.current.token {
+        let start = (parser.current.start.line, parser.current.start.column);
+        if start > cursor {
+            break;
+        }
+        match token {
+            Token::Identifier(name) => {
+                if !after_dot {
+                    names.clear();
+                }
+                names.push(name.clone());
+                if cursor <= (parser.current.end.line, parser.current.end.column) {
+                    return Ok(Some(names));
+                }
+                after_dot = false;
+            }
+            Token::Dot if !names.is_empty() => after_dot = true,
+            _ => {
+                names.clear();
+                after_dot = false;
+            }
+        }
+        parser.consume_token()?;
+    }
+    Ok(None)
+}
+
 /**
  * The parser used in [`parse_file`].
  */
@@ -387,10 +816,36 @@ struct Parser<'str, 'iter> {
      * Information on the current token.
      */
     current: TokenInfo,
+    /**
+     * The tokens after [`Self::current`] which have been read by
+     * [`Self::peek_nth`] but not consumed yet.
+     */
+    lookahead: VecDeque<TokenInfo>,
     /**
      * End index of the previous token.
      */
     prev_end: Index,
+    /**
+     * Whether parsing a placeholder in a string literal, where `:` starts
+     * a format spec instead of a type annotation.
+     */
+    in_placeholder: bool,
+    /**
+     * See [`File::allowed_lints`]. Collected from the tokens read so far.
+     */
+    allowed_lints: Vec<(usize, String)>,
+    /**
+     * See [`File::blocks`]. Collected from the blocks closed so far.
+     */
+    blocks: Vec<(usize, usize)>,
+    /**
+     * How many blocks and terms are being parsed, each containing the next.
+     */
+    nesting_depth: usize,
+    /**
+     * See [`parse_file_with_max_nesting_depth`].
+     */
+    max_nesting_depth: usize,
 }
 
 impl<'str, 'iter> Parser<'str, 'iter> {
@@ -402,11 +857,17 @@ impl<'str, 'iter> Parser<'str, 'iter> {
      */
     fn new(iter: &'iter mut CharsPeekable<'str>) -> Result<Parser<'str, 'iter>, ParseError> {
         let start = iter.index();
-        let first_token = read_token(iter, false)?;
+        let mut first_token = read_token(iter, false)?;
         Ok(Parser {
             iter,
+            allowed_lints: std::mem::take(&mut first_token.allowed_lints),
+            blocks: Vec::new(),
             current: first_token,
+            lookahead: VecDeque::new(),
             prev_end: start,
+            in_placeholder: false,
+            nesting_depth: 0,
+            max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH,
         })
     }
 }
@@ -414,6 +875,7 @@ impl<'str, 'iter> Parser<'str, 'iter> {
 /**
  * Information on a token.
  */
+#[derive(Clone)]
 struct TokenInfo {
     /**
      * Token.
@@ -423,23 +885,43 @@ struct TokenInfo {
      * Start index of the token.
      */
     start: Index,
+    /**
+     * End index of the token.
+     */
+    end: Index,
     /**
      * Whether there is a line break between this token and the previous
      * one.
      */
     is_on_new_line: bool,
+    /**
+     * Text of the doc comments (`---`) between this token and the previous
+     * one, one line for each comment.
+     */
+    doc_comment: Option<String>,
+    /**
+     * Positions of the comments between this token and the previous one.
+     */
+    comments: Vec<Pos>,
+    /**
+     * Lints allowed by the comments between this token and the previous
+     * one. See [`File::allowed_lints`].
+     */
+    allowed_lints: Vec<(usize, String)>,
 }
 
 /**
  * A token.
  */
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 enum Token {
     Digits(String),
+    PrefixedDigits { radix: u32, digits: String },
     StringLiteral(Vec<StringLiteralComponent>),
     KeywordImport,
     KeywordExport,
     KeywordStruct,
+    KeywordEnum,
     KeywordFunc,
     KeywordMethod,
     KeywordIf,
@@ -450,6 +932,7 @@ enum Token {
     KeywordReturn,
     KeywordEnd,
     KeywordVar,
+    KeywordConst,
     KeywordInt,
     KeywordFloat,
     Underscore,
@@ -461,6 +944,7 @@ enum Token {
     HyphenGreater,
     Asterisk,
     AsteriskEqual,
+    DoubleAsterisk,
     Slash,
     SlashEqual,
     Percent,
@@ -501,6 +985,35 @@ enum Token {
     ClosingBrace,
 }
 
+impl Token {
+    fn class(&self) -> TokenClass {
+        match self {
+            Token::Digits(_) | Token::PrefixedDigits { .. } | Token::StringLiteral(_) => {
+                TokenClass::Literal
+            }
+            Token::KeywordImport
+            | Token::KeywordExport
+            | Token::KeywordStruct
+            | Token::KeywordEnum
+            | Token::KeywordFunc
+            | Token::KeywordMethod
+            | Token::KeywordIf
+            | Token::KeywordElse
+            | Token::KeywordWhile
+            | Token::KeywordBreak
+            | Token::KeywordContinue
+            | Token::KeywordReturn
+            | Token::KeywordEnd
+            | Token::KeywordVar
+            | Token::KeywordConst
+            | Token::KeywordInt
+            | Token::KeywordFloat => TokenClass::Keyword,
+            Token::Underscore | Token::Identifier(_) => TokenClass::Identifier,
+            _ => TokenClass::Operator,
+        }
+    }
+}
+
 impl Parser<'_, '_> {
     /**
      * Parses an import statement.
@@ -552,37 +1065,15 @@ impl Parser<'_, '_> {
             None
         };
 
-        let ty_parameters = if self.current.is_on_new_line {
-            None
-        } else if let Some(Token::OpeningBracket) = self.current.token {
-            let opening_bracket_pos = self.current_pos();
-            self.consume_token()?;
-
-            let (ty_parameters, _) = self.parse_list_elements_and_trailing_comma()?;
-            match self.current.token {
-                Some(Token::ClosingBracket) => self.consume_token()?,
-                Some(_) => {
-                    return Err(ParseError::UnexpectedTokenInBrackets {
-                        unexpected_token_pos: self.current_pos(),
-                        opening_bracket_pos,
-                    })
-                }
-                None => {
-                    return Err(ParseError::UnclosedBracket {
-                        opening_bracket_pos,
-                    });
-                }
-            }
-            Some(ty_parameters)
-        } else {
-            None
-        };
+        let ty_parameters = self.parse_ty_parameters()?;
 
         let extra_tokens_after_name_and_ty_parameters = self.consume_line()?;
 
         let mut fields = Vec::new();
         loop {
             if let Some(Token::KeywordEnd) = self.current.token {
+                self.blocks
+                    .push((keyword_struct_pos.line(), self.current.start.line));
                 self.consume_token()?;
                 break;
             } else if let Some(field) = self.parse_factor(false)? {
@@ -610,6 +1101,7 @@ impl Parser<'_, '_> {
                 name,
                 keyword_struct_pos,
                 extra_tokens_pos: extra_tokens_after_name_and_ty_parameters,
+                doc_comment: None,
             },
             StructureDefinition {
                 ty_parameters,
@@ -619,13 +1111,13 @@ impl Parser<'_, '_> {
         ))
     }
 
-    fn parse_function_definition(
-        &mut self,
-    ) -> Result<(FunctionName, FunctionDefinition), ParseError> {
-        let keyword_func_pos = self.current_pos();
+    /**
+     * Parses an enumeration definition ([`EnumerationDefinition`]).
+     */
+    fn parse_enumeration_definition(&mut self) -> Result<EnumerationDefinition, ParseError> {
+        let keyword_enum_pos = self.current_pos();
         self.consume_token()?;
 
-        // The function name should immediately follow `func`, without a line break.
         let name = if self.current.is_on_new_line {
             None
         } else if let Some(name) = &mut self.current.token {
@@ -636,9 +1128,9 @@ impl Parser<'_, '_> {
                     Some(name)
                 }
                 _ => {
-                    return Err(ParseError::UnexpectedTokenAfterKeywordFunc {
+                    return Err(ParseError::UnexpectedTokenAfterKeywordEnum {
                         unexpected_token_pos: self.current_pos(),
-                        keyword_func_pos,
+                        keyword_enum_pos,
                     })
                 }
             }
@@ -646,15 +1138,61 @@ impl Parser<'_, '_> {
             None
         };
 
-        // Generic parameters list can follow.
-        let ty_parameters = if self.current.is_on_new_line {
-            None
-        } else if let Some(Token::OpeningBracket) = self.current.token {
-            let opening_bracket_pos = self.current_pos();
-            self.consume_token()?;
+        let ty_parameters = self.parse_ty_parameters()?;
 
-            let (ty_parameters, _) = self.parse_list_elements_and_trailing_comma()?;
-            match self.current.token {
+        let extra_tokens_after_name_pos = self.consume_line()?;
+
+        let mut variants = Vec::new();
+        loop {
+            if let Some(Token::KeywordEnd) = self.current.token {
+                self.blocks
+                    .push((keyword_enum_pos.line(), self.current.start.line));
+                self.consume_token()?;
+                break;
+            } else if let Some(variant) = self.parse_factor(false)? {
+                let extra_tokens_pos = self.consume_line()?;
+                variants.push(EnumerationVariant {
+                    variant,
+                    extra_tokens_pos,
+                });
+            } else if self.current.token.is_some() {
+                return Err(ParseError::UnexpectedTokenInBlock {
+                    unexpected_token_pos: self.current_pos(),
+                    start_line_indices: vec![keyword_enum_pos.line()],
+                });
+            } else {
+                return Err(ParseError::UnclosedBlock {
+                    start_line_indices: vec![keyword_enum_pos.line()],
+                });
+            }
+        }
+
+        let extra_tokens_pos = self.consume_line()?;
+
+        Ok(EnumerationDefinition {
+            keyword_enum_pos,
+            name,
+            ty_parameters,
+            extra_tokens_after_name_pos,
+            variants,
+            extra_tokens_pos,
+            doc_comment: None,
+        })
+    }
+
+    /**
+     * Parses type parameters in brackets `[` ... `]` following the name of a
+     * structure, an enumeration or a function, if any.
+     */
+    fn parse_ty_parameters(&mut self) -> Result<Option<Vec<ListElement>>, ParseError> {
+        Ok(if self.current.is_on_new_line {
+            None
+        } else if let Some(Token::OpeningBracket) = self.current.token {
+            let opening_bracket_pos = self.current_pos();
+            self.consume_token()?;
+
+            let (ty_parameters, _) = self.parse_list_elements_and_trailing_comma()?;
+            match self.current.token {
                 Some(Token::ClosingBracket) => self.consume_token()?,
                 Some(_) => {
                     return Err(ParseError::UnexpectedTokenInBrackets {
@@ -671,8 +1209,46 @@ impl Parser<'_, '_> {
             Some(ty_parameters)
         } else {
             None
+        })
+    }
+
+    fn parse_function_definition(
+        &mut self,
+    ) -> Result<(FunctionName, FunctionDefinition), ParseError> {
+        let keyword_func_pos = self.current_pos();
+        let is_method = matches!(self.current.token, Some(Token::KeywordMethod));
+        self.consume_token()?;
+
+        // The function name should immediately follow `func`, without a line break.
+        let name = if self.current.is_on_new_line {
+            None
+        } else if let Some(name) = &mut self.current.token {
+            match name {
+                Token::Identifier(name) => {
+                    let name = std::mem::take(name);
+                    self.consume_token()?;
+                    Some(name)
+                }
+                _ if is_method => {
+                    return Err(ParseError::UnexpectedTokenAfterKeywordMethod {
+                        unexpected_token_pos: self.current_pos(),
+                        keyword_method_pos: keyword_func_pos,
+                    })
+                }
+                _ => {
+                    return Err(ParseError::UnexpectedTokenAfterKeywordFunc {
+                        unexpected_token_pos: self.current_pos(),
+                        keyword_func_pos,
+                    })
+                }
+            }
+        } else {
+            None
         };
 
+        // Generic parameters list can follow.
+        let ty_parameters = self.parse_ty_parameters()?;
+
         // parameters list follows.
         let parameters = if self.current.is_on_new_line {
             None
@@ -740,8 +1316,10 @@ impl Parser<'_, '_> {
         Ok((
             FunctionName {
                 keyword_func_pos,
+                is_method,
                 name,
                 extra_tokens_pos: extra_tokens_after_signature,
+                doc_comment: None,
             },
             FunctionDefinition {
                 parameters,
@@ -753,6 +1331,63 @@ impl Parser<'_, '_> {
         ))
     }
 
+    /**
+     * Parses a constant definition ([`ConstantDefinition`]).
+     *
+     * # Errors
+     * - [`ParseError::ExtraTokenAfterLine`]\: An extra token after the
+     *   definition.
+     */
+    fn parse_constant_definition(&mut self) -> Result<ConstantDefinition, ParseError> {
+        let keyword_const_pos = self.current_pos();
+        self.consume_token()?;
+        let term = self.parse_assign(false)?;
+        if !self.current.is_on_new_line && self.current.token.is_some() {
+            return Err(ParseError::ExtraTokenAfterLine {
+                extra_token_pos: self.current_pos(),
+                line_pos: self.range_from(keyword_const_pos.start),
+            });
+        }
+        Ok(ConstantDefinition {
+            keyword_const_pos,
+            term,
+            doc_comment: None,
+        })
+    }
+
+    /**
+     * Parses a test or benchmark definition ([`TestDefinition`]).
+     */
+    fn parse_test_definition(&mut self) -> Result<TestDefinition, ParseError> {
+        let keyword_test_pos = self.current_pos();
+        let is_benchmark =
+            matches!(&self.current.token, Some(Token::Identifier(name)) if name == "bench");
+        self.consume_token()?;
+
+        // The name should immediately follow `test` or `bench`, without a line break.
+        let name = if self.current.is_on_new_line {
+            None
+        } else {
+            self.parse_atom(false)?
+        };
+
+        let extra_tokens_after_name = self.consume_line()?;
+
+        // The test body follows.
+        let body = self.parse_block(&mut vec![keyword_test_pos.line()])?;
+
+        let extra_tokens_after_end = self.consume_line()?;
+
+        Ok(TestDefinition {
+            keyword_test_pos,
+            is_benchmark,
+            name,
+            extra_tokens_after_name_pos: extra_tokens_after_name,
+            body,
+            extra_tokens_pos: extra_tokens_after_end,
+        })
+    }
+
     /**
      * Parses a block consisting of zero or more statements and a keyword
      * `end`.
@@ -768,10 +1403,34 @@ impl Parser<'_, '_> {
         &mut self,
         start_line_indices: &mut Vec<usize>,
     ) -> Result<Vec<Statement>, ParseError> {
+        self.parse_block_or_else(start_line_indices, false)
+            .map(|(body, _)| body)
+    }
+
+    /**
+     * Parses a block, which may also be ended by `else` if `allow_else`.
+     * Returns the position of `else` in that case, leaving what follows
+     * unconsumed.
+     */
+    fn parse_block_or_else(
+        &mut self,
+        start_line_indices: &mut Vec<usize>,
+        allow_else: bool,
+    ) -> Result<(Vec<Statement>, Option<Pos>), ParseError> {
+        self.enter_nesting()?;
         let mut body = Vec::new();
         loop {
-            if let Some(Token::KeywordEnd) = self.current.token {
+            if let (true, Some(Token::KeywordElse)) = (allow_else, &self.current.token) {
+                let keyword_else_pos = self.current_pos();
+                let start_line = *start_line_indices.last().unwrap();
+                self.blocks.push((start_line, keyword_else_pos.line() - 1));
+                self.consume_token()?;
+                self.nesting_depth -= 1;
+                return Ok((body, Some(keyword_else_pos)));
+            } else if let Some(Token::KeywordEnd) = self.current.token {
                 let keyword_end_pos = self.current_pos();
+                let start_line = *start_line_indices.last().unwrap();
+                self.blocks.push((start_line, keyword_end_pos.line()));
                 self.consume_token()?;
                 if !self.current.is_on_new_line && self.current.token.is_some() {
                     return Err(ParseError::ExtraTokenAfterLine {
@@ -779,7 +1438,8 @@ impl Parser<'_, '_> {
                         line_pos: keyword_end_pos,
                     });
                 }
-                return Ok(body);
+                self.nesting_depth -= 1;
+                return Ok((body, None));
             } else if let Some(statement) = self.parse_statement(start_line_indices)? {
                 body.push(statement);
             } else if self.current.token.is_some() {
@@ -811,7 +1471,22 @@ impl Parser<'_, '_> {
         } else if let Some(Token::KeywordWhile) = self.current.token {
             self.parse_while_statement(start_line_indices)
                 .map(Option::Some)
-        } else if let Some(term) = self.parse_assign(false)? {
+        } else if let Some(Token::KeywordIf) = self.current.token {
+            self.parse_if_statement(start_line_indices)
+                .map(Option::Some)
+        } else if let Some(Token::KeywordBreak | Token::KeywordContinue) = self.current.token {
+            self.parse_break_or_continue().map(Option::Some)
+        } else if let Some(Token::KeywordReturn) = self.current.token {
+            self.parse_return_statement().map(Option::Some)
+        } else if self.is_assert_statement_start()? {
+            self.parse_assert_statement().map(Option::Some)
+        } else if let Some(mut term) = self.parse_assign(false)? {
+            if !self.current.is_on_new_line
+                && matches!(self.current.token, Some(Token::Comma))
+                && !matches!(term.term, Term::Assignment { .. })
+            {
+                term = self.parse_multiple_assignment(term)?;
+            }
             // A term immediately followed by a line break can be a statement.
             if !self.current.is_on_new_line && self.current.token.is_some() {
                 return Err(ParseError::ExtraTokenAfterLine {
@@ -825,6 +1500,77 @@ impl Parser<'_, '_> {
         }
     }
 
+    /**
+     * Parses `a, b = x, y` after `a`, into an assignment of a tuple. If there
+     * is only one value, it is assigned as it is.
+     *
+     * # Errors
+     * - [`ParseError::MissingEqualAfterTargets`]\: The line ends before `=`.
+     */
+    fn parse_multiple_assignment(&mut self, first: TermWithPos) -> Result<TermWithPos, ParseError> {
+        let start = first.pos.start;
+        let mut targets = vec![ListElement::NonEmpty(first)];
+        self.parse_unparenthesized_elements(&mut targets)?;
+        let targets_pos = self.range_from(start);
+        match self.current.token {
+            Some(Token::Equal) if !self.current.is_on_new_line => {}
+            Some(_) if !self.current.is_on_new_line => {
+                return Err(ParseError::UnexpectedToken(self.current_pos()));
+            }
+            _ => return Err(ParseError::MissingEqualAfterTargets { targets_pos }),
+        }
+        let operator_pos = self.current_pos();
+        self.consume_token()?;
+        let values_start = self.current.start;
+        let mut value = self.parse_disjunction(false)?;
+        if !self.current.is_on_new_line && matches!(self.current.token, Some(Token::Comma)) {
+            let mut values = vec![match value {
+                Some(value) => ListElement::NonEmpty(value),
+                None => ListElement::Empty {
+                    comma_pos: self.current_pos(),
+                },
+            }];
+            self.parse_unparenthesized_elements(&mut values)?;
+            value = Some(TermWithPos {
+                term: Term::Tuple { elements: values },
+                pos: self.range_from(values_start),
+            });
+        }
+        Ok(TermWithPos {
+            term: Term::Assignment {
+                left_hand_side: Some(Box::new(TermWithPos {
+                    term: Term::Tuple { elements: targets },
+                    pos: targets_pos,
+                })),
+                operator: Box::new(TermWithPos {
+                    term: Term::MethodName(String::from("assign")),
+                    pos: operator_pos,
+                }),
+                right_hand_side: value.map(Box::new),
+            },
+            pos: self.range_from(start),
+        })
+    }
+
+    /**
+     * Parses `, y, z` after the first element of a list without
+     * parentheses, and appends the elements to `elements`.
+     */
+    fn parse_unparenthesized_elements(
+        &mut self,
+        elements: &mut Vec<ListElement>,
+    ) -> Result<(), ParseError> {
+        while !self.current.is_on_new_line && matches!(self.current.token, Some(Token::Comma)) {
+            let comma_pos = self.current_pos();
+            self.consume_token()?;
+            match self.parse_disjunction(false)? {
+                Some(element) => elements.push(ListElement::NonEmpty(element)),
+                None => elements.push(ListElement::Empty { comma_pos }),
+            }
+        }
+        Ok(())
+    }
+
     /**
      * Parses a [`Statement::VariableDeclaration`].
      *
@@ -835,7 +1581,17 @@ impl Parser<'_, '_> {
     fn parse_variable_declaration(&mut self) -> Result<Statement, ParseError> {
         let keyword_var_pos = self.current_pos();
         self.consume_token()?;
-        let term = self.parse_assign(false)?;
+        let term = match self.parse_assign(false)? {
+            // `var a, b = x, y`
+            Some(first)
+                if !self.current.is_on_new_line
+                    && matches!(self.current.token, Some(Token::Comma))
+                    && !matches!(first.term, Term::Assignment { .. }) =>
+            {
+                Some(self.parse_multiple_assignment(first)?)
+            }
+            term => term,
+        };
         if !self.current.is_on_new_line && self.current.token.is_some() {
             return Err(ParseError::ExtraTokenAfterLine {
                 extra_token_pos: self.current_pos(),
@@ -854,6 +1610,7 @@ impl Parser<'_, '_> {
      * # Errors
      * - [`ParseError::ExtraTokenAfterLine`]\: An extra token after `while`
      *   or the condition.
+     * - [`ParseError::EqualAfterCondition`]\: `=` after the condition.
      */
     fn parse_while_statement(
         &mut self,
@@ -869,6 +1626,8 @@ impl Parser<'_, '_> {
             self.parse_disjunction(false)?
         };
 
+        self.check_equal_after_condition(&condition)?;
+
         // A line break is required right after the condition.
         if !self.current.is_on_new_line && self.current.token.is_some() {
             return Err(ParseError::ExtraTokenAfterLine {
@@ -887,6 +1646,197 @@ impl Parser<'_, '_> {
         })
     }
 
+    /**
+     * Parses an if statement ([`Statement::If`]), with the `else` and
+     * `else if` following it.
+     *
+     * # Errors
+     * - [`ParseError::ExtraTokenAfterLine`]\: An extra token after the
+     *   condition or `else`.
+     * - [`ParseError::EqualAfterCondition`]\: `=` after the condition.
+     */
+    fn parse_if_statement(
+        &mut self,
+        start_line_indices: &mut Vec<usize>,
+    ) -> Result<Statement, ParseError> {
+        let keyword_if_pos = self.current_pos();
+        self.consume_token()?;
+
+        // The condition should immediately follow `if`, without line break.
+        let condition = if self.current.is_on_new_line {
+            None
+        } else {
+            self.parse_disjunction(false)?
+        };
+
+        self.check_equal_after_condition(&condition)?;
+
+        // A line break is required right after the condition.
+        if !self.current.is_on_new_line && self.current.token.is_some() {
+            return Err(ParseError::ExtraTokenAfterLine {
+                extra_token_pos: self.current_pos(),
+                line_pos: self.range_from(keyword_if_pos.start),
+            });
+        }
+
+        start_line_indices.push(keyword_if_pos.line());
+        let (body, keyword_else_pos) = self.parse_block_or_else(start_line_indices, true)?;
+        start_line_indices.pop();
+
+        let else_body = match keyword_else_pos {
+            Some(keyword_else_pos) => {
+                let else_body = if !self.current.is_on_new_line
+                    && matches!(self.current.token, Some(Token::KeywordIf))
+                {
+                    // `else if` shares the `end` with the preceding `if`.
+                    vec![self.parse_if_statement(start_line_indices)?]
+                } else {
+                    if !self.current.is_on_new_line && self.current.token.is_some() {
+                        return Err(ParseError::ExtraTokenAfterLine {
+                            extra_token_pos: self.current_pos(),
+                            line_pos: keyword_else_pos,
+                        });
+                    }
+                    start_line_indices.push(keyword_else_pos.line());
+                    let else_body = self.parse_block(start_line_indices)?;
+                    start_line_indices.pop();
+                    else_body
+                };
+                Some((keyword_else_pos, else_body))
+            }
+            None => None,
+        };
+        Ok(Statement::If {
+            keyword_if_pos,
+            condition,
+            body,
+            else_body,
+        })
+    }
+
+    /**
+     * Parses [`Statement::Break`] or [`Statement::Continue`]. Whether it is
+     * inside a loop is checked in the translation.
+     *
+     * # Errors
+     * - [`ParseError::ExtraTokenAfterLine`]\: An extra token after the
+     *   keyword.
+     */
+    fn parse_break_or_continue(&mut self) -> Result<Statement, ParseError> {
+        let keyword_pos = self.current_pos();
+        let statement = match self.current.token {
+            Some(Token::KeywordBreak) => Statement::Break {
+                keyword_break_pos: keyword_pos.clone(),
+            },
+            _ => Statement::Continue {
+                keyword_continue_pos: keyword_pos.clone(),
+            },
+        };
+        self.consume_token()?;
+        if !self.current.is_on_new_line && self.current.token.is_some() {
+            return Err(ParseError::ExtraTokenAfterLine {
+                extra_token_pos: self.current_pos(),
+                line_pos: keyword_pos,
+            });
+        }
+        Ok(statement)
+    }
+
+    /**
+     * Parses a return statement ([`Statement::Return`]). Whether it is
+     * inside a function is checked in the translation.
+     *
+     * # Errors
+     * - [`ParseError::ExtraTokenAfterLine`]\: An extra token after the
+     *   value.
+     */
+    fn parse_return_statement(&mut self) -> Result<Statement, ParseError> {
+        let keyword_return_pos = self.current_pos();
+        self.consume_token()?;
+
+        // The value, if any, should immediately follow `return`, without line
+        // break.
+        let value = if self.current.is_on_new_line {
+            None
+        } else {
+            self.parse_disjunction(false)?
+        };
+
+        if !self.current.is_on_new_line && self.current.token.is_some() {
+            return Err(ParseError::ExtraTokenAfterLine {
+                extra_token_pos: self.current_pos(),
+                line_pos: self.range_from(keyword_return_pos.start),
+            });
+        }
+
+        Ok(Statement::Return {
+            keyword_return_pos,
+            value,
+        })
+    }
+
+    /**
+     * Catches `while x = 1`, which is likely meant to be `while x == 1`.
+     * Conditions are parsed without assignments, so `=` would otherwise be
+     * reported as an extra token.
+     */
+    fn check_equal_after_condition(
+        &self,
+        condition: &Option<TermWithPos>,
+    ) -> Result<(), ParseError> {
+        match (condition, &self.current.token) {
+            (Some(condition), Some(Token::Equal)) if !self.current.is_on_new_line => {
+                Err(ParseError::EqualAfterCondition {
+                    equal_pos: self.current_pos(),
+                    condition_pos: condition.pos.clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /**
+     * Parses an assert statement ([`Statement::Assert`]).
+     *
+     * # Errors
+     * - [`ParseError::ExtraTokenAfterLine`]\: An extra token after the
+     *   condition or the message.
+     * - [`ParseError::EqualAfterCondition`]\: `=` after the condition.
+     */
+    fn parse_assert_statement(&mut self) -> Result<Statement, ParseError> {
+        let keyword_assert_pos = self.current_pos();
+        self.consume_token()?;
+
+        // The condition should immediately follow `assert`, without line break.
+        let condition = if self.current.is_on_new_line {
+            None
+        } else {
+            self.parse_disjunction(false)?
+        };
+        self.check_equal_after_condition(&condition)?;
+
+        let message =
+            if !self.current.is_on_new_line && matches!(self.current.token, Some(Token::Comma)) {
+                self.consume_token()?;
+                self.parse_disjunction(false)?
+            } else {
+                None
+            };
+
+        if !self.current.is_on_new_line && self.current.token.is_some() {
+            return Err(ParseError::ExtraTokenAfterLine {
+                extra_token_pos: self.current_pos(),
+                line_pos: self.range_from(keyword_assert_pos.start),
+            });
+        }
+
+        Ok(Statement::Assert {
+            keyword_assert_pos,
+            condition,
+            message,
+        })
+    }
+
     /**
      * Consumes all remaining tokens on the current line.
      */
@@ -909,7 +1859,9 @@ impl Parser<'_, '_> {
         if let Some(operator) = self.current.token.as_ref().and_then(assignment_operator) {
             let operator_pos = self.current_pos();
             self.consume_token()?;
+            self.enter_nesting()?;
             let right_hand_side = self.parse_assign(allow_line_break)?;
+            self.nesting_depth -= 1;
             Ok(Some(TermWithPos {
                 pos: self.range_from(start),
                 term: Term::Assignment {
@@ -982,53 +1934,62 @@ impl Parser<'_, '_> {
         &mut self,
         allow_line_break: bool,
     ) -> Result<Option<TermWithPos>, ParseError> {
-        self.parse_binary_operation_rec(allow_line_break, Precedence::first())
+        self.parse_binary_operation_above(allow_line_break, None)
     }
 
-    fn parse_binary_operation_rec(
+    /**
+     * Parses a term in which binary operators have precedence higher than
+     * `lowest` (or any precedence if `None`), by precedence climbing. It
+     * recurses only when the precedence goes up, or stays the same for
+     * right-associative operators.
+     */
+    fn parse_binary_operation_above(
         &mut self,
         allow_line_break: bool,
-        precedence: Option<Precedence>,
+        lowest: Option<Precedence>,
     ) -> Result<Option<TermWithPos>, ParseError> {
-        let Some(precedence) = precedence else {
-            return self.parse_factor(allow_line_break);
-        };
         let start = self.current.start;
-        let mut left_operand =
-            self.parse_binary_operation_rec(allow_line_break, precedence.next())?;
+        let mut left_operand = self.parse_factor(allow_line_break)?;
         while allow_line_break || !self.current.is_on_new_line {
-            let Some(ref token) = self.current.token else {
+            let Some((operator, precedence)) = self.current.token.as_ref().and_then(infix_operator)
+            else {
                 break;
             };
-            if let Some(operator) = infix_operator(token, precedence) {
-                let operator_pos = self.current_pos();
-                self.consume_token()?;
-                let right_operand =
-                    self.parse_binary_operation_rec(allow_line_break, precedence.next())?;
-                left_operand = Some(TermWithPos {
-                    term: Term::BinaryOperation {
-                        left_operand: left_operand.map(Box::new),
-                        operator: Box::new(TermWithPos {
-                            // TODO: remove `.to_string()`
-                            term: Term::MethodName(operator.to_string()),
-                            pos: operator_pos,
-                        }),
-                        right_operand: right_operand.map(Box::new),
-                    },
-                    pos: self.range_from(start),
-                });
-            } else {
+            if lowest.is_some_and(|lowest| {
+                precedence < lowest || precedence == lowest && !precedence.is_right_associative()
+            }) {
                 break;
             }
+            let operator_pos = self.current_pos();
+            self.consume_token()?;
+            let right_operand =
+                self.parse_binary_operation_above(allow_line_break, Some(precedence))?;
+            left_operand = Some(TermWithPos {
+                term: Term::BinaryOperation {
+                    left_operand: left_operand.map(Box::new),
+                    operator: Box::new(TermWithPos {
+                        // TODO: remove `.to_string()`
+                        term: Term::MethodName(operator.to_string()),
+                        pos: operator_pos,
+                    }),
+                    right_operand: right_operand.map(Box::new),
+                },
+                pos: self.range_from(start),
+            });
         }
         Ok(left_operand)
     }
 
     fn parse_factor(&mut self, allow_line_break: bool) -> Result<Option<TermWithPos>, ParseError> {
+        // Parentheses and prefix operators are nested through here.
+        self.enter_nesting()?;
         let start = self.current.start;
         let mut factor = match self.parse_atom(allow_line_break)? {
             Some(factor) => factor,
-            None => return Ok(None),
+            None => {
+                self.nesting_depth -= 1;
+                return Ok(None);
+            }
         };
         while let Some(ref token) = self.current.token {
             if let Token::Dot = token {
@@ -1065,7 +2026,7 @@ impl Parser<'_, '_> {
                     }
                     None => return Err(ParseError::MissingFieldAfterDot { dot_pos }),
                 }
-            } else if let Token::Colon = token {
+            } else if matches!(token, Token::Colon) && !self.in_placeholder {
                 let colon_pos = self.current_pos();
                 self.consume_token()?;
                 let opt_term_right = self.parse_factor(allow_line_break)?;
@@ -1145,6 +2106,7 @@ impl Parser<'_, '_> {
                 break;
             }
         }
+        self.nesting_depth -= 1;
         Ok(Some(factor))
     }
 
@@ -1154,6 +2116,7 @@ impl Parser<'_, '_> {
         };
         let start = self.current.start;
         let term = if let Token::Underscore = first_token {
+            self.consume_token()?;
             Term::Identity
         } else if let Token::Identifier(name) = first_token {
             let name = std::mem::take(name);
@@ -1166,36 +2129,27 @@ impl Parser<'_, '_> {
         } else if let Token::Digits(value) = first_token {
             let mut value = std::mem::take(value);
             self.consume_token()?;
-            if self.current.start == self.prev_end {
-                if let Some(Token::Dot) = self.current.token {
-                    let number_pos = self.range_from(start);
-                    self.consume_token()?;
-                    if let Some(Token::Identifier(ref mut name)) = self.current.token {
-                        let number = TermWithPos {
-                            term: Term::NumericLiteral(value),
-                            pos: number_pos,
-                        };
-                        let name = std::mem::take(name);
+            // `1.5` and `1.` are numbers, while `1.x` is a field of `1`, which is
+            // left to `parse_factor`.
+            if self.current.start == self.prev_end
+                && matches!(self.current.token, Some(Token::Dot))
+                && !matches!(self.peek_nth(1)?, Some(Token::Identifier(_)))
+            {
+                self.consume_token()?;
+                value.push('.');
+                if self.current.start == self.prev_end {
+                    if let Some(Token::Digits(ref decimal_part)) = self.current.token {
+                        value.push_str(decimal_part);
                         self.consume_token()?;
-                        return Ok(Some(TermWithPos {
-                            term: Term::FieldByName {
-                                term_left: Box::new(number),
-                                name,
-                            },
-                            pos: self.range_from(start),
-                        }));
-                    } else {
-                        value.push('.');
-                        if self.current.start == self.prev_end {
-                            if let Some(Token::Digits(ref decimal_part)) = self.current.token {
-                                value.push_str(decimal_part);
-                                self.consume_token()?;
-                            }
-                        }
                     }
                 }
             }
             Term::NumericLiteral(value)
+        } else if let Token::PrefixedDigits { radix, digits } = first_token {
+            let radix = *radix;
+            let digits = std::mem::take(digits);
+            self.consume_token()?;
+            Term::IntegerLiteral { radix, digits }
         } else if let Token::Dot = first_token {
             let dot_pos = self.current_pos();
             self.consume_token()?;
@@ -1305,13 +2259,13 @@ fn prefix_operator(token: &Token) -> Option<&'static str> {
 }
 
 /**
- * Precedence of binary operators.
+ * Precedence of binary operators, from the loosest to the tightest. All of
+ * them are left-associative except [`Precedence::Power`].
  */
-#[derive(Clone, Copy, Sequence)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum Precedence {
-    LogicalOr,
-    LogicalAnd,
     Equality,
+    Membership,
     Inequality,
     BitOr,
     BitXor,
@@ -1319,27 +2273,41 @@ enum Precedence {
     BitShift,
     AddSub,
     MulDivRem,
-    TimeShift,
+    Power,
+}
+
+impl Precedence {
+    fn is_right_associative(self) -> bool {
+        matches!(self, Precedence::Power)
+    }
 }
 
-fn infix_operator(token: &Token, precedence: Precedence) -> Option<&'static str> {
-    match (token, precedence) {
-        (Token::Asterisk, Precedence::MulDivRem) => Some("mul"),
-        (Token::Slash, Precedence::MulDivRem) => Some("div"),
-        (Token::Percent, Precedence::MulDivRem) => Some("rem"),
-        (Token::Plus, Precedence::AddSub) => Some("add"),
-        (Token::Hyphen, Precedence::AddSub) => Some("sub"),
-        (Token::DoubleGreater, Precedence::BitShift) => Some("right_shift"),
-        (Token::DoubleLess, Precedence::BitShift) => Some("left_shift"),
-        (Token::Ampersand, Precedence::BitAnd) => Some("bitwise_and"),
-        (Token::Circumflex, Precedence::BitXor) => Some("bitwise_xor"),
-        (Token::Bar, Precedence::BitOr) => Some("bitwise_or"),
-        (Token::Greater, Precedence::Inequality) => Some("greater"),
-        (Token::GreaterEqual, Precedence::Inequality) => Some("greater_or_equal"),
-        (Token::Less, Precedence::Inequality) => Some("less"),
-        (Token::LessEqual, Precedence::Inequality) => Some("less_or_equal"),
-        (Token::DoubleEqual, Precedence::Equality) => Some("equal"),
-        (Token::ExclamationEqual, Precedence::Equality) => Some("not_equal"),
+/**
+ * The table of binary operators, with the method names and the precedence.
+ */
+fn infix_operator(token: &Token) -> Option<(&'static str, Precedence)> {
+    match token {
+        Token::DoubleAsterisk => Some(("pow", Precedence::Power)),
+        // `in` is an operator only here, where an identifier cannot appear. The
+        // operands are swapped when translated, since the container is the
+        // receiver of `contains`.
+        Token::Identifier(name) if name == "in" => Some(("contains", Precedence::Membership)),
+        Token::Asterisk => Some(("mul", Precedence::MulDivRem)),
+        Token::Slash => Some(("div", Precedence::MulDivRem)),
+        Token::Percent => Some(("rem", Precedence::MulDivRem)),
+        Token::Plus => Some(("add", Precedence::AddSub)),
+        Token::Hyphen => Some(("sub", Precedence::AddSub)),
+        Token::DoubleGreater => Some(("right_shift", Precedence::BitShift)),
+        Token::DoubleLess => Some(("left_shift", Precedence::BitShift)),
+        Token::Ampersand => Some(("bitwise_and", Precedence::BitAnd)),
+        Token::Circumflex => Some(("bitwise_xor", Precedence::BitXor)),
+        Token::Bar => Some(("bitwise_or", Precedence::BitOr)),
+        Token::Greater => Some(("greater", Precedence::Inequality)),
+        Token::GreaterEqual => Some(("greater_or_equal", Precedence::Inequality)),
+        Token::Less => Some(("less", Precedence::Inequality)),
+        Token::LessEqual => Some(("less_or_equal", Precedence::Inequality)),
+        Token::DoubleEqual => Some(("equal", Precedence::Equality)),
+        Token::ExclamationEqual => Some(("not_equal", Precedence::Equality)),
         _ => None,
     }
 }
@@ -1362,13 +2330,76 @@ fn assignment_operator(token: &Token) -> Option<&'static str> {
 }
 
 impl Parser<'_, '_> {
+    /**
+     * Called when starting to parse a block or a term which may contain
+     * another. The caller decrements [`Self::nesting_depth`] when it is
+     * done, unless it returns an error.
+     */
+    fn enter_nesting(&mut self) -> Result<(), ParseError> {
+        if self.nesting_depth == self.max_nesting_depth {
+            return Err(ParseError::TooDeeplyNested {
+                pos: self.current_pos(),
+                max_nesting_depth: self.max_nesting_depth,
+            });
+        }
+        self.nesting_depth += 1;
+        Ok(())
+    }
+    /**
+     * Whether the current token starts a test or a benchmark at the start of
+     * an item: `test` or `bench` followed by a string literal on the same
+     * line, or by a line break before the body. Otherwise it is an
+     * identifier, as in `test = 1` and `bench.count += 1`.
+     */
+    fn is_test_definition_start(&mut self) -> Result<bool, ParseError> {
+        let Some(Token::Identifier(name)) = &self.current.token else {
+            return Ok(false);
+        };
+        if name != "test" && name != "bench" {
+            return Ok(false);
+        }
+        Ok(match self.peek_nth(1)? {
+            None | Some(Token::StringLiteral(_)) => true,
+            Some(_) => self.lookahead[0].is_on_new_line,
+        })
+    }
+    /**
+     * Whether the current token starts an assertion at the start of a
+     * statement: `assert` followed on the same line, after a space, by a
+     * token that starts a term. Otherwise it is an identifier, as in
+     * `assert(x)`, `assert = 1` and `assert.count += 1`.
+     */
+    fn is_assert_statement_start(&mut self) -> Result<bool, ParseError> {
+        if !matches!(&self.current.token, Some(Token::Identifier(name)) if name == "assert") {
+            return Ok(false);
+        }
+        let keyword_end = self.current.end;
+        let Some(next_token) = self.peek_nth(1)? else {
+            return Ok(false);
+        };
+        let starts_term = prefix_operator(next_token).is_some()
+            || matches!(
+                next_token,
+                Token::Underscore
+                    | Token::Identifier(_)
+                    | Token::StringLiteral(_)
+                    | Token::Digits(_)
+                    | Token::PrefixedDigits { .. }
+                    | Token::Dot
+                    | Token::KeywordInt
+                    | Token::KeywordFloat
+                    | Token::OpeningParenthesis
+            );
+        let next = &self.lookahead[0];
+        Ok(starts_term && !next.is_on_new_line && next.start != keyword_end)
+    }
     /**
      * A shorthand to get the [`Pos`] of the current token.
      */
     fn current_pos(&self) -> Pos {
         Pos {
             start: self.current.start,
-            end: self.iter.index(),
+            end: self.current.end,
         }
     }
     /**
@@ -1386,10 +2417,123 @@ impl Parser<'_, '_> {
      * [`Self::current`].
      */
     fn consume_token(&mut self) -> Result<(), ParseError> {
-        self.prev_end = self.iter.index();
-        self.current = read_token(&mut self.iter, false)?;
+        self.prev_end = self.current.end;
+        self.current = match self.lookahead.pop_front() {
+            Some(token_info) => token_info,
+            None => read_token(self.iter, false)?,
+        };
+        self.allowed_lints.append(&mut self.current.allowed_lints);
         Ok(())
     }
+    /**
+     * Returns the `n`-th token after the current one without consuming
+     * anything, where `peek_nth(0)` is the current token itself and `None`
+     * means EOF.
+     *
+     * The tokens are read from [`Self::iter`] in advance, so a lexical error
+     * in them is returned here. Do not peek beyond the closing brace of a
+     * placeholder, whose parser shares the iterator with the string literal.
+     */
+    fn peek_nth(&mut self, n: usize) -> Result<Option<&Token>, ParseError> {
+        if n == 0 {
+            return Ok(self.current.token.as_ref());
+        }
+        while self.lookahead.len() < n {
+            if self
+                .lookahead
+                .back()
+                .map_or(&self.current, |last| last)
+                .token
+                .is_none()
+            {
+                return Ok(None);
+            }
+            let token_info = read_token(self.iter, false)?;
+            self.lookahead.push_back(token_info);
+        }
+        Ok(self.lookahead[n - 1].token.as_ref())
+    }
+}
+
+/**
+ * Reads a format spec after `:` in a placeholder, up to and including the
+ * closing brace `}`.
+ *
+ * The format spec consists of, in this order, all optional:
+ * - `0` to pad with zeros,
+ * - the minimum width,
+ * - `.` followed by the precision,
+ * - `x`, `o` or `b` for the radix.
+ */
+fn read_format_spec(
+    iter: &mut CharsPeekable,
+    start_index: Index,
+) -> Result<FormatSpec, ParseError> {
+    let spec_start = iter.index();
+    let mut spec = String::new();
+    loop {
+        match iter.peek() {
+            Some('}') => break,
+            Some('"') => {
+                return Err(ParseError::InvalidFormatSpec {
+                    spec_pos: Pos {
+                        start: spec_start,
+                        end: iter.index(),
+                    },
+                })
+            }
+            Some(ch) => spec.push(ch),
+            None => return Err(ParseError::UnterminatedStringLiteral { start_index }),
+        }
+        iter.consume();
+    }
+    let spec_pos = Pos {
+        start: spec_start,
+        end: iter.index(),
+    };
+    iter.consume();
+    parse_format_spec(&spec).ok_or(ParseError::InvalidFormatSpec { spec_pos })
+}
+
+fn parse_format_spec(spec: &str) -> Option<FormatSpec> {
+    let mut chars = spec.chars().peekable();
+    let zero_padding = chars.next_if_eq(&'0').is_some();
+    let width = read_format_spec_number(&mut chars)?;
+    let precision = if chars.next_if_eq(&'.').is_some() {
+        Some(read_format_spec_number(&mut chars)??)
+    } else {
+        None
+    };
+    let radix = match chars.next() {
+        Some('x') => Some(16),
+        Some('o') => Some(8),
+        Some('b') => Some(2),
+        Some(_) => return None,
+        None => None,
+    };
+    chars.next().is_none().then_some(FormatSpec {
+        zero_padding,
+        width,
+        precision,
+        radix,
+    })
+}
+
+/**
+ * Returns `Some(None)` if there are no digits, or `None` on overflow.
+ */
+fn read_format_spec_number(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+) -> Option<Option<usize>> {
+    let mut digits = String::new();
+    while let Some(ch) = chars.next_if(char::is_ascii_digit) {
+        digits.push(ch);
+    }
+    if digits.is_empty() {
+        Some(None)
+    } else {
+        digits.parse().ok().map(Some)
+    }
 }
 
 /**
@@ -1404,303 +2548,437 @@ impl Parser<'_, '_> {
  *   backslash `\` in a string literal.
  * - [`ParseError::UnexpectedTokenInStringLiteral`]: Unexpected token while
  *   reading a placeholder `${` ... `}` in a string literal.
+ * - [`ParseError::MissingBraceInPlaceholder`]: The string literal ends
+ *   after `$` without `{`.
+ * - [`ParseError::InvalidFormatSpec`]: Invalid format spec after `:` in a
+ *   placeholder.
  * - [`ParseError::InvalidBlockComment`]: `is_on_new_line` is `false` when a
  *   block comment starts.
  */
 fn read_token(iter: &mut CharsPeekable, mut is_on_new_line: bool) -> Result<TokenInfo, ParseError> {
-    let (start_index, first_ch) = loop {
-        let Some(ch) = iter.peek() else {
-            return Ok(TokenInfo {
-                token: None,
-                start: iter.index(),
-                is_on_new_line,
-            });
-        };
-        if ch.is_ascii_whitespace() {
-            if ch == '\n' {
-                is_on_new_line = true
-            }
-            iter.consume();
-        } else {
-            break (iter.index(), ch);
-        }
-    };
-    iter.consume();
-    let token = match first_ch {
-        '0'..='9' => {
-            let mut value = first_ch.to_string();
-            let mut after_e = false;
-            while let Some(ch) = iter.peek() {
-                after_e = match ch {
-                    'e' | 'E' => true,
-                    '0'..='9' | 'a'..='z' | 'A'..='Z' | '_' => false,
-                    '+' | '-' if after_e => false,
-                    _ => break,
-                };
-                if ch != '_' {
-                    value.push(ch);
+    let mut doc_comment: Option<String> = None;
+    let mut comments = Vec::new();
+    let mut allowed_lints = Vec::new();
+    loop {
+        let (start_index, first_ch) = loop {
+            let Some(ch) = iter.peek() else {
+                return Ok(TokenInfo {
+                    token: None,
+                    start: iter.index(),
+                    end: iter.index(),
+                    is_on_new_line,
+                    doc_comment,
+                    comments,
+                    allowed_lints,
+                });
+            };
+            if ch.is_ascii_whitespace() {
+                if ch == '\n' {
+                    is_on_new_line = true
                 }
                 iter.consume();
+            } else {
+                break (iter.index(), ch);
             }
-            Token::Digits(value)
-        }
-        '"' => {
-            let mut components = Vec::new();
-            let mut string = String::new();
-            loop {
-                let Some(ch1) = iter.peek() else {
-                    return Err(ParseError::UnterminatedStringLiteral { start_index });
+        };
+        iter.count_token();
+        iter.consume();
+        let token = match first_ch {
+            '0' if matches!(iter.peek(), Some('x' | 'b' | 'o')) => {
+                let radix = match iter.peek() {
+                    Some('x') => 16,
+                    Some('b') => 2,
+                    _ => 8,
                 };
-                let index1 = iter.index();
                 iter.consume();
-                match ch1 {
-                    '$' => {
-                        if !string.is_empty() {
-                            components
-                                .push(StringLiteralComponent::String(std::mem::take(&mut string)));
+                let mut digits = String::new();
+                while let Some(ch) = iter.peek() {
+                    match ch {
+                        '0'..='9' | 'a'..='z' | 'A'..='Z' => digits.push(ch),
+                        '_' => {}
+                        _ => break,
+                    }
+                    iter.consume();
+                }
+                Token::PrefixedDigits { radix, digits }
+            }
+            '0'..='9' => {
+                let mut value = first_ch.to_string();
+                let mut after_e = false;
+                while let Some(ch) = iter.peek() {
+                    after_e = match ch {
+                        'e' | 'E' => true,
+                        '0'..='9' | 'a'..='z' | 'A'..='Z' | '_' => false,
+                        '+' | '-' if after_e => false,
+                        _ => break,
+                    };
+                    if ch != '_' {
+                        value.push(ch);
+                    }
+                    iter.consume();
+                }
+                Token::Digits(value)
+            }
+            '"' => {
+                let mut components = Vec::new();
+                let mut string = String::new();
+                loop {
+                    let Some(ch1) = iter.peek() else {
+                        return Err(ParseError::UnterminatedStringLiteral { start_index });
+                    };
+                    let index1 = iter.index();
+                    iter.consume();
+                    match ch1 {
+                        '$' => {
+                            if !string.is_empty() {
+                                components.push(StringLiteralComponent::String(std::mem::take(
+                                    &mut string,
+                                )));
+                            }
+                            // Since the usage of format strings is undecided, the current
+                            // implementation is kept simple for now.
+                            let mut format = String::new();
+                            loop {
+                                let Some(ch2) = iter.peek() else {
+                                    return Err(ParseError::UnterminatedStringLiteral {
+                                        start_index,
+                                    });
+                                };
+                                iter.consume();
+                                match ch2 {
+                                    '"' => {
+                                        return Err(ParseError::MissingBraceInPlaceholder {
+                                            dollar_index: index1,
+                                        })
+                                    }
+                                    '{' => break,
+                                    ch => format.push(ch),
+                                }
+                            }
+                            let mut parser = Parser::new(iter)?;
+                            parser.in_placeholder = true;
+                            let value = parser.parse_disjunction(true)?;
+                            match parser.current.token {
+                                Some(Token::ClosingBrace) => {
+                                    components.push(StringLiteralComponent::PlaceHolder {
+                                        format,
+                                        value,
+                                        spec: None,
+                                    });
+                                }
+                                Some(Token::Colon) => {
+                                    let spec = read_format_spec(parser.iter, start_index)?;
+                                    components.push(StringLiteralComponent::PlaceHolder {
+                                        format,
+                                        value,
+                                        spec: Some(spec),
+                                    });
+                                }
+                                Some(_) => {
+                                    return Err(ParseError::UnexpectedTokenInStringLiteral {
+                                        unexpected_token_pos: parser.current_pos(),
+                                        dollar_index: index1,
+                                    });
+                                }
+                                None => {
+                                    return Err(ParseError::UnterminatedStringLiteral {
+                                        start_index,
+                                    });
+                                }
+                            }
                         }
-                        // Since the usage of format strings is undecided, the current
-                        // implementation is kept simple for now.
-                        let mut format = String::new();
-                        loop {
-                            let Some(ch2) = iter.peek() else {
+                        '\\' => {
+                            let Some(ch) = iter.peek() else {
                                 return Err(ParseError::UnterminatedStringLiteral { start_index });
                             };
                             iter.consume();
-                            match ch2 {
-                                '"' => todo!(),
-                                '{' => break,
-                                ch => format.push(ch),
-                            }
+                            string.push(match ch {
+                                'n' => '\n',
+                                'r' => '\r',
+                                't' => '\t',
+                                '"' => '\"',
+                                '\\' => '\\',
+                                '0' => '\0',
+                                '\'' => '\'',
+                                _ => {
+                                    return Err(ParseError::InvalidEscapeSequence {
+                                        backslash_index: index1,
+                                    })
+                                }
+                            });
                         }
-                        let mut parser = Parser::new(iter)?;
-                        let value = parser.parse_disjunction(true)?;
-                        match parser.current.token {
-                            Some(Token::ClosingBrace) => {
-                                components
-                                    .push(StringLiteralComponent::PlaceHolder { format, value });
-                            }
-                            Some(_) => {
-                                return Err(ParseError::UnexpectedTokenInStringLiteral {
-                                    unexpected_token_pos: parser.current_pos(),
-                                    dollar_index: index1,
-                                });
-                            }
-                            None => {
-                                return Err(ParseError::UnterminatedStringLiteral { start_index });
+                        '"' => {
+                            if !string.is_empty() {
+                                components.push(StringLiteralComponent::String(std::mem::take(
+                                    &mut string,
+                                )));
                             }
+                            break Token::StringLiteral(components);
                         }
+                        ch => string.push(ch),
                     }
-                    '\\' => {
-                        let Some(ch) = iter.peek() else {
-                            return Err(ParseError::UnterminatedStringLiteral { start_index });
-                        };
+                }
+            }
+            _ if first_ch == '_' || unicode_ident::is_xid_start(first_ch) => {
+                let mut name = first_ch.to_string();
+                while let Some(ch) = iter.peek() {
+                    if unicode_ident::is_xid_continue(ch) {
+                        name.push(ch);
                         iter.consume();
-                        string.push(match ch {
-                            'n' => '\n',
-                            'r' => '\r',
-                            't' => '\t',
-                            '"' => '\"',
-                            '\\' => '\\',
-                            '0' => '\0',
-                            '\'' => '\'',
-                            _ => {
-                                return Err(ParseError::InvalidEscapeSequence {
-                                    backslash_index: index1,
-                                })
+                    } else {
+                        break;
+                    }
+                }
+                match name.as_str() {
+                    "import" => Token::KeywordImport,
+                    "export" => Token::KeywordExport,
+                    "struct" => Token::KeywordStruct,
+                    "enum" => Token::KeywordEnum,
+                    "func" => Token::KeywordFunc,
+                    "method" => Token::KeywordMethod,
+                    "if" => Token::KeywordIf,
+                    "else" => Token::KeywordElse,
+                    "while" => Token::KeywordWhile,
+                    "break" => Token::KeywordBreak,
+                    "continue" => Token::KeywordContinue,
+                    "return" => Token::KeywordReturn,
+                    "end" => Token::KeywordEnd,
+                    "var" => Token::KeywordVar,
+                    "const" => Token::KeywordConst,
+                    "int" => Token::KeywordInt,
+                    "float" => Token::KeywordFloat,
+                    "_" => Token::Underscore,
+                    _ => Token::Identifier(name),
+                }
+            }
+            '+' => {
+                if iter.consume_if('=') {
+                    Token::PlusEqual
+                } else {
+                    Token::Plus
+                }
+            }
+            '-' => {
+                if iter.consume_if('-') {
+                    if iter.consume_if('-') && iter.peek() != Some('-') {
+                        let text = read_line_comment(iter);
+                        match &mut doc_comment {
+                            Some(doc_comment) => {
+                                doc_comment.push('\n');
+                                doc_comment.push_str(&text);
                             }
+                            None => doc_comment = Some(text),
+                        }
+                        comments.push(Pos {
+                            start: start_index,
+                            end: iter.index(),
                         });
+                        is_on_new_line = true;
+                        continue;
                     }
-                    '"' => {
-                        if !string.is_empty() {
-                            components
-                                .push(StringLiteralComponent::String(std::mem::take(&mut string)));
-                        }
-                        break Token::StringLiteral(components);
+                    let text = read_line_comment(iter);
+                    for lint in parse_allow_comment(&text) {
+                        allowed_lints.push((start_index.line + 1, lint));
                     }
-                    ch => string.push(ch),
+                    comments.push(Pos {
+                        start: start_index,
+                        end: iter.index(),
+                    });
+                    is_on_new_line = true;
+                    continue;
+                } else if iter.consume_if('=') {
+                    Token::HyphenEqual
+                } else if iter.consume_if('>') {
+                    Token::HyphenGreater
+                } else {
+                    Token::Hyphen
                 }
             }
-        }
-        _ if first_ch == '_' || unicode_ident::is_xid_start(first_ch) => {
-            let mut name = first_ch.to_string();
-            while let Some(ch) = iter.peek() {
-                if unicode_ident::is_xid_continue(ch) {
-                    name.push(ch);
-                    iter.consume();
+            '*' => {
+                if iter.consume_if('=') {
+                    Token::AsteriskEqual
+                } else if iter.consume_if('*') {
+                    Token::DoubleAsterisk
                 } else {
-                    break;
+                    Token::Asterisk
                 }
             }
-            match name.as_str() {
-                "import" => Token::KeywordImport,
-                "export" => Token::KeywordExport,
-                "struct" => Token::KeywordStruct,
-                "func" => Token::KeywordFunc,
-                "method" => Token::KeywordMethod,
-                "if" => Token::KeywordIf,
-                "else" => Token::KeywordElse,
-                "while" => Token::KeywordWhile,
-                "break" => Token::KeywordBreak,
-                "continue" => Token::KeywordContinue,
-                "return" => Token::KeywordReturn,
-                "end" => Token::KeywordEnd,
-                "var" => Token::KeywordVar,
-                "int" => Token::KeywordInt,
-                "float" => Token::KeywordFloat,
-                "_" => Token::Underscore,
-                _ => Token::Identifier(name),
-            }
-        }
-        '+' => {
-            if iter.consume_if('=') {
-                Token::PlusEqual
-            } else {
-                Token::Plus
+            '/' => {
+                if iter.consume_if('-') {
+                    skip_block_comment(iter, start_index, '/', '-', '-', '/')?;
+                    comments.push(Pos {
+                        start: start_index,
+                        end: iter.index(),
+                    });
+                    continue;
+                } else if iter.consume_if('/') {
+                    if !is_on_new_line {
+                        return Err(ParseError::InvalidBlockComment { start_index });
+                    }
+                    skip_block_comment(iter, start_index, '/', '/', '\\', '\\')?;
+                    skip_line_comment(iter);
+                    comments.push(Pos {
+                        start: start_index,
+                        end: iter.index(),
+                    });
+                    is_on_new_line = true;
+                    continue;
+                } else if iter.consume_if('=') {
+                    Token::SlashEqual
+                } else {
+                    Token::Slash
+                }
             }
-        }
-        '-' => {
-            if iter.consume_if('-') {
-                skip_line_comment(iter);
-                return read_token(iter, true);
-            } else if iter.consume_if('=') {
-                Token::HyphenEqual
-            } else if iter.consume_if('>') {
-                Token::HyphenGreater
-            } else {
-                Token::Hyphen
+            '%' => {
+                if iter.consume_if('=') {
+                    Token::PercentEqual
+                } else {
+                    Token::Percent
+                }
             }
-        }
-        '*' => {
-            if iter.consume_if('=') {
-                Token::AsteriskEqual
-            } else {
-                Token::Asterisk
+            '=' => {
+                if iter.consume_if('=') {
+                    Token::DoubleEqual
+                } else if iter.consume_if('>') {
+                    Token::EqualGreater
+                } else {
+                    Token::Equal
+                }
             }
-        }
-        '/' => {
-            if iter.consume_if('-') {
-                skip_block_comment(iter, start_index, '/', '-', '-', '/')?;
-                return read_token(iter, is_on_new_line);
-            } else if iter.consume_if('/') {
-                if !is_on_new_line {
-                    return Err(ParseError::InvalidBlockComment { start_index });
+            '!' => {
+                if iter.consume_if('=') {
+                    Token::ExclamationEqual
+                } else {
+                    Token::Exclamation
                 }
-                skip_block_comment(iter, start_index, '/', '/', '\\', '\\')?;
-                skip_line_comment(iter);
-                return read_token(iter, true);
-            } else if iter.consume_if('=') {
-                Token::SlashEqual
-            } else {
-                Token::Slash
             }
-        }
-        '%' => {
-            if iter.consume_if('=') {
-                Token::PercentEqual
-            } else {
-                Token::Percent
+            '>' => {
+                if iter.consume_if('>') {
+                    if iter.consume_if('=') {
+                        Token::DoubleGreaterEqual
+                    } else {
+                        Token::DoubleGreater
+                    }
+                } else if iter.consume_if('=') {
+                    Token::GreaterEqual
+                } else {
+                    Token::Greater
+                }
             }
-        }
-        '=' => {
-            if iter.consume_if('=') {
-                Token::DoubleEqual
-            } else if iter.consume_if('>') {
-                Token::EqualGreater
-            } else {
-                Token::Equal
+            '<' => {
+                if iter.consume_if('<') {
+                    if iter.consume_if('=') {
+                        Token::DoubleLessEqual
+                    } else {
+                        Token::DoubleLess
+                    }
+                } else if iter.consume_if('=') {
+                    Token::LessEqual
+                } else {
+                    Token::Less
+                }
             }
-        }
-        '!' => {
-            if iter.consume_if('=') {
-                Token::ExclamationEqual
-            } else {
-                Token::Exclamation
+            '&' => {
+                if iter.consume_if('&') {
+                    Token::DoubleAmpersand
+                } else if iter.consume_if('=') {
+                    Token::AmpersandEqual
+                } else {
+                    Token::Ampersand
+                }
             }
-        }
-        '>' => {
-            if iter.consume_if('>') {
-                if iter.consume_if('=') {
-                    Token::DoubleGreaterEqual
+            '|' => {
+                if iter.consume_if('|') {
+                    Token::DoubleBar
+                } else if iter.consume_if('=') {
+                    Token::BarEqual
                 } else {
-                    Token::DoubleGreater
+                    Token::Bar
                 }
-            } else if iter.consume_if('=') {
-                Token::GreaterEqual
-            } else {
-                Token::Greater
             }
-        }
-        '<' => {
-            if iter.consume_if('<') {
+            '^' => {
                 if iter.consume_if('=') {
-                    Token::DoubleLessEqual
+                    Token::CircumflexEqual
                 } else {
-                    Token::DoubleLess
+                    Token::Circumflex
                 }
-            } else if iter.consume_if('=') {
-                Token::LessEqual
-            } else {
-                Token::Less
-            }
-        }
-        '&' => {
-            if iter.consume_if('&') {
-                Token::DoubleAmpersand
-            } else if iter.consume_if('=') {
-                Token::AmpersandEqual
-            } else {
-                Token::Ampersand
-            }
-        }
-        '|' => {
-            if iter.consume_if('|') {
-                Token::DoubleBar
-            } else if iter.consume_if('=') {
-                Token::BarEqual
-            } else {
-                Token::Bar
-            }
-        }
-        '^' => {
-            if iter.consume_if('=') {
-                Token::CircumflexEqual
-            } else {
-                Token::Circumflex
             }
+            ':' => Token::Colon,
+            ';' => Token::Semicolon,
+            ',' => Token::Comma,
+            '?' => Token::Question,
+            '~' => Token::Tilde,
+            '(' => Token::OpeningParenthesis,
+            ')' => Token::ClosingParenthesis,
+            '[' => Token::OpeningBracket,
+            ']' => Token::ClosingBracket,
+            '{' => Token::OpeningBrace,
+            '}' => Token::ClosingBrace,
+            '.' => Token::Dot,
+            '$' => Token::Dollar,
+            _ => return Err(ParseError::UnexpectedCharacter(start_index)),
+        };
+        return Ok(TokenInfo {
+            token: Some(token),
+            start: start_index,
+            end: iter.index(),
+            is_on_new_line,
+            doc_comment,
+            comments,
+            allowed_lints,
+        });
+    }
+}
+
+/**
+ * Reads the text of a line comment until the end of line, without a single
+ * leading space.
+ */
+fn read_line_comment(iter: &mut CharsPeekable) -> String {
+    iter.consume_if(' ');
+    let mut text = String::new();
+    while let Some(ch) = iter.peek() {
+        if ch == '\n' {
+            break;
         }
-        ':' => Token::Colon,
-        ';' => Token::Semicolon,
-        ',' => Token::Comma,
-        '?' => Token::Question,
-        '~' => Token::Tilde,
-        '(' => Token::OpeningParenthesis,
-        ')' => Token::ClosingParenthesis,
-        '[' => Token::OpeningBracket,
-        ']' => Token::ClosingBracket,
-        '{' => Token::OpeningBrace,
-        '}' => Token::ClosingBrace,
-        '.' => Token::Dot,
-        '$' => Token::Dollar,
-        _ => return Err(ParseError::UnexpectedCharacter(start_index)),
+        text.push(ch);
+        iter.consume();
+    }
+    if text.ends_with('\r') {
+        text.pop();
+    }
+    text
+}
+
+/**
+ * Returns the names of the lints in a comment `-- syscraws:allow(a, b)`,
+ * given the text after `--`.
+ */
+fn parse_allow_comment(text: &str) -> Vec<String> {
+    let Some(names) = text
+        .trim_end()
+        .strip_prefix("syscraws:allow(")
+        .and_then(|names| names.strip_suffix(')'))
+    else {
+        return Vec::new();
     };
-    Ok(TokenInfo {
-        token: Some(token),
-        start: start_index,
-        is_on_new_line,
-    })
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_owned)
+        .collect()
 }
 
 /**
- * Skips until the end of line.
+ * Skips until the end of line, leaving the line break.
  */
 fn skip_line_comment(iter: &mut CharsPeekable) {
-    loop {
-        let ch = iter.peek();
-        iter.consume();
-        if let None | Some('\n') = ch {
+    while let Some(ch) = iter.peek() {
+        if ch == '\n' {
             break;
         }
+        iter.consume();
     }
 }
 
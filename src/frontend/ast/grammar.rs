@@ -0,0 +1,178 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/*!
+ * The grammar accepted by the parser, in EBNF.
+ *
+ * Update [`RULES`] together with the parse functions. Tokens on the same
+ * line are written as consecutive symbols, and a line break is required
+ * where `NEWLINE` appears.
+ */
+
+mod tests;
+
+/**
+ * Each rule as its name and definition, in the order of the parse
+ * functions.
+ */
+pub const RULES: &[(&str, &str)] = &[
+    // `parse_file`
+    ("file", "{ item }"),
+    (
+        "item",
+        "import | structure | enumeration | function | constant | test | statement",
+    ),
+    // `Parser::parse_import`
+    ("import", "\"import\", factor, NEWLINE"),
+    // `Parser::parse_structure_definition`
+    (
+        "structure",
+        "\"struct\", identifier, [ ty_parameters ], NEWLINE, { factor, NEWLINE }, \"end\", NEWLINE",
+    ),
+    // `Parser::parse_enumeration_definition`
+    (
+        "enumeration",
+        "\"enum\", identifier, [ ty_parameters ], NEWLINE, { factor, NEWLINE }, \"end\", NEWLINE",
+    ),
+    // `Parser::parse_ty_parameters`
+    ("ty_parameters", "\"[\", list, \"]\""),
+    // `Parser::parse_function_definition`
+    (
+        "function",
+        "( \"func\" | \"method\" ), identifier, [ ty_parameters ], [ \"(\", list, \")\" ], \
+         [ \":\", disjunction ], NEWLINE, block",
+    ),
+    // `Parser::parse_constant_definition`
+    ("constant", "\"const\", assign, NEWLINE"),
+    // `Parser::parse_test_definition`
+    // `test` and `bench` are keywords only here, as decided by
+    // `Parser::is_test_definition_start`.
+    (
+        "test",
+        "( \"test\" | \"bench\" ), [ string_literal ], NEWLINE, block",
+    ),
+    // `Parser::parse_block`
+    ("block", "{ statement }, \"end\", NEWLINE"),
+    // `Parser::parse_statement`
+    (
+        "statement",
+        "variable_declaration | while | if | return | assert | ( \"break\" | \"continue\" | assign | multiple_assign ), NEWLINE",
+    ),
+    // `Parser::parse_multiple_assignment`
+    (
+        "multiple_assign",
+        "disjunction, \",\", unparenthesized_list, \"=\", unparenthesized_list",
+    ),
+    (
+        "unparenthesized_list",
+        "[ disjunction ], { \",\", [ disjunction ] }",
+    ),
+    // `Parser::parse_variable_declaration`
+    ("variable_declaration", "\"var\", assign, NEWLINE"),
+    // `Parser::parse_while_statement`
+    ("while", "\"while\", disjunction, NEWLINE, block"),
+    // `Parser::parse_if_statement`
+    (
+        "if",
+        "\"if\", disjunction, NEWLINE, ( block | { statement }, \"else\", ( if | NEWLINE, block ) )",
+    ),
+    // `Parser::parse_return_statement`
+    ("return", "\"return\", [ disjunction ], NEWLINE"),
+    // `Parser::parse_assert_statement`. `assert` is a keyword only here, followed by a space, as
+    // decided by `Parser::is_assert_statement_start`.
+    (
+        "assert",
+        "\"assert\", disjunction, [ \",\", disjunction ], NEWLINE",
+    ),
+    // `Parser::parse_assign` and `assignment_operator`
+    ("assign", "disjunction, [ assignment_operator, assign ]"),
+    (
+        "assignment_operator",
+        "\"=\" | \"+=\" | \"-=\" | \"*=\" | \"/=\" | \"%=\" | \">>=\" | \"<<=\" | \"&=\" | \"^=\" | \"|=\"",
+    ),
+    // `Parser::parse_disjunction`
+    ("disjunction", "conjunction, { \"||\", conjunction }"),
+    // `Parser::parse_conjunction`
+    ("conjunction", "equality, { \"&&\", equality }"),
+    // `Parser::parse_binary_operation`, by the precedences in `infix_operator`
+    (
+        "equality",
+        "membership, { ( \"==\" | \"!=\" ), membership }",
+    ),
+    // `in` is not a keyword but an identifier, which is an operator only
+    // after an operand.
+    (
+        "membership",
+        "inequality, { \"in\", inequality }",
+    ),
+    (
+        "inequality",
+        "bitwise_or, { ( \"<\" | \"<=\" | \">\" | \">=\" ), bitwise_or }",
+    ),
+    ("bitwise_or", "bitwise_xor, { \"|\", bitwise_xor }"),
+    ("bitwise_xor", "bitwise_and, { \"^\", bitwise_and }"),
+    ("bitwise_and", "shift, { \"&\", shift }"),
+    ("shift", "additive, { ( \"<<\" | \">>\" ), additive }"),
+    (
+        "additive",
+        "multiplicative, { ( \"+\" | \"-\" ), multiplicative }",
+    ),
+    (
+        "multiplicative",
+        "power, { ( \"*\" | \"/\" | \"%\" ), power }",
+    ),
+    ("power", "factor, [ \"**\", power ]"),
+    // `Parser::parse_factor`
+    (
+        "factor",
+        "atom, { \".\", ( identifier | digits ) | \":\", factor | \"->\", factor \
+         | \"(\", list, \")\" | \"[\", list, \"]\" }",
+    ),
+    // `Parser::parse_atom` and `prefix_operator`
+    (
+        "atom",
+        "\"_\" | identifier | string_literal | number | prefixed_integer | \"int\" | \"float\" \
+         | \"(\", list, \")\" | prefix_operator, factor",
+    ),
+    ("number", "digits, [ \".\", [ digits ] ] | \".\", digits"),
+    ("prefix_operator", "\"+\" | \"-\" | \"/\" | \"!\" | \"~\""),
+    // `Parser::parse_list_elements_and_trailing_comma`
+    ("list", "[ assign ], { \",\", [ assign ] }"),
+    // `read_token`
+    ("identifier", "? a word other than keywords ?"),
+    ("digits", "? a sequence of decimal digits ?"),
+    (
+        "prefixed_integer",
+        "( \"0x\" | \"0o\" | \"0b\" ), ? digits, optionally separated by underscores ?",
+    ),
+    (
+        "string_literal",
+        "? text in double quotes, with placeholders `${value:spec}` ?",
+    ),
+    ("NEWLINE", "? a line break ?"),
+];
+
+/**
+ * Writes [`RULES`] in EBNF, one rule per line.
+ */
+pub fn ebnf() -> String {
+    RULES
+        .iter()
+        .map(|(name, definition)| format!("{name} = {definition} ;\n"))
+        .collect()
+}
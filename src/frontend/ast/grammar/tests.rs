@@ -0,0 +1,72 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use std::collections::HashSet;
+
+use super::*;
+
+/**
+ * Returns the names of the rules referred to in `definition`, skipping
+ * terminals in quotes and special sequences in `?`.
+ */
+fn references(definition: &str) -> Vec<&str> {
+    let mut references = Vec::new();
+    let mut chars = definition.char_indices().peekable();
+    while let Some((start, ch)) = chars.next() {
+        if ch == '"' || ch == '?' {
+            chars.find(|&(_, end)| end == ch);
+        } else if ch.is_ascii_alphabetic() {
+            let mut end = start + 1;
+            while let Some(&(index, ch)) = chars.peek() {
+                if !(ch.is_ascii_alphanumeric() || ch == '_') {
+                    break;
+                }
+                end = index + 1;
+                chars.next();
+            }
+            references.push(&definition[start..end]);
+        }
+    }
+    references
+}
+
+#[test]
+fn rules_are_consistent() {
+    let names: HashSet<_> = RULES.iter().map(|&(name, _)| name).collect();
+    assert_eq!(names.len(), RULES.len(), "duplicate rules");
+    let mut referred = HashSet::from(["file"]);
+    for (name, definition) in RULES {
+        for reference in references(definition) {
+            assert!(
+                names.contains(reference),
+                "`{reference}` in `{name}` is not defined"
+            );
+            referred.insert(reference);
+        }
+    }
+    assert_eq!(referred, names, "unused rules");
+}
+
+#[test]
+fn write_ebnf() {
+    let ebnf = ebnf();
+    assert!(ebnf.starts_with("file = { item } ;\nitem = import | "));
+    assert_eq!(ebnf.lines().count(), RULES.len());
+}
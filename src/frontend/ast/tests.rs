@@ -147,6 +147,108 @@ fn skip_comments() {
     }
 }
 
+#[test]
+fn classify() {
+    let input = "func f(x) -- comment\n    /- block -/ x + \"s${x}\"\nend";
+    let mut chars_peekable = CharsPeekable::new(&input);
+    assert_eq!(
+        classify_tokens(&mut chars_peekable).unwrap(),
+        [
+            (TokenClass::Keyword, pos!(0:0-0:4)),
+            (TokenClass::Identifier, pos!(0:5-0:6)),
+            (TokenClass::Operator, pos!(0:6-0:7)),
+            (TokenClass::Identifier, pos!(0:7-0:8)),
+            (TokenClass::Operator, pos!(0:8-0:9)),
+            (TokenClass::Comment, pos!(0:10-0:20)),
+            (TokenClass::Comment, pos!(1:4-1:15)),
+            (TokenClass::Identifier, pos!(1:16-1:17)),
+            (TokenClass::Operator, pos!(1:18-1:19)),
+            (TokenClass::Literal, pos!(1:20-1:27)),
+            (TokenClass::Keyword, pos!(2:0-2:3)),
+        ]
+    );
+}
+
+#[test]
+fn allow_comments() {
+    let input = "-- syscraws:allow(a, b)\nx\n    -- syscraws:allow(c)\n    y\n-- allow(d)\nz\n";
+    let mut chars_peekable = CharsPeekable::new(&input);
+    let file = parse_file(&mut chars_peekable).unwrap();
+    assert_eq!(
+        file.allowed_lints,
+        [
+            (1, String::from("a")),
+            (1, String::from("b")),
+            (3, String::from("c")),
+        ]
+    );
+}
+
+#[test]
+fn nesting_depth_limit() {
+    for (input, depth) in [
+        (format!("{}x{}\n", "(".repeat(7), ")".repeat(7)), 8),
+        (format!("{}x\n", "!".repeat(7)), 8),
+        (format!("{}{}", "while x\n".repeat(8), "end\n".repeat(8)), 8),
+        (String::from("a = b = c = d\n"), 4),
+    ] {
+        let mut chars_peekable = CharsPeekable::new(&input);
+        assert!(parse_file_with_max_nesting_depth(&mut chars_peekable, depth).is_ok());
+        let mut chars_peekable = CharsPeekable::new(&input);
+        let Err(ParseError::TooDeeplyNested {
+            max_nesting_depth, ..
+        }) = parse_file_with_max_nesting_depth(&mut chars_peekable, depth - 1)
+        else {
+            panic!("{input}");
+        };
+        assert_eq!(max_nesting_depth, depth - 1);
+    }
+}
+
+#[test]
+fn enclosing_call() {
+    let input = "func g(a, b)\n    f(x, [y, z], h(w)";
+    let call_at = |cursor| {
+        find_enclosing_call(&mut CharsPeekable::new(input), cursor)
+            .unwrap()
+            .map(|call| (call.name, call.active_parameter))
+    };
+    // In the parameters of a definition.
+    assert_eq!(call_at(index!(0:9)), None);
+    assert_eq!(call_at(index!(1:6)), Some((String::from("f"), 0)));
+    // The commas in `[y, z]` are not counted.
+    assert_eq!(call_at(index!(1:13)), Some((String::from("f"), 1)));
+    assert_eq!(call_at(index!(1:20)), Some((String::from("h"), 0)));
+    assert_eq!(call_at(index!(1:21)), Some((String::from("f"), 2)));
+    assert_eq!(call_at(index!(1:4)), None);
+}
+
+#[test]
+fn peek_tokens() {
+    let input = "a -- comment\n(b)";
+    let mut chars_peekable = CharsPeekable::new(&input);
+    let mut parser = Parser::new(&mut chars_peekable).unwrap();
+    assert_eq!(
+        parser.peek_nth(3).unwrap(),
+        Some(&Token::ClosingParenthesis)
+    );
+    assert_eq!(parser.peek_nth(4).unwrap(), None);
+    assert_eq!(parser.peek_nth(5).unwrap(), None);
+    assert_eq!(
+        parser.peek_nth(0).unwrap(),
+        Some(&Token::Identifier(String::from("a")))
+    );
+    parser.consume_token().unwrap();
+    assert_eq!(parser.current_pos(), pos!(1:0-1:1));
+    assert_eq!(parser.prev_end, pos!(0:0-0:1).end);
+    assert!(parser.current.is_on_new_line);
+    assert_eq!(parser.current.comments, [pos!(0:2-0:12)]);
+    assert_eq!(
+        parser.peek_nth(1).unwrap(),
+        Some(&Token::Identifier(String::from("b")))
+    );
+}
+
 #[test]
 fn parse_numeric_literal() {
     for input in ["12", "1.2", "12.", ".12", "6.02e23", "6.02e+23", "1.6e-19"] {
@@ -158,6 +260,63 @@ fn parse_numeric_literal() {
     }
 }
 
+#[test]
+fn parse_prefixed_integer_literal() {
+    for (input, radix, digits) in [
+        ("0x1e+5", 16, "1e"),
+        ("0xFF_FF", 16, "FFFF"),
+        ("0b1010", 2, "1010"),
+        ("0o17", 8, "17"),
+        ("0b", 2, ""),
+    ] {
+        let mut chars_peekable = CharsPeekable::new(&input);
+        let mut parser = Parser::new(&mut chars_peekable).unwrap();
+        let factor = parser.parse_atom(false).unwrap().unwrap();
+        assert_eq!(
+            factor.term,
+            Term::IntegerLiteral {
+                radix,
+                digits: String::from(digits)
+            }
+        );
+    }
+}
+
+#[test]
+fn parse_format_spec() {
+    for (input, zero_padding, width, precision, radix) in [
+        (r#""${x:04}""#, true, Some(4), None, None),
+        (r#""${f:.3}""#, false, None, Some(3), None),
+        (r#""${n:8x}""#, false, Some(8), None, Some(16)),
+        (r#""${n:b}""#, false, None, None, Some(2)),
+        (r#""${x:}""#, false, None, None, None),
+    ] {
+        let mut chars_peekable = CharsPeekable::new(&input);
+        let mut parser = Parser::new(&mut chars_peekable).unwrap();
+        let factor = parser.parse_atom(false).unwrap().unwrap();
+        let Term::StringLiteral(components) = factor.term else {
+            panic!("Not a string literal");
+        };
+        let [StringLiteralComponent::PlaceHolder { value, spec, .. }] = &components[..] else {
+            panic!("Not a placeholder");
+        };
+        assert!(matches!(value.as_ref().unwrap().term, Term::Identifier(_)));
+        assert_eq!(
+            *spec,
+            Some(FormatSpec {
+                zero_padding,
+                width,
+                precision,
+                radix,
+            })
+        );
+    }
+    for input in [r#""${x:4y}""#, r#""${x:.}""#, r#""${x:04"#] {
+        let mut chars_peekable = CharsPeekable::new(&input);
+        assert!(Parser::new(&mut chars_peekable).is_err());
+    }
+}
+
 #[test]
 fn parse_string_literal() {
     let input = r#""foo$x{10}${ bar }baz""#;
@@ -173,7 +332,7 @@ fn parse_string_literal() {
     );
     match &components[1] {
         StringLiteralComponent::String(s) => panic!("{}", s),
-        StringLiteralComponent::PlaceHolder { format, value } => {
+        StringLiteralComponent::PlaceHolder { format, value, .. } => {
             assert_eq!(format, "x");
             let value = value.as_ref().unwrap();
             assert_eq!(value.term, Term::NumericLiteral(String::from("10")));
@@ -182,7 +341,7 @@ fn parse_string_literal() {
     }
     match &components[2] {
         StringLiteralComponent::String(s) => panic!("{}", s),
-        StringLiteralComponent::PlaceHolder { format, value } => {
+        StringLiteralComponent::PlaceHolder { format, value, .. } => {
             assert_eq!(format, "");
             let value = value.as_ref().unwrap();
             assert_eq!(value.term, Term::Identifier(String::from("bar")));
@@ -301,3 +460,136 @@ fn parse_function_definition() {
         }
     }
 }
+
+#[test]
+fn parse_test_definition() {
+    let input = r#"
+    test "addition"
+        x + y
+    end
+    "#;
+    let mut chars_peekable = CharsPeekable::new(&input);
+    let mut parser = Parser::new(&mut chars_peekable).unwrap();
+    let definition = parser.parse_test_definition().unwrap();
+    assert_eq!(definition.keyword_test_pos, pos!(1:4-1:8));
+    let name = definition.name.unwrap();
+    assert_eq!(
+        name.term,
+        Term::StringLiteral(vec![StringLiteralComponent::String(String::from(
+            "addition"
+        ))])
+    );
+    assert_eq!(name.pos, pos!(1:9-1:19));
+    assert!(definition.extra_tokens_after_name_pos.is_none());
+    assert_eq!(definition.body.len(), 1);
+    assert!(definition.extra_tokens_pos.is_none());
+}
+
+#[test]
+fn parse_assert_statement() {
+    let input = r#"assert x == 1, "x is one""#;
+    let mut chars_peekable = CharsPeekable::new(&input);
+    let mut parser = Parser::new(&mut chars_peekable).unwrap();
+    let Statement::Assert {
+        keyword_assert_pos,
+        condition,
+        message,
+    } = parser.parse_assert_statement().unwrap()
+    else {
+        panic!();
+    };
+    assert_eq!(keyword_assert_pos, pos!(0:0-0:6));
+    assert_eq!(condition.unwrap().pos, pos!(0:7-0:13));
+    assert_eq!(message.unwrap().pos, pos!(0:15-0:25));
+}
+
+#[test]
+fn contextual_keywords() {
+    let input = r#"
+var test = 1
+test += 1
+test "uses"
+    assert(test)
+    assert test == 2
+    assert -test < 0
+    bench.count = 1
+end
+"#;
+    let mut chars_peekable = CharsPeekable::new(input);
+    let file = parse_file(&mut chars_peekable).unwrap();
+    let [declaration, assignment, TopLevelStatement::TestDefinition(definition)] =
+        &file.top_level_statements[..]
+    else {
+        panic!("{:#?}", file.top_level_statements);
+    };
+    assert!(matches!(
+        declaration,
+        TopLevelStatement::Statement(Statement::VariableDeclaration { .. })
+    ));
+    let TopLevelStatement::Statement(Statement::Term(assignment)) = assignment else {
+        panic!("{assignment:#?}");
+    };
+    assert!(matches!(assignment.term, Term::Assignment { .. }));
+    let [Statement::Term(call), assert_equal, assert_less, Statement::Term(field)] =
+        &definition.body[..]
+    else {
+        panic!("{:#?}", definition.body);
+    };
+    assert!(matches!(call.term, Term::FunctionCall { .. }));
+    assert!(matches!(assert_equal, Statement::Assert { .. }));
+    assert!(matches!(assert_less, Statement::Assert { .. }));
+    assert!(matches!(field.term, Term::Assignment { .. }));
+}
+
+#[test]
+fn test_definition_start() {
+    // `test` and `bench` start an item only before a string literal or a
+    // line break.
+    let input = r#"
+test(1)
+bench.count = 1
+test "named"
+end
+bench
+end
+"#;
+    let mut chars_peekable = CharsPeekable::new(input);
+    let file = parse_file(&mut chars_peekable).unwrap();
+    let [TopLevelStatement::Statement(Statement::Term(call)), TopLevelStatement::Statement(Statement::Term(field)), TopLevelStatement::TestDefinition(named), TopLevelStatement::TestDefinition(unnamed)] =
+        &file.top_level_statements[..]
+    else {
+        panic!("{:#?}", file.top_level_statements);
+    };
+    assert!(matches!(call.term, Term::FunctionCall { .. }));
+    assert!(matches!(field.term, Term::Assignment { .. }));
+    assert!(!named.is_benchmark && named.name.is_some());
+    assert!(unnamed.is_benchmark && unnamed.name.is_none());
+}
+
+#[test]
+fn assert_statement_start() {
+    // `assert` starts a statement only before a space and a term.
+    let input = "assert (x)\nassert(x)\nassert = 1\nassert\n";
+    let mut chars_peekable = CharsPeekable::new(input);
+    let file = parse_file(&mut chars_peekable).unwrap();
+    let [TopLevelStatement::Statement(Statement::Assert { .. }), TopLevelStatement::Statement(Statement::Term(call)), TopLevelStatement::Statement(Statement::Term(assignment)), TopLevelStatement::Statement(Statement::Term(name))] =
+        &file.top_level_statements[..]
+    else {
+        panic!("{:#?}", file.top_level_statements);
+    };
+    assert!(matches!(call.term, Term::FunctionCall { .. }));
+    assert!(matches!(assignment.term, Term::Assignment { .. }));
+    assert_eq!(name.term, Term::Identifier(String::from("assert")));
+}
+
+#[test]
+fn many_comments() {
+    // Consecutive comments must not deepen the recursion in `read_token`.
+    let input = "--comment\n".repeat(100_000) + "foo";
+    let mut chars_peekable = CharsPeekable::new(&input);
+    let parser = Parser::new(&mut chars_peekable).unwrap();
+    assert_eq!(
+        parser.current.token,
+        Some(Token::Identifier(String::from("foo")))
+    );
+}
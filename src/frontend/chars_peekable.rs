@@ -60,6 +60,10 @@ pub struct CharsPeekable<'input> {
      * The byte ranges of each line in the input string.
      */
     lines: Vec<Range<usize>>,
+    /**
+     * The number of tokens started so far, counted by the lexer.
+     */
+    num_tokens: usize,
 }
 
 impl<'input> CharsPeekable<'input> {
@@ -75,6 +79,7 @@ impl<'input> CharsPeekable<'input> {
             current_index: 0,
             current_line_start: 0,
             lines: Vec::new(),
+            num_tokens: 0,
         }
     }
     /**
@@ -120,6 +125,20 @@ impl<'input> CharsPeekable<'input> {
         }
         ret
     }
+    /**
+     * Records that a token starts at the current character.
+     */
+    pub fn count_token(&mut self) {
+        self.num_tokens += 1;
+    }
+    /**
+     * Returns the number of tokens recorded by
+     * [`count_token`](Self::count_token), including the ones in
+     * placeholders of string literals.
+     */
+    pub fn num_tokens(&self) -> usize {
+        self.num_tokens
+    }
     /**
      * Returns the byte ranges of each line in the input string.
      * For each line, the range is represented by the byte positions
@@ -0,0 +1,427 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/*!
+ * Evaluation of the initializers of `const` definitions.
+ */
+
+use std::collections::HashMap;
+
+use super::{ast, translate_import, Item};
+use crate::{backend::Constant, log};
+
+/**
+ * Converts a numeric literal, such as `42`, `1.5` or `1e5`, into an integer
+ * if it consists only of digits, or into a floating-point number otherwise.
+ */
+pub fn numeric_literal(
+    value: &str,
+    pos: log::Pos,
+    file: &log::File,
+    logger: &mut log::Logger,
+) -> Option<Constant> {
+    if value.chars().all(|ch| ch.is_ascii_digit()) {
+        return match value.parse() {
+            Ok(value) => Some(Constant::Integer(value)),
+            Err(_) => {
+                logger.error(
+                    file,
+                    log::Diagnostic::new(format!("Overflow in an integer literal at {pos}."))
+                        .quote_pos(pos),
+                );
+                None
+            }
+        };
+    }
+    if let Ok(value) = value.parse() {
+        Some(Constant::Float(value))
+    } else {
+        logger.error(
+            file,
+            log::Diagnostic::new(format!("Invalid numeric literal at {pos}.")).quote_pos(pos),
+        );
+        None
+    }
+}
+
+/**
+ * Converts an integer literal with a radix prefix, validating the digits.
+ */
+pub fn integer_literal(
+    radix: u32,
+    digits: &str,
+    pos: log::Pos,
+    file: &log::File,
+    logger: &mut log::Logger,
+) -> Option<Constant> {
+    let name = match radix {
+        16 => "a hexadecimal",
+        8 => "an octal",
+        _ => "a binary",
+    };
+    if digits.is_empty() {
+        logger.error(
+            file,
+            log::Diagnostic::new(format!("Missing digits in {name} literal at {pos}."))
+                .quote_pos(pos),
+        );
+        return None;
+    }
+    if let Some(ch) = digits.chars().find(|ch| !ch.is_digit(radix)) {
+        logger.error(
+            file,
+            log::Diagnostic::new(format!("Invalid digit `{ch}` in {name} literal at {pos}."))
+                .quote_pos(pos),
+        );
+        return None;
+    }
+    match i64::from_str_radix(digits, radix) {
+        Ok(value) => Some(Constant::Integer(value)),
+        Err(_) => {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Overflow in {name} literal at {pos}."))
+                    .quote_pos(pos),
+            );
+            None
+        }
+    }
+}
+
+/**
+ * Evaluates `term` at compile time. It may contain numeric (including
+ * hexadecimal, octal and binary) and string literals, constants defined
+ * before, and arithmetic, comparison and logical operators. Strings can be
+ * concatenated by `+` and repeated by `*` with an integer.
+ */
+pub fn evaluate(
+    term: ast::TermWithPos,
+    named_items: &HashMap<String, Item>,
+    exported_items: &Vec<HashMap<String, Item>>,
+    file: &log::File,
+    logger: &mut log::Logger,
+) -> Option<Constant> {
+    let pos = term.pos;
+    match term.term {
+        ast::Term::NumericLiteral(value) => numeric_literal(&value, pos, file, logger),
+        ast::Term::IntegerLiteral { radix, digits } => {
+            integer_literal(radix, &digits, pos, file, logger)
+        }
+        ast::Term::StringLiteral(components) => {
+            let mut value = String::new();
+            for component in components {
+                match component {
+                    ast::StringLiteralComponent::String(string) => value.push_str(&string),
+                    ast::StringLiteralComponent::PlaceHolder { .. } => {
+                        logger.error(
+                            file,
+                            log::Diagnostic::new(
+                                "A constant string must not contain a placeholder.",
+                            )
+                            .quote_pos(pos),
+                        );
+                        return None;
+                    }
+                }
+            }
+            Some(Constant::String(value))
+        }
+        ast::Term::Identifier(name) => match named_items.get(&name) {
+            Some(Item::Constant(value)) => Some(value.clone()),
+            _ => {
+                logger.error(
+                    file,
+                    log::Diagnostic::new(format!("`{name}` at {pos} is not a constant."))
+                        .quote_pos(pos),
+                );
+                None
+            }
+        },
+        ast::Term::FieldByName { term_left, name } => {
+            let item = translate_import(&term_left, named_items, exported_items)
+                .and_then(|file_index| exported_items[file_index].get(&name));
+            match item {
+                Some(Item::Constant(value)) => Some(value.clone()),
+                _ => {
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!("`{name}` at {pos} is not a constant."))
+                            .quote_pos(pos),
+                    );
+                    None
+                }
+            }
+        }
+        ast::Term::Parenthesized { inner } => {
+            evaluate(*inner, named_items, exported_items, file, logger)
+        }
+        ast::Term::UnaryOperation { operator, operand } => {
+            let ast::Term::MethodName(method) = operator.term else {
+                unreachable!();
+            };
+            let Some(operand) = operand else {
+                logger.error(
+                    file,
+                    log::Diagnostic::new(format!("Missing operand in a constant at {pos}."))
+                        .quote_pos(pos),
+                );
+                return None;
+            };
+            let operand = evaluate(*operand, named_items, exported_items, file, logger)?;
+            let value = match (method.as_str(), operand) {
+                ("plus", Constant::Integer(value)) => Some(Constant::Integer(value)),
+                ("plus", Constant::Float(value)) => Some(Constant::Float(value)),
+                ("minus", Constant::Integer(value)) => value.checked_neg().map(Constant::Integer),
+                ("minus", Constant::Float(value)) => Some(Constant::Float(-value)),
+                ("reciprocal", Constant::Float(value)) => Some(Constant::Float(1. / value)),
+                ("logical_not", Constant::Boolean(value)) => Some(Constant::Boolean(!value)),
+                ("bitwise_not", Constant::Integer(value)) => Some(Constant::Integer(!value)),
+                _ => {
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!(
+                            "Invalid operand of `{method}` in a constant at {pos}."
+                        ))
+                        .quote_pos(pos),
+                    );
+                    return None;
+                }
+            };
+            if value.is_none() {
+                logger.error(
+                    file,
+                    log::Diagnostic::new(format!("Overflow in a constant at {pos}."))
+                        .quote_pos(pos),
+                );
+            }
+            value
+        }
+        ast::Term::BinaryOperation {
+            left_operand,
+            operator,
+            right_operand,
+        } => {
+            let ast::Term::MethodName(method) = operator.term else {
+                unreachable!();
+            };
+            let (Some(left_operand), Some(right_operand)) = (left_operand, right_operand) else {
+                logger.error(
+                    file,
+                    log::Diagnostic::new(format!("Missing operand in a constant at {pos}."))
+                        .quote_pos(pos),
+                );
+                return None;
+            };
+            let left_operand = evaluate(*left_operand, named_items, exported_items, file, logger);
+            let right_operand = evaluate(*right_operand, named_items, exported_items, file, logger);
+            let (left_operand, right_operand) = (left_operand?, right_operand?);
+            let value = match (left_operand, right_operand) {
+                (Constant::Integer(left), Constant::Integer(right)) => {
+                    integer_operation(&method, left, right)
+                }
+                (Constant::Float(left), Constant::Float(right)) => {
+                    float_operation(&method, left, right)
+                }
+                (Constant::Boolean(left), Constant::Boolean(right)) => match method.as_str() {
+                    "equal" => Some(Ok(Constant::Boolean(left == right))),
+                    "not_equal" => Some(Ok(Constant::Boolean(left != right))),
+                    _ => None,
+                },
+                (Constant::String(left), Constant::String(right)) => match method.as_str() {
+                    "add" => Some(concatenate(left, &right)),
+                    "equal" => Some(Ok(Constant::Boolean(left == right))),
+                    "not_equal" => Some(Ok(Constant::Boolean(left != right))),
+                    _ => None,
+                },
+                (Constant::String(left), Constant::Integer(count)) if method == "mul" => {
+                    Some(repeat(&left, count))
+                }
+                _ => None,
+            };
+            match value {
+                Some(Ok(value)) => Some(value),
+                Some(Err(message)) => {
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!("{message} in a constant at {pos}."))
+                            .quote_pos(pos),
+                    );
+                    None
+                }
+                None => {
+                    logger.error(
+                        file,
+                        log::Diagnostic::new(format!(
+                            "Invalid operands of `{method}` in a constant at {pos}."
+                        ))
+                        .quote_pos(pos),
+                    );
+                    None
+                }
+            }
+        }
+        ast::Term::Conjunction { conditions, .. } => {
+            evaluate_conditions(conditions, true, named_items, exported_items, file, logger)
+        }
+        ast::Term::Disjunction { conditions, .. } => {
+            evaluate_conditions(conditions, false, named_items, exported_items, file, logger)
+        }
+        _ => {
+            logger.error(
+                file,
+                log::Diagnostic::new(format!("Cannot evaluate at compile time at {pos}."))
+                    .quote_pos(pos),
+            );
+            None
+        }
+    }
+}
+
+/**
+ * Evaluates the operands of `&&` (if `is_conjunction`) or `||`. All of them
+ * are evaluated to report errors, without short-circuiting.
+ */
+fn evaluate_conditions(
+    conditions: Vec<Option<ast::TermWithPos>>,
+    is_conjunction: bool,
+    named_items: &HashMap<String, Item>,
+    exported_items: &Vec<HashMap<String, Item>>,
+    file: &log::File,
+    logger: &mut log::Logger,
+) -> Option<Constant> {
+    let mut ret = Some(is_conjunction);
+    for condition in conditions {
+        let pos = condition.as_ref().map(|condition| condition.pos.clone());
+        match condition
+            .and_then(|condition| evaluate(condition, named_items, exported_items, file, logger))
+        {
+            Some(Constant::Boolean(value)) => {
+                if let Some(ret) = &mut ret {
+                    if is_conjunction {
+                        *ret &= value;
+                    } else {
+                        *ret |= value;
+                    }
+                }
+            }
+            Some(_) => {
+                let pos = pos.unwrap();
+                logger.error(
+                    file,
+                    log::Diagnostic::new(format!("Expected a boolean constant at {pos}."))
+                        .quote_pos(pos),
+                );
+                ret = None;
+            }
+            None => ret = None,
+        }
+    }
+    ret.map(Constant::Boolean)
+}
+
+/**
+ * Returns `None` if `method` is not defined for integers, or `Some(Err)`
+ * with a message if the result cannot be computed.
+ */
+fn integer_operation(
+    method: &str,
+    left: i64,
+    right: i64,
+) -> Option<Result<Constant, &'static str>> {
+    let value = match method {
+        "add" => left.checked_add(right),
+        "sub" => left.checked_sub(right),
+        "mul" => left.checked_mul(right),
+        "div" | "rem" if right == 0 => return Some(Err("Division by zero")),
+        "div" => left.checked_div(right),
+        "rem" => left.checked_rem(right),
+        "pow" if right < 0 => return Some(Err("Negative exponent")),
+        "pow" => u32::try_from(right)
+            .ok()
+            .and_then(|right| left.checked_pow(right)),
+        // Same as `backend::Function::ILeftShift` and `IRightShift`.
+        "left_shift" | "right_shift" if !(0..i64::BITS as i64).contains(&right) => {
+            return Some(Err("Shift amount out of range"))
+        }
+        "left_shift" => Some(left << right),
+        "right_shift" => Some(left >> right),
+        "bitwise_and" => Some(left & right),
+        "bitwise_xor" => Some(left ^ right),
+        "bitwise_or" => Some(left | right),
+        _ => return compare(method, &left, &right).map(Ok),
+    };
+    Some(value.map(Constant::Integer).ok_or("Overflow"))
+}
+
+/**
+ * Same as [`integer_operation`], for floating-point numbers.
+ */
+fn float_operation(method: &str, left: f64, right: f64) -> Option<Result<Constant, &'static str>> {
+    let value = match method {
+        "add" => left + right,
+        "sub" => left - right,
+        "mul" => left * right,
+        "div" => left / right,
+        "rem" => left % right,
+        "pow" => left.powf(right),
+        _ => return compare(method, &left, &right).map(Ok),
+    };
+    Some(Ok(Constant::Float(value)))
+}
+
+/**
+ * The maximum length in bytes of a string made in a constant, so that
+ * something like `"ab" * 1000000000000000` is reported instead of
+ * exhausting the memory.
+ */
+const MAX_STRING_LEN: usize = 1 << 20;
+
+/**
+ * `left + right` on strings.
+ */
+fn concatenate(mut left: String, right: &str) -> Result<Constant, &'static str> {
+    if left.len() + right.len() > MAX_STRING_LEN {
+        return Err("String too long");
+    }
+    left.push_str(right);
+    Ok(Constant::String(left))
+}
+
+/**
+ * `string * count`, which repeats `string` `count` times.
+ */
+fn repeat(string: &str, count: i64) -> Result<Constant, &'static str> {
+    let count = usize::try_from(count).map_err(|_| "Negative repetition count")?;
+    match string.len().checked_mul(count) {
+        Some(len) if len <= MAX_STRING_LEN => Ok(Constant::String(string.repeat(count))),
+        _ => Err("String too long"),
+    }
+}
+
+fn compare<T: PartialOrd>(method: &str, left: &T, right: &T) -> Option<Constant> {
+    let value = match method {
+        "equal" => left == right,
+        "not_equal" => left != right,
+        "less" => left < right,
+        "less_or_equal" => left <= right,
+        "greater" => left > right,
+        "greater_or_equal" => left >= right,
+        _ => return None,
+    };
+    Some(Constant::Boolean(value))
+}
@@ -0,0 +1,523 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/*!
+ * Documentation of the items defined in files, for `syscraws doc`,
+ * `syscraws symbols`, signature help and outlines.
+ */
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use super::{ast, resolve_import, CharsPeekable, Errors, SourceProvider};
+use crate::log;
+
+/**
+ * The items defined in a file.
+ */
+#[derive(Debug, PartialEq, Eq)]
+pub struct ModuleDocumentation {
+    pub path: PathBuf,
+    pub items: Vec<DocumentedItem>,
+}
+
+/**
+ * An item defined in a file, with its signature and doc comment.
+ */
+#[derive(Debug, PartialEq, Eq)]
+pub struct DocumentedItem {
+    pub kind: ItemKind,
+    pub name: String,
+    /**
+     * The source of the item without the bodies of functions, e.g.
+     * `func add[T](x: T, y: T): T`.
+     */
+    pub signature: String,
+    /**
+     * The source of each parameter of a function or a method, e.g. `x: T`.
+     * Empty for the other items.
+     */
+    pub parameters: Vec<String>,
+    pub doc_comment: Option<String>,
+    /**
+     * The zero-based line where the item is defined.
+     */
+    pub line: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    Structure,
+    Enumeration,
+    Function,
+    Method,
+    Constant,
+    Variable,
+}
+
+impl std::fmt::Display for ItemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let keyword = match self {
+            ItemKind::Structure => "struct",
+            ItemKind::Enumeration => "enum",
+            ItemKind::Function => "func",
+            ItemKind::Method => "method",
+            ItemKind::Constant => "const",
+            ItemKind::Variable => "var",
+        };
+        write!(f, "{keyword}")
+    }
+}
+
+/**
+ * Reads the file specified by `root_file_path` and the files it imports,
+ * and lists the items defined in each of them. The root file comes first.
+ */
+pub fn read_documentation(
+    root_file_path: &Path,
+    source_provider: &dyn SourceProvider,
+    sink: &mut dyn log::DiagnosticSink,
+) -> Result<Vec<ModuleDocumentation>, Errors> {
+    let root_file_path = root_file_path.with_extension("sysc");
+    let root_file_path = match source_provider.canonicalize(&root_file_path) {
+        Ok(path) => path,
+        Err(err) => {
+            sink.emit(None, log::root_file_not_found(&root_file_path, err));
+            return Err(Errors {
+                num_errors: 1,
+                timings: Vec::new(),
+            });
+        }
+    };
+    let mut logger = log::Logger::new(sink);
+    let mut modules = Vec::new();
+    let mut visited = HashSet::from([root_file_path.clone()]);
+    let mut queue = vec![root_file_path];
+    while let Some(path) = queue.pop() {
+        let content = match source_provider.read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) => {
+                logger.error_without_file(log::cannot_read_root_file(&path, err));
+                continue;
+            }
+        };
+        let mut chars_peekable = CharsPeekable::new(&content);
+        let result = ast::parse_file(&mut chars_peekable);
+        let file = log::File {
+            path: path.clone(),
+            lines: chars_peekable.lines(),
+            content,
+            allowed_lints: Vec::new(),
+        };
+        let ast = match result {
+            Ok(ast) => ast,
+            Err(err) => {
+                logger.error(&file, err.into());
+                continue;
+            }
+        };
+        let parent_directory = path.parent().unwrap();
+        let mut imported_paths = Vec::new();
+        for import in ast.imports {
+            if let Ok((_, imported_path)) = resolve_import(
+                import,
+                parent_directory,
+                source_provider,
+                &file,
+                &mut logger,
+            ) {
+                if visited.insert(imported_path.clone()) {
+                    imported_paths.push(imported_path);
+                }
+            }
+        }
+        // Pushed in reverse so that the files are visited in the order of imports.
+        queue.extend(imported_paths.into_iter().rev());
+        let items = document_items(
+            ast.structure_names,
+            ast.enumeration_definitions,
+            ast.function_names,
+            ast.constant_definitions,
+            ast.top_level_statements,
+            &file,
+        );
+        modules.push(ModuleDocumentation { path, items });
+    }
+    if logger.num_errors() > 0 {
+        logger.aborting();
+        return Err(Errors {
+            num_errors: logger.num_errors(),
+            timings: Vec::new(),
+        });
+    }
+    Ok(modules)
+}
+
+fn document_items(
+    structure_names: Vec<ast::StructureName>,
+    enumeration_definitions: Vec<ast::EnumerationDefinition>,
+    function_names: Vec<ast::FunctionName>,
+    constant_definitions: Vec<ast::ConstantDefinition>,
+    top_level_statements: Vec<ast::TopLevelStatement>,
+    file: &log::File,
+) -> Vec<DocumentedItem> {
+    let mut structure_definitions = Vec::new();
+    let mut function_definitions = Vec::new();
+    let mut global_variables = Vec::new();
+    for statement in top_level_statements {
+        match statement {
+            ast::TopLevelStatement::StructureDefinition(definition) => {
+                structure_definitions.push(definition)
+            }
+            ast::TopLevelStatement::FunctionDefinition(definition) => {
+                function_definitions.push(definition)
+            }
+            ast::TopLevelStatement::Statement(ast::Statement::VariableDeclaration {
+                keyword_var_pos,
+                term: Some(term),
+            }) => global_variables.push((keyword_var_pos, term)),
+            _ => {}
+        }
+    }
+
+    let mut items = Vec::new();
+    for (structure, definition) in structure_names.into_iter().zip(structure_definitions) {
+        let Some(name) = structure.name else {
+            continue;
+        };
+        let mut signature = format!("struct {name}");
+        write_list(&mut signature, "[", &definition.ty_parameters, "]", file);
+        for field in &definition.fields {
+            write!(signature, "\n    {}", file.source(&field.field.pos)).unwrap();
+        }
+        signature.push_str("\nend");
+        items.push(DocumentedItem {
+            kind: ItemKind::Structure,
+            name,
+            signature,
+            parameters: Vec::new(),
+            doc_comment: structure.doc_comment,
+            line: structure.keyword_struct_pos.line(),
+        });
+    }
+    for enumeration in enumeration_definitions {
+        let Some(name) = enumeration.name else {
+            continue;
+        };
+        let mut signature = format!("enum {name}");
+        write_list(&mut signature, "[", &enumeration.ty_parameters, "]", file);
+        for variant in &enumeration.variants {
+            write!(signature, "\n    {}", file.source(&variant.variant.pos)).unwrap();
+        }
+        signature.push_str("\nend");
+        items.push(DocumentedItem {
+            kind: ItemKind::Enumeration,
+            name,
+            signature,
+            parameters: Vec::new(),
+            doc_comment: enumeration.doc_comment,
+            line: enumeration.keyword_enum_pos.line(),
+        });
+    }
+    for (function, definition) in function_names.into_iter().zip(function_definitions) {
+        let Some(name) = function.name else {
+            continue;
+        };
+        let kind = if function.is_method {
+            ItemKind::Method
+        } else {
+            ItemKind::Function
+        };
+        let mut signature = format!("{kind} {name}");
+        write_list(&mut signature, "[", &definition.ty_parameters, "]", file);
+        write_list(&mut signature, "(", &definition.parameters, ")", file);
+        if let Some(ast::ReturnType { ty: Some(ty), .. }) = &definition.return_ty {
+            write!(signature, ": {}", file.source(&ty.pos)).unwrap();
+        }
+        let parameters = definition
+            .parameters
+            .iter()
+            .flatten()
+            .filter_map(|element| match element {
+                ast::ListElement::NonEmpty(parameter) => {
+                    Some(file.source(&parameter.pos).to_owned())
+                }
+                ast::ListElement::Empty { .. } => None,
+            })
+            .collect();
+        items.push(DocumentedItem {
+            kind,
+            name,
+            signature,
+            parameters,
+            doc_comment: function.doc_comment,
+            line: function.keyword_func_pos.line(),
+        });
+    }
+    for constant in constant_definitions {
+        let Some(term) = constant.term else {
+            continue;
+        };
+        let ast::Term::Assignment {
+            left_hand_side: Some(ref left_hand_side),
+            ..
+        } = term.term
+        else {
+            continue;
+        };
+        let ast::Term::Identifier(ref name) = left_hand_side.term else {
+            continue;
+        };
+        items.push(DocumentedItem {
+            kind: ItemKind::Constant,
+            name: name.clone(),
+            signature: format!("const {}", file.source(&term.pos)),
+            parameters: Vec::new(),
+            doc_comment: constant.doc_comment,
+            line: constant.keyword_const_pos.line(),
+        });
+    }
+    for (keyword_var_pos, term) in global_variables {
+        // `var x`, `var x = value` or `var x, y = values`.
+        let names = match term {
+            ast::TermWithPos {
+                term:
+                    ast::Term::Assignment {
+                        left_hand_side: Some(left_hand_side),
+                        ..
+                    },
+                ..
+            } => *left_hand_side,
+            term => term,
+        };
+        let names = match names.term {
+            ast::Term::Tuple { elements } => elements
+                .into_iter()
+                .filter_map(|element| match element {
+                    ast::ListElement::NonEmpty(name) => Some(name),
+                    ast::ListElement::Empty { .. } => None,
+                })
+                .collect(),
+            _ => vec![names],
+        };
+        for name in names {
+            if let ast::Term::Identifier(name) = name.term {
+                items.push(DocumentedItem {
+                    kind: ItemKind::Variable,
+                    signature: format!("var {name}"),
+                    name,
+                    parameters: Vec::new(),
+                    doc_comment: None,
+                    line: keyword_var_pos.line(),
+                });
+            }
+        }
+    }
+    items
+}
+
+/**
+ * An item in the outline of a file, spanning from `start_line` to
+ * `end_line`, both zero-based and inclusive.
+ */
+#[derive(Debug, PartialEq, Eq)]
+pub struct OutlineItem {
+    pub kind: ItemKind,
+    pub name: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/**
+ * Lists the items defined in `source`, the content of a file, in order of
+ * appearance. A structure, an enumeration or a function spans up to its
+ * `end`.
+ */
+pub fn outline(source: &str) -> Result<Vec<OutlineItem>, log::ParseError> {
+    let mut chars_peekable = CharsPeekable::new(source);
+    let ast = ast::parse_file(&mut chars_peekable)?;
+    let file = log::File {
+        path: PathBuf::new(),
+        lines: chars_peekable.lines(),
+        content: source.to_owned(),
+        allowed_lints: Vec::new(),
+    };
+    let block_ends: HashMap<_, _> = ast.blocks.into_iter().collect();
+    let items = document_items(
+        ast.structure_names,
+        ast.enumeration_definitions,
+        ast.function_names,
+        ast.constant_definitions,
+        ast.top_level_statements,
+        &file,
+    );
+    let mut outline: Vec<_> = items
+        .into_iter()
+        .map(|item| OutlineItem {
+            end_line: match item.kind {
+                ItemKind::Constant | ItemKind::Variable => item.line,
+                _ => block_ends.get(&item.line).copied().unwrap_or(item.line),
+            },
+            kind: item.kind,
+            name: item.name,
+            start_line: item.line,
+        })
+        .collect();
+    outline.sort_by_key(|item| item.start_line);
+    Ok(outline)
+}
+
+/**
+ * What to show while typing the arguments of a call.
+ */
+#[derive(Debug, PartialEq, Eq)]
+pub struct SignatureHelp {
+    pub signature: String,
+    pub parameters: Vec<String>,
+    /**
+     * The zero-based index of the parameter for the argument at the
+     * cursor.
+     */
+    pub active_parameter: usize,
+}
+
+/**
+ * Finds the call `f(...` enclosing `cursor` in `source`, the content of the
+ * root file being edited, and the signature of `f`. The source need not be
+ * complete, but `f` is looked up among the functions defined in the root
+ * file as read through `source_provider`.
+ */
+pub fn signature_help(
+    source: &str,
+    cursor: log::Index,
+    root_file_path: &Path,
+    source_provider: &dyn SourceProvider,
+    sink: &mut dyn log::DiagnosticSink,
+) -> Option<SignatureHelp> {
+    let call = ast::find_enclosing_call(&mut CharsPeekable::new(source), cursor).ok()??;
+    let modules = read_documentation(root_file_path, source_provider, sink).ok()?;
+    let function = modules[0]
+        .items
+        .iter()
+        .find(|item| item.kind == ItemKind::Function && item.name == call.name)?;
+    Some(SignatureHelp {
+        signature: function.signature.clone(),
+        parameters: function.parameters.clone(),
+        active_parameter: call.active_parameter,
+    })
+}
+
+/**
+ * Whether `name` matches `pattern` in the fuzzy way: the characters of
+ * `pattern` appear in `name` in the same order, ignoring case.
+ */
+pub fn fuzzy_match(pattern: &str, name: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    pattern
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|ch| name.any(|name_ch| name_ch == ch))
+}
+
+/**
+ * Writes the elements of `list` separated by commas and enclosed in
+ * `opening` and `closing`, if `list` is `Some`.
+ */
+fn write_list(
+    signature: &mut String,
+    opening: &str,
+    list: &Option<Vec<ast::ListElement>>,
+    closing: &str,
+    file: &log::File,
+) {
+    let Some(list) = list else {
+        return;
+    };
+    let elements: Vec<_> = list
+        .iter()
+        .filter_map(|element| match element {
+            ast::ListElement::NonEmpty(element) => Some(file.source(&element.pos)),
+            ast::ListElement::Empty { .. } => None,
+        })
+        .collect();
+    write!(signature, "{opening}{}{closing}", elements.join(", ")).unwrap();
+}
+
+/**
+ * Renders the documentation of a module as Markdown. `title` is used as the
+ * heading.
+ */
+pub fn render_markdown(module: &ModuleDocumentation, title: &str) -> String {
+    let mut page = format!("# {title}\n");
+    for item in &module.items {
+        write!(
+            page,
+            "\n## `{} {}`\n\n```\n{}\n```\n",
+            item.kind, item.name, item.signature
+        )
+        .unwrap();
+        if let Some(doc_comment) = &item.doc_comment {
+            write!(page, "\n{doc_comment}\n").unwrap();
+        }
+    }
+    page
+}
+
+/**
+ * Renders the documentation of a module as a standalone HTML page. `title`
+ * is used as the heading.
+ */
+pub fn render_html(module: &ModuleDocumentation, title: &str) -> String {
+    let title = escape_html(title);
+    let mut page = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n"
+    );
+    for item in &module.items {
+        write!(
+            page,
+            "<h2 id=\"{name}\"><code>{kind} {name}</code></h2>\n<pre><code>{signature}</code></pre>\n",
+            kind = item.kind,
+            name = escape_html(&item.name),
+            signature = escape_html(&item.signature),
+        )
+        .unwrap();
+        if let Some(doc_comment) = &item.doc_comment {
+            for paragraph in doc_comment.split("\n\n") {
+                writeln!(page, "<p>{}</p>", escape_html(paragraph)).unwrap();
+            }
+        }
+    }
+    page.push_str("</body>\n</html>\n");
+    page
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::new();
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,141 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/*!
+ * Global variables and functions provided by the application embedding the
+ * language.
+ */
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::{Item, Reader};
+use crate::{backend, log};
+
+/**
+ * Global variables and functions which the application embedding the
+ * language provides to scripts, visible in every file without imports. A
+ * dotted name such as `app.log` puts the item in the namespace `app`.
+ *
+ * The items are recorded in [`backend::Definitions::host_functions`] and
+ * [`backend::Definitions::host_variables`] in the order they are added.
+ */
+#[derive(Clone, Default)]
+pub struct HostItems {
+    variables: Vec<(String, backend::TyBuilder)>,
+    functions: Vec<(String, backend::FunctionTy)>,
+}
+
+impl HostItems {
+    pub fn new() -> Self {
+        HostItems::default()
+    }
+    pub fn variable(mut self, name: impl Into<String>, ty: backend::TyBuilder) -> Self {
+        self.variables.push((name.into(), ty));
+        self
+    }
+    /**
+     * Adding functions with the same name overloads them, like user-defined
+     * functions.
+     */
+    pub fn function(mut self, name: impl Into<String>, ty: backend::FunctionTy) -> Self {
+        self.functions.push((name.into(), ty));
+        self
+    }
+}
+
+impl Reader<'_, '_> {
+    /**
+     * Registers `host_items`, which [`Reader::read_content`] then adds to
+     * the items of every file.
+     */
+    pub(super) fn inject_host_items(&mut self, host_items: &HostItems) {
+        let mut items = HashMap::new();
+        for (name, ty) in &host_items.variables {
+            let index = self.definitions.host_variables.len();
+            self.definitions
+                .host_variables
+                .push((name.clone(), ty.clone()));
+            self.insert_host_item(&mut items, "", name, Item::HostVariable(index));
+        }
+        for (name, ty) in &host_items.functions {
+            let index = self.definitions.host_functions.len();
+            self.definitions
+                .host_functions
+                .push((name.clone(), ty.clone()));
+            let item = Item::Function(vec![backend::Function::Host(index)]);
+            self.insert_host_item(&mut items, "", name, item);
+        }
+        self.host_items = items;
+    }
+
+    /**
+     * Inserts `item` into `items` by `name`, which is relative to the
+     * namespace `prefix`.
+     */
+    fn insert_host_item(
+        &mut self,
+        items: &mut HashMap<String, Item>,
+        prefix: &str,
+        name: &str,
+        item: Item,
+    ) {
+        let Some((namespace, rest)) = name.split_once('.') else {
+            match (items.get_mut(name), item) {
+                (Some(Item::Function(functions)), Item::Function(new_functions)) => {
+                    functions.extend(new_functions);
+                }
+                (_, item) => {
+                    items.insert(name.to_owned(), item);
+                }
+            }
+            return;
+        };
+        let prefix = format!("{prefix}{namespace}.");
+        let index = match items.get(namespace) {
+            Some(&Item::Import(index)) => index,
+            _ => {
+                let index = self.new_namespace(&prefix[..prefix.len() - 1]);
+                items.insert(namespace.to_owned(), Item::Import(index));
+                index
+            }
+        };
+        let mut namespace_items = std::mem::take(&mut self.exported_items[index]);
+        self.insert_host_item(&mut namespace_items, &prefix, rest, item);
+        self.exported_items[index] = namespace_items;
+    }
+
+    /**
+     * Gives a namespace an index as if it were an imported file, so that
+     * `app.log` is resolved in the same way as an item of a file.
+     */
+    fn new_namespace(&mut self, qualified_name: &str) -> usize {
+        let index = self.file_indices.len();
+        let path = PathBuf::from(format!("<{qualified_name}>"));
+        self.file_indices.insert(path.clone(), index);
+        self.exported_items.push(HashMap::new());
+        self.global_statements.push(Vec::new());
+        self.files.push(log::File {
+            path,
+            content: String::new(),
+            lines: Vec::new(),
+            allowed_lints: Vec::new(),
+        });
+        index
+    }
+}
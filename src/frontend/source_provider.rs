@@ -0,0 +1,163 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/*!
+ * Defines [`SourceProvider`], through which the frontend reads source
+ * files, and its implementations.
+ */
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+/**
+ * Provides the content of source files to
+ * [`read_input_with`](super::read_input_with) and
+ * [`read_input_from_string`](super::read_input_from_string).
+ */
+pub trait SourceProvider {
+    /**
+     * Returns the canonical form of `path`, which identifies a file.
+     * Fails if there is no file at `path`.
+     */
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+    /**
+     * Reads the whole content of the file at the canonical `path`.
+     */
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+}
+
+/**
+ * A [`SourceProvider`] reading files from the file system, used by
+ * [`read_input`](super::read_input).
+ */
+pub struct FileSystem;
+
+impl SourceProvider for FileSystem {
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        path.canonicalize()
+    }
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let mut file = std::fs::File::open(path)?;
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        Ok(content)
+    }
+}
+
+/**
+ * A [`SourceProvider`] holding files in memory, for example unsaved
+ * buffers of an editor or inputs of tests.
+ *
+ * Paths are normalized lexically: `.` is removed and `..` removes the
+ * preceding component. Symbolic links do not exist here, and directories
+ * exist only as the ancestors of the files.
+ */
+#[derive(Default)]
+pub struct InMemory {
+    files: HashMap<PathBuf, String>,
+}
+
+impl InMemory {
+    /**
+     * Adds a file, returning the previous content if the file already
+     * existed.
+     */
+    pub fn insert(&mut self, path: impl AsRef<Path>, content: impl Into<String>) -> Option<String> {
+        self.files.insert(normalize(path.as_ref()), content.into())
+    }
+    /**
+     * Removes a file, returning its content if it existed.
+     */
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> Option<String> {
+        self.files.remove(&normalize(path.as_ref()))
+    }
+}
+
+impl SourceProvider for InMemory {
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = normalize(path);
+        // A directory exists if it contains a file.
+        if self
+            .files
+            .keys()
+            .any(|file_path| file_path.starts_with(&path))
+        {
+            Ok(path)
+        } else {
+            Err(not_found())
+        }
+    }
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.files.get(path).cloned().ok_or_else(not_found)
+    }
+}
+
+/**
+ * A [`SourceProvider`] remembering the results of
+ * [`SourceProvider::canonicalize`] of another one, so that a file imported
+ * from many files is canonicalized only once. Failures are not remembered.
+ */
+pub(super) struct CachedCanonicalization<'provider> {
+    source_provider: &'provider dyn SourceProvider,
+    canonical_paths: RefCell<HashMap<PathBuf, PathBuf>>,
+}
+
+impl<'provider> CachedCanonicalization<'provider> {
+    pub(super) fn new(source_provider: &'provider dyn SourceProvider) -> Self {
+        CachedCanonicalization {
+            source_provider,
+            canonical_paths: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl SourceProvider for CachedCanonicalization<'_> {
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        if let Some(canonical_path) = self.canonical_paths.borrow().get(path) {
+            return Ok(canonical_path.clone());
+        }
+        let canonical_path = self.source_provider.canonicalize(path)?;
+        self.canonical_paths
+            .borrow_mut()
+            .insert(path.to_path_buf(), canonical_path.clone());
+        Ok(canonical_path)
+    }
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.source_provider.read_to_string(path)
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn not_found() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "No such file in memory")
+}
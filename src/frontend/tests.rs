@@ -0,0 +1,1832 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/*!
+ * Golden-file tests for the frontend.
+ *
+ * Every `.sysc` file under the `tests/parse` and `tests/parse-errors`
+ * directories is parsed. If parsing succeeds, the AST is dumped and
+ * compared with the `.ast` file next to it; otherwise, the parse error is
+ * dumped and compared with the `.err` file.
+ *
+ * Every `.sysc` file directly in the `tests/diagnostics` directory is read
+ * as the root file, and the diagnostics are compared with the `.out` file
+ * next to it. Files in its subdirectories are only imported.
+ *
+ * Run the tests with the environment variable `BLESS` set to overwrite the
+ * expected files with the current output.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn golden_files() {
+    let bless = std::env::var_os("BLESS").is_some();
+    let tests_directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut inputs = Vec::new();
+    collect_inputs(&tests_directory.join("parse"), &mut inputs);
+    collect_inputs(&tests_directory.join("parse-errors"), &mut inputs);
+    inputs.sort();
+    assert!(!inputs.is_empty());
+
+    let mut mismatches = Vec::new();
+    for input in inputs {
+        let content = std::fs::read_to_string(&input).unwrap();
+        let mut chars_peekable = CharsPeekable::new(&content);
+        let (actual, expected_path, stale_path) = match ast::parse_file(&mut chars_peekable) {
+            Ok(ast) => (
+                format!("{ast:#?}\n"),
+                input.with_extension("ast"),
+                input.with_extension("err"),
+            ),
+            Err(err) => (
+                format!("{err:#?}\n"),
+                input.with_extension("err"),
+                input.with_extension("ast"),
+            ),
+        };
+        if bless {
+            std::fs::write(&expected_path, actual).unwrap();
+            if stale_path.exists() {
+                std::fs::remove_file(stale_path).unwrap();
+            }
+        } else if std::fs::read_to_string(&expected_path).ok().as_ref() != Some(&actual) {
+            mismatches.push(expected_path);
+        }
+    }
+    assert!(
+        mismatches.is_empty(),
+        "Output differs from (or is missing in) {mismatches:#?}. Rerun with `BLESS=1` to update \
+         them."
+    );
+}
+
+#[test]
+fn diagnostic_snapshots() {
+    let bless = std::env::var_os("BLESS").is_some();
+    let directory =
+        std::fs::canonicalize(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/diagnostics"))
+            .unwrap();
+    let mut inputs: Vec<_> = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "sysc")
+        })
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty());
+
+    let mut mismatches = Vec::new();
+    for input in inputs {
+        let mut sink = log::HumanReadable::new(Vec::new()).relative_to(&directory);
+        let _ = read_input_with(&input, &FileSystem, &mut sink);
+        let actual = String::from_utf8(sink.into_inner()).unwrap();
+        let expected_path = input.with_extension("out");
+        if bless {
+            std::fs::write(&expected_path, actual).unwrap();
+        } else if std::fs::read_to_string(&expected_path).ok().as_ref() != Some(&actual) {
+            mismatches.push(expected_path);
+        }
+    }
+    assert!(
+        mismatches.is_empty(),
+        "Output differs from (or is missing in) {mismatches:#?}. Rerun with `BLESS=1` to update \
+         them."
+    );
+}
+
+/**
+ * Collects `.sysc` files in `directory` and its subdirectories.
+ */
+fn collect_inputs(directory: &Path, inputs: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_inputs(&path, inputs);
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "sysc")
+        {
+            inputs.push(path);
+        }
+    }
+}
+
+/**
+ * Reads `source` as `main.sysc`, which must have no diagnostics.
+ */
+fn read(source: &str) -> Program {
+    let mut sink = log::Collector::default();
+    let program = read_input_from_string(
+        source,
+        Path::new("main.sysc"),
+        &InMemory::default(),
+        &mut sink,
+    )
+    .unwrap();
+    assert!(sink.diagnostics.is_empty(), "{:#?}", sink.diagnostics);
+    program
+}
+
+/**
+ * Reads `source` as `main.sysc`, which must fail, and returns the messages
+ * of the diagnostics.
+ */
+fn errors(source: &str) -> Vec<String> {
+    let mut sink = log::Collector::default();
+    assert!(read_input_from_string(
+        source,
+        Path::new("main.sysc"),
+        &InMemory::default(),
+        &mut sink,
+    )
+    .is_err());
+    sink.diagnostics
+        .into_iter()
+        .map(|(_, diagnostic)| diagnostic.message)
+        .collect()
+}
+
+#[test]
+fn read_from_string() {
+    let source = "
+    func add(x: int, y: int): int
+        x.add(y)
+    end
+    ";
+    let program = read_input_from_string(
+        source,
+        Path::new("main.sysc"),
+        &FileSystem,
+        &mut log::Collector::default(),
+    )
+    .unwrap();
+    assert_eq!(program.definitions.functions.len(), 1);
+}
+
+#[test]
+fn program() {
+    let mut files = InMemory::default();
+    files.insert("/project/lib.sysc", "func f()\nend\n");
+    files.insert(
+        "/project/main.sysc",
+        "import lib\nstruct S\nend\nvar x\nx\nfunc g()\nend\n",
+    );
+    let mut sink = log::Collector::default();
+    let program = read_input_with(Path::new("/project/main"), &files, &mut sink).unwrap();
+    let paths: Vec<_> = program.files.iter().map(|file| &file.path).collect();
+    assert_eq!(
+        paths,
+        [
+            Path::new("/project/lib.sysc"),
+            Path::new("/project/main.sysc")
+        ]
+    );
+    assert!(matches!(program.items[1].get("lib"), Some(Item::Import(0))));
+    assert!(matches!(
+        program.items[1].get("x"),
+        Some(Item::GlobalVariable(0))
+    ));
+    assert_eq!(program.global_statements[0].len(), 0);
+    assert_eq!(program.global_statements[1].len(), 1);
+    let positions: Vec<_> = program
+        .function_positions
+        .iter()
+        .map(|(file_index, pos)| (*file_index, format!("{pos}")))
+        .collect();
+    assert_eq!(
+        positions,
+        [(0, String::from("1:1-1:4")), (1, String::from("6:1-6:4"))]
+    );
+    assert_eq!(program.structure_positions[0].0, 1);
+}
+
+#[test]
+fn benchmarks() {
+    let source = r#"
+    test "check"
+    end
+    bench "loop"
+        var x
+    end
+    "#;
+    let program = read(source);
+    assert_eq!(program.definitions.tests.len(), 1);
+    let [benchmark] = &program.definitions.benchmarks[..] else {
+        panic!();
+    };
+    assert_eq!(benchmark.name, "loop");
+    assert_eq!(benchmark.definition.num_local_variables, 1);
+
+    assert_eq!(
+        errors("bench\nend\n"),
+        ["Missing benchmark name after `bench` at 1:1-1:5."]
+    );
+}
+
+#[test]
+fn assert_statement() {
+    let source = r#"
+    test "assertion"
+        var x
+        assert x, "x holds"
+    end
+    "#;
+    let program = read(source);
+    let [test] = &program.definitions.tests[..] else {
+        panic!();
+    };
+    let [backend::Statement::Assert {
+        condition_source,
+        message,
+        ..
+    }] = &test.definition.body[..]
+    else {
+        panic!();
+    };
+    assert_eq!(condition_source, "x");
+    assert_eq!(message.as_deref(), Some("x holds"));
+}
+
+#[test]
+fn constants() {
+    let mut files = InMemory::default();
+    files.insert("/project/lib.sysc", "const N = 42\n");
+    files.insert(
+        "/project/main.sysc",
+        r#"
+        import lib
+        const A = 1 + 2 * 3
+        const B = (A << 2) - -1
+        const NAME = "sys" + "craws"
+        const P = 2 ** 3 ** 2
+        const M = (~0 << 63 >> 62 ^ 5) & -4 | 1
+        const LINE = "=" + "-" * 3 + "="
+        const C = B == 29 && NAME == "syscraws" && lib.N / 2 > 20 && P == 512 && M == -7 && LINE == "=---="
+        test "constants"
+            assert C
+        end
+        "#,
+    );
+    let mut sink = log::Collector::default();
+    let program = read_input_with(Path::new("/project/main"), &files, &mut sink).unwrap();
+    assert!(sink.diagnostics.is_empty());
+    let [backend::Statement::Assert {
+        condition: backend::Expression::Constant(value),
+        ..
+    }] = &program.definitions.tests[0].definition.body[..]
+    else {
+        panic!();
+    };
+    assert_eq!(*value, backend::Constant::Boolean(true));
+}
+
+#[test]
+fn constant_conditions() {
+    let source = "
+    const DEBUG = 1 == 0
+    const VERBOSE = DEBUG || 1 == 1
+    func f()
+        while DEBUG
+            _ = DEBUG
+        end
+        while VERBOSE
+            while DEBUG
+            end
+        end
+    end
+    while DEBUG
+    end
+    ";
+    let program = read(source);
+    assert!(program.global_statements[0].is_empty());
+    let [backend::Statement::While(backend::Expression::Constant(condition), body)] =
+        &program.definitions.functions[0].1.body[..]
+    else {
+        panic!();
+    };
+    assert_eq!(*condition, backend::Constant::Boolean(true));
+    assert!(body.is_empty());
+}
+
+#[test]
+fn if_statements() {
+    let source = "
+    const DEBUG = 1 == 0
+    func f[T](x: T, y: T)
+        if x
+            var z = y
+        else if y
+            var z = x
+        else
+            _ = x
+        end
+        if DEBUG
+            _ = x
+        end
+        if y
+            _ = x
+        end
+    end
+    if DEBUG
+        _ = DEBUG
+    else
+        var w
+    end
+    ";
+    let program = read(source);
+    // Only the `else` branch of `if DEBUG` is left.
+    assert!(program.global_statements[0].is_empty());
+    let body = &program.definitions.functions[0].1.body;
+    let [backend::Statement::If(_, then_body, else_body), backend::Statement::If(_, _, _)] =
+        &body[..]
+    else {
+        panic!();
+    };
+    // Each branch declares its own `z`.
+    let [backend::Statement::Expr(backend::Expression::Function { calls, .. })] = &then_body[..]
+    else {
+        panic!();
+    };
+    assert!(matches!(
+        calls[0].arguments[0],
+        backend::Expression::LocalVariable(2)
+    ));
+    let [backend::Statement::If(_, else_if_body, else_else_body)] = &else_body[..] else {
+        panic!();
+    };
+    let [backend::Statement::Expr(backend::Expression::Function { calls, .. })] = &else_if_body[..]
+    else {
+        panic!();
+    };
+    assert!(matches!(
+        calls[0].arguments[0],
+        backend::Expression::LocalVariable(3)
+    ));
+    assert_eq!(else_else_body.len(), 1);
+}
+
+#[test]
+fn missing_conditions() {
+    let source = "
+    while
+    end
+    if
+    end
+    ";
+    assert_eq!(
+        errors(source),
+        [
+            "Missing condition after `while` at 2:5-2:9.",
+            "Missing condition after `if` at 4:5-4:6.",
+        ]
+    );
+}
+
+#[test]
+fn break_and_continue() {
+    let source = "
+    func f[T](x: T)
+        while x
+            if x
+                break
+            end
+            continue
+        end
+        if x
+            break
+        end
+    end
+    continue
+    ";
+    assert_eq!(
+        errors(source),
+        [
+            "`break` outside of a loop at 10:13-10:17.",
+            "`continue` outside of a loop at 13:5-13:12."
+        ]
+    );
+}
+
+#[test]
+fn return_statements() {
+    let source = "
+    func f[T](x: T)
+        while x
+            return x
+        end
+        return
+    end
+    test \"t\"
+        return
+    end
+    return
+    ";
+    assert_eq!(
+        errors(source),
+        ["`return` outside of a function at 11:5-11:10."]
+    );
+
+    // A test returns early, like a function.
+    let source = "
+    test \"t\"
+        var x
+        if x
+            return
+        end
+    end
+    ";
+    let program = read(source);
+    let [backend::Statement::If(_, then, _)] = &program.definitions.tests[0].definition.body[..]
+    else {
+        panic!();
+    };
+    assert!(matches!(then[..], [backend::Statement::Return(None)]));
+}
+
+#[test]
+fn function_calls() {
+    let source = "
+    const N = 1 == 1
+    func f[T](x: T)
+    end
+    func f[T](x: T, y: T)
+    end
+    func g[T](x: T)
+        f(x, x)
+        N(x)
+    end
+    ";
+    assert_eq!(
+        errors(source),
+        ["Calling a value which is not a function at 9:9-9:9."]
+    );
+
+    let source = "
+    func f[T](x: T)
+    end
+    func f[T](x: T, y: T)
+    end
+    func g[T](x: T)
+        f(x, x)
+    end
+    ";
+    let program = read(source);
+    let [backend::Statement::Expr(backend::Expression::Function { candidates, calls })] =
+        &program.definitions.functions[2].1.body[..]
+    else {
+        panic!();
+    };
+    // Both overloads are candidates, called once with the two arguments.
+    assert!(
+        candidates
+            == &[
+                backend::Function::UserDefined(0),
+                backend::Function::UserDefined(1)
+            ]
+    );
+    let [backend::Call { arguments }] = &calls[..] else {
+        panic!();
+    };
+    assert_eq!(arguments.len(), 2);
+}
+
+#[test]
+fn imported_function_calls() {
+    let mut files = InMemory::default();
+    files.insert("/project/lib.sysc", "func double(x: int): int\nend\n");
+    files.insert(
+        "/project/main.sysc",
+        "import lib\nfunc f(x: int)\n    lib.double(x)\nend\n",
+    );
+    let mut sink = log::Collector::default();
+    let program = read_input_with(Path::new("/project/main"), &files, &mut sink).unwrap();
+    assert!(sink.diagnostics.is_empty(), "{:#?}", sink.diagnostics);
+    // `lib.sysc` is read first, so `double` is the first function.
+    let [backend::Statement::Expr(backend::Expression::Function { candidates, calls })] =
+        &program.definitions.functions[1].1.body[..]
+    else {
+        panic!();
+    };
+    assert!(candidates == &[backend::Function::UserDefined(0)]);
+    let [backend::Call { arguments }] = &calls[..] else {
+        panic!();
+    };
+    assert_eq!(arguments.len(), 1);
+}
+
+#[test]
+fn numeric_literals() {
+    let source = "
+    var x = 42
+    var y = 0x10
+    var z = 1.5
+    var w = 1e5
+    ";
+    let program = read(source);
+    let values: Vec<_> = program.global_statements[0]
+        .iter()
+        .map(|statement| {
+            let backend::Statement::Expr(backend::Expression::Function { calls, .. }) = statement
+            else {
+                panic!();
+            };
+            let backend::Expression::Constant(value) = &calls[0].arguments[1] else {
+                panic!();
+            };
+            value.clone()
+        })
+        .collect();
+    assert_eq!(
+        values,
+        [
+            backend::Constant::Integer(42),
+            backend::Constant::Integer(16),
+            backend::Constant::Float(1.5),
+            backend::Constant::Float(1e5)
+        ]
+    );
+
+    let source = "
+    var x = 9223372036854775808
+    var y = 0b2
+    var z = 12abc
+    ";
+    assert_eq!(
+        errors(source),
+        [
+            "Overflow in an integer literal at 2:13-2:31.",
+            "Invalid digit `2` in a binary literal at 3:13-3:15.",
+            "Invalid numeric literal at 4:13-4:17."
+        ]
+    );
+}
+
+#[test]
+fn string_literals() {
+    let source = r#"
+    var x = 7
+    var s = "plain"
+    var t = "x = ${x:04}."
+    "#;
+    let program = read(source);
+    let [_, backend::Statement::Expr(init_s), backend::Statement::Expr(init_t)] =
+        &program.global_statements[0][..]
+    else {
+        panic!();
+    };
+    let backend::Expression::Function { calls, .. } = init_s else {
+        panic!();
+    };
+    assert!(matches!(
+        &calls[0].arguments[1],
+        backend::Expression::Constant(backend::Constant::String(s)) if s == "plain"
+    ));
+    let backend::Expression::Function { calls, .. } = init_t else {
+        panic!();
+    };
+    let backend::Expression::Interpolation(pieces) = &calls[0].arguments[1] else {
+        panic!();
+    };
+    let [backend::InterpolationPiece::String(before), backend::InterpolationPiece::Value { spec, .. }, backend::InterpolationPiece::String(after)] =
+        &pieces[..]
+    else {
+        panic!();
+    };
+    assert_eq!(before, "x = ");
+    assert_eq!(spec.format_integer(7), "0007");
+    assert_eq!(after, ".");
+
+    let source = r#"
+    var s = "${}"
+    "#;
+    assert_eq!(
+        errors(source),
+        ["Missing value in a placeholder in the string literal at 2:13-2:17."]
+    );
+}
+
+#[test]
+fn structure_constructors() {
+    let source = "
+    struct Point
+        x: float
+        y: float
+    end
+    enum Shape
+        empty
+    end
+    func f[T](x: T)
+        Point(x, x)
+        Shape
+    end
+    ";
+    assert_eq!(errors(source), ["Type at 11:9-11:13 is used as a value."]);
+
+    let source = "
+    struct Point
+        x: float
+        y: float
+    end
+    func f[T](x: T)
+        Point(x, x)
+    end
+    ";
+    let program = read(source);
+    let [backend::Statement::Expr(backend::Expression::Function { candidates, calls })] =
+        &program.definitions.functions[0].1.body[..]
+    else {
+        panic!();
+    };
+    assert!(candidates == &[backend::Function::Construct { structure_index: 0 }]);
+    assert_eq!(calls[0].arguments.len(), 2);
+}
+
+#[test]
+fn field_accesses() {
+    let source = "
+    struct Point
+        x: float
+        y: float
+    end
+    struct Size
+        y: int
+    end
+    func f(p: Point)
+        p.x = p.y
+        p.w = p.y
+    end
+    ";
+    assert_eq!(
+        errors(source),
+        ["No field `w` is defined, used at 11:9-11:11."]
+    );
+
+    let source = "
+    struct Point
+        x: float
+        y: float
+    end
+    struct Size
+        y: int
+    end
+    func f(p: Point)
+        p.x = p.y
+    end
+    ";
+    let program = read(source);
+    let [backend::Statement::Expr(backend::Expression::Function { candidates, calls })] =
+        &program.definitions.functions[0].1.body[..]
+    else {
+        panic!();
+    };
+    assert!(candidates == &[backend::Function::Assign]);
+    let [backend::Expression::Function {
+        candidates: target_candidates,
+        ..
+    }, backend::Expression::Function {
+        candidates: value_candidates,
+        calls: value_calls,
+    }] = &calls[0].arguments[..]
+    else {
+        panic!();
+    };
+    assert!(
+        target_candidates
+            == &[backend::Function::FieldRef {
+                structure_index: 0,
+                field_index: 0,
+            }]
+    );
+    // Every structure with the field `y` is a candidate.
+    assert!(
+        value_candidates
+            == &[
+                backend::Function::Field {
+                    structure_index: 0,
+                    field_index: 1,
+                },
+                backend::Function::Field {
+                    structure_index: 1,
+                    field_index: 0,
+                },
+            ]
+    );
+    assert_eq!(value_calls[0].arguments.len(), 1);
+}
+
+#[test]
+fn invalid_constants() {
+    let source = r#"
+    const A = 1 / 0
+    const B = 1 + 2.5
+    const C = 9223372036854775807 + 1
+    const D = x
+    const E = 1
+    const E = 2
+    const F = 2 ** -1
+    const G = 2 ** 64
+    const H = 1 << 64
+    const I = 1 >> -1
+    const J = "-" * -1
+    const K = 2 * "-"
+    const L = "ab" * 1000000000000000
+    const M = "ab" * 400000 + "ab" * 400000
+    "#;
+    assert_eq!(
+        errors(source),
+        [
+            "Division by zero in a constant at 2:15-2:19.",
+            "Invalid operands of `add` in a constant at 3:15-3:21.",
+            "Overflow in a constant at 4:15-4:37.",
+            "`x` at 5:15-5:15 is not a constant.",
+            "Duplicate definition of `E`.",
+            "Negative exponent in a constant at 8:15-8:21.",
+            "Overflow in a constant at 9:15-9:21.",
+            "Shift amount out of range in a constant at 10:15-10:21.",
+            "Shift amount out of range in a constant at 11:15-11:21.",
+            "Negative repetition count in a constant at 12:15-12:22.",
+            "Invalid operands of `mul` in a constant at 13:15-13:21.",
+            "String too long in a constant at 14:15-14:37.",
+            "String too long in a constant at 15:15-15:43.",
+        ]
+    );
+}
+
+#[test]
+fn prefixed_integer_literals() {
+    let source = "
+    const A = 0xff + 0o17 + 0b1010_1010
+    const B = 0x7FFF_FFFF_FFFF_FFFF
+    const C = 0b102
+    const D = 0xg
+    const E = 0o
+    const F = 0x8000_0000_0000_0000
+    ";
+    assert_eq!(
+        errors(source),
+        [
+            "Invalid digit `2` in a binary literal at 4:15-4:19.",
+            "Invalid digit `g` in a hexadecimal literal at 5:15-5:17.",
+            "Missing digits in an octal literal at 6:15-6:16.",
+            "Overflow in a hexadecimal literal at 7:15-7:35.",
+        ]
+    );
+}
+
+#[test]
+fn enumerations() {
+    let source = "
+    enum Shape
+        circle: float
+        square: float
+        empty
+    end
+    enum Option[T]
+        some: T
+        none
+    end
+    ";
+    let program = read(source);
+    let [shape, option] = &program.definitions.enumerations[..] else {
+        panic!();
+    };
+    assert_eq!(shape.num_ty_parameters, 0);
+    let variants: Vec<_> = shape
+        .variants
+        .iter()
+        .map(|variant| (variant.name.as_str(), variant.payload_ty.is_some()))
+        .collect();
+    assert_eq!(
+        variants,
+        [("circle", true), ("square", true), ("empty", false)]
+    );
+    assert_eq!(option.num_ty_parameters, 1);
+    assert!(matches!(
+        option.variants[0].payload_ty,
+        Some(backend::TyBuilder::Parameter(0))
+    ));
+}
+
+#[test]
+fn invalid_enumerations() {
+    let source = "
+    enum Shape
+        circle: float
+        circle
+        1
+        square: Unknown
+    end
+    enum Shape
+    end
+    ";
+    assert_eq!(
+        errors(source),
+        [
+            "Duplicate definition of `Shape`.",
+            "Duplicate variant `circle`.",
+            "Invalid enumeration variant at 5:9-5:9.",
+            "Undefined name `Unknown` at 6:17-6:23.",
+        ]
+    );
+}
+
+#[test]
+fn operators_and_methods() {
+    let source = "
+    struct Vector
+        x: float
+        y: float
+    end
+    method add(a: Vector, b: Vector): Vector
+    end
+    test \"operators\"
+        var u
+        var v
+        _ = u + v
+        u.add(v)
+    end
+    ";
+    let program = read(source);
+    for statement in &program.definitions.tests[0].definition.body {
+        let backend::Statement::Expr(backend::Expression::Function { candidates, calls }) =
+            statement
+        else {
+            panic!();
+        };
+        assert!(
+            candidates
+                == &[
+                    backend::Function::IAdd,
+                    backend::Function::FAdd,
+                    backend::Function::SConcat,
+                    backend::Function::UserDefined(0)
+                ]
+        );
+        assert_eq!(calls[0].arguments.len(), 2);
+    }
+}
+
+#[test]
+fn builtin_operators() {
+    let source = "
+    test \"operators\"
+        var u
+        var v
+        _ = u - v
+        _ = u * v
+        _ = u / v
+        _ = u % v
+        _ = u == v
+        _ = u != v
+        _ = u < v
+        _ = u <= v
+        _ = u > v
+        _ = u >= v
+        _ = -u
+        _ = !u
+    end
+    ";
+    let program = read(source);
+    let candidates: Vec<_> = program.definitions.tests[0]
+        .definition
+        .body
+        .iter()
+        .map(|statement| {
+            // `_ = value` is translated as `value`.
+            let backend::Statement::Expr(backend::Expression::Function { candidates, .. }) =
+                statement
+            else {
+                panic!();
+            };
+            candidates.clone()
+        })
+        .collect();
+    assert_eq!(candidates.len(), 12);
+    // Arithmetic operators.
+    assert!(candidates[0] == [backend::Function::ISub, backend::Function::FSub]);
+    // `*` also repeats a string.
+    assert!(
+        candidates[1]
+            == [
+                backend::Function::IMul,
+                backend::Function::FMul,
+                backend::Function::SRepeat
+            ]
+    );
+    assert!(candidates[2] == [backend::Function::IDiv, backend::Function::FDiv]);
+    assert!(candidates[3] == [backend::Function::IRem, backend::Function::FRem]);
+    // Equality operators.
+    assert!(candidates[4] == [backend::Function::IEqual, backend::Function::FEqual]);
+    assert!(candidates[5] == [backend::Function::INotEqual, backend::Function::FNotEqual]);
+    // Inequality operators.
+    assert!(candidates[6] == [backend::Function::ILess, backend::Function::FLess]);
+    assert!(
+        candidates[7]
+            == [
+                backend::Function::ILessOrEqual,
+                backend::Function::FLessOrEqual
+            ]
+    );
+    assert!(candidates[8] == [backend::Function::IGreater, backend::Function::FGreater]);
+    assert!(
+        candidates[9]
+            == [
+                backend::Function::IGreaterOrEqual,
+                backend::Function::FGreaterOrEqual
+            ]
+    );
+    // Prefix operators.
+    assert!(candidates[10] == [backend::Function::INeg, backend::Function::FNeg]);
+    assert!(candidates[11] == [backend::Function::LogicalNot]);
+}
+
+#[test]
+fn membership() {
+    let source = "
+    struct Set
+    end
+    method contains(s: Set, x: int): int
+    end
+    test \"membership\"
+        var x
+        var s
+        _ = x in s
+    end
+    ";
+    let program = read(source);
+    let [backend::Statement::Expr(backend::Expression::Function { candidates, calls })] =
+        &program.definitions.tests[0].definition.body[..]
+    else {
+        panic!();
+    };
+    assert!(
+        candidates
+            == &[
+                backend::Function::SContains,
+                backend::Function::UserDefined(0)
+            ]
+    );
+    // The set is the receiver.
+    let variables: Vec<_> = calls[0]
+        .arguments
+        .iter()
+        .map(|argument| {
+            let backend::Expression::Function { calls, .. } = argument else {
+                panic!();
+            };
+            let [backend::Expression::LocalVariable(index)] = calls[0].arguments[..] else {
+                panic!();
+            };
+            index
+        })
+        .collect();
+    assert_eq!(variables, [1, 0]);
+}
+
+#[test]
+fn string_membership() {
+    // `in` is still usable as a name.
+    let source = "
+    test \"membership\"
+        var in = \"b\"
+        var s = \"abc\"
+        _ = in in s
+        _ = \"b\" in s
+    end
+    ";
+    let program = read(source);
+    let [.., backend::Statement::Expr(variable), backend::Statement::Expr(literal)] =
+        &program.definitions.tests[0].definition.body[..]
+    else {
+        panic!();
+    };
+    for expression in [variable, literal] {
+        let backend::Expression::Function { candidates, calls } = expression else {
+            panic!();
+        };
+        assert!(candidates == &[backend::Function::SContains]);
+        // The string searched is the receiver.
+        let backend::Expression::Function { calls, .. } = &calls[0].arguments[0] else {
+            panic!();
+        };
+        assert!(matches!(
+            calls[0].arguments[..],
+            [backend::Expression::LocalVariable(1)]
+        ));
+    }
+}
+
+#[test]
+fn assignments() {
+    let source = "
+    var x
+    var y
+    x = y
+    x += y
+    x -= y
+    _ = y
+    y.z = x
+    x, y = y, x, y
+    x, , y = x, y, x
+    ";
+    assert_eq!(
+        errors(source),
+        [
+            "No field `z` is defined, used at 8:5-8:7.",
+            "Expected 2 values at 9:12-9:18, found 3.",
+            "Missing target next to `,` at 10:6-10:6.",
+        ]
+    );
+    let source = "
+    var x
+    var y
+    x = y
+    x += y
+    x, y = y, x
+    ";
+    let program = read(source);
+    let [backend::Statement::Expr(assign), backend::Statement::Expr(add_assign), backend::Statement::Expr(swap)] =
+        &program.global_statements[0][..]
+    else {
+        panic!();
+    };
+    let backend::Expression::Function { candidates, calls } = assign else {
+        panic!();
+    };
+    assert!(candidates == &[backend::Function::Assign]);
+    assert!(matches!(
+        calls[0].arguments[..],
+        [backend::Expression::GlobalVariable(0), _]
+    ));
+    // The target is evaluated once, and read through the reference.
+    let backend::Expression::CompoundAssignment {
+        target,
+        operator,
+        value: _,
+    } = add_assign
+    else {
+        panic!();
+    };
+    assert!(matches!(**target, backend::Expression::GlobalVariable(0)));
+    assert!(
+        operator
+            == &[
+                backend::Function::IAdd,
+                backend::Function::FAdd,
+                backend::Function::SConcat
+            ]
+    );
+    let backend::Expression::MultipleAssignment { targets, values } = swap else {
+        panic!();
+    };
+    assert!(matches!(
+        targets[..],
+        [
+            backend::Expression::GlobalVariable(0),
+            backend::Expression::GlobalVariable(1)
+        ]
+    ));
+    assert_eq!(values.len(), 2);
+}
+
+#[test]
+fn compound_assignment_to_field() {
+    let source = "
+    struct P
+        x: int
+    end
+    test \"scale\"
+        var p
+        p.x *= 2
+    end
+    ";
+    let program = read(source);
+    let [backend::Statement::Expr(backend::Expression::CompoundAssignment {
+        target,
+        operator,
+        value,
+    })] = &program.definitions.tests[0].definition.body[..]
+    else {
+        panic!();
+    };
+    // `p.x` is evaluated once, as a reference to the field, and is not read
+    // again on the right-hand side.
+    let backend::Expression::Function { candidates, calls } = &**target else {
+        panic!();
+    };
+    assert!(
+        candidates
+            == &[backend::Function::FieldRef {
+                structure_index: 0,
+                field_index: 0
+            }]
+    );
+    assert!(matches!(
+        calls[..],
+        [backend::Call { ref arguments }]
+            if matches!(arguments[..], [backend::Expression::LocalVariable(0)])
+    ));
+    assert!(
+        operator
+            == &[
+                backend::Function::IMul,
+                backend::Function::FMul,
+                backend::Function::SRepeat
+            ]
+    );
+    assert!(matches!(
+        **value,
+        backend::Expression::Constant(backend::Constant::Integer(2))
+    ));
+}
+
+#[test]
+fn variable_initializers() {
+    let source = "
+    var w
+    var x = w
+    var y, z = x, x
+    func f[T](t: T)
+        var q, r = t
+        var s = q.add(r)
+    end
+    ";
+    let program = read(source);
+    let [backend::Statement::Expr(init_x), backend::Statement::Expr(init_yz)] =
+        &program.global_statements[0][..]
+    else {
+        panic!();
+    };
+    let backend::Expression::Function { candidates, calls } = init_x else {
+        panic!();
+    };
+    assert!(candidates == &[backend::Function::Assign]);
+    assert!(matches!(
+        calls[0].arguments[..],
+        [backend::Expression::GlobalVariable(1), _]
+    ));
+    let backend::Expression::MultipleAssignment { targets, values } = init_yz else {
+        panic!();
+    };
+    assert!(matches!(
+        targets[..],
+        [
+            backend::Expression::GlobalVariable(2),
+            backend::Expression::GlobalVariable(3)
+        ]
+    ));
+    assert_eq!(values.len(), 2);
+    let [backend::Statement::Expr(init_qr), backend::Statement::Expr(_)] =
+        &program.definitions.functions[0].1.body[..]
+    else {
+        panic!();
+    };
+    // The tuple is destructured without being stored anywhere.
+    let backend::Expression::Destructuring { targets, value } = init_qr else {
+        panic!();
+    };
+    assert!(matches!(
+        targets[..],
+        [
+            backend::Expression::LocalVariable(1),
+            backend::Expression::LocalVariable(2)
+        ]
+    ));
+    let backend::Expression::Function { candidates, calls } = &**value else {
+        panic!();
+    };
+    assert!(candidates == &[backend::Function::Deref]);
+    assert!(matches!(
+        calls[0].arguments[..],
+        [backend::Expression::LocalVariable(0)]
+    ));
+
+    let source = "
+    var c
+    var d, e = c, c, c
+    var f, g.h = c
+    var i =
+    ";
+    assert_eq!(
+        errors(source),
+        [
+            "Expected 2 values at 3:16-3:22, found 3.",
+            "Expected a variable name at 4:12-4:14.",
+            "Missing right-hand side of `assign` at 5:11-5:11.",
+        ]
+    );
+}
+
+#[test]
+fn undefined_operator() {
+    let source = "
+    test \"operators\"
+        var u
+        /u
+    end
+    ";
+    assert_eq!(
+        errors(source),
+        ["No method `reciprocal` is defined, used at 4:9-4:9."]
+    );
+}
+
+#[test]
+fn undefined_names() {
+    let source = "
+    var y = undefined_name
+    nosuch(1)
+    func f(x: undefined_ty)
+    end
+    var z = 1
+    ";
+    assert_eq!(
+        errors(source),
+        [
+            "Undefined name `undefined_name` at 2:13-2:26.",
+            "Undefined name `nosuch` at 3:5-3:10.",
+            "Undefined name `undefined_ty` at 4:15-4:26.",
+        ]
+    );
+}
+
+#[test]
+fn unsupported_expressions() {
+    let source = "
+    var a = 1
+    var x = (a + 2)
+    var b = a && a
+    var c = a || a
+    var t = (1, 2)
+    var e = t.0
+    var y = 1 : int
+    ";
+    assert_eq!(
+        errors(source),
+        [
+            "`&&` at 4:13-4:18 is not supported yet.",
+            "`||` at 5:13-5:18 is not supported yet.",
+            "Tuple at 6:13-6:18 is not supported yet.",
+            "Tuple element access at 7:13-7:15 is not supported yet.",
+            "Type annotation at 8:13-8:19 is not supported yet.",
+        ]
+    );
+}
+
+#[test]
+fn imported_global_variables() {
+    let mut files = InMemory::default();
+    files.insert("/project/lib.sysc", "var v = 1\n");
+    files.insert("/project/main.sysc", "import lib\nvar x = (lib.v)\n");
+    let mut sink = log::Collector::default();
+    let program = read_input_with(Path::new("/project/main"), &files, &mut sink).unwrap();
+    assert!(sink.diagnostics.is_empty());
+    // `lib.sysc` is finished first.
+    let Some(&Item::GlobalVariable(index)) = program.items[0].get("v") else {
+        panic!();
+    };
+    let Some(&Item::GlobalVariable(x_index)) = program.items[1].get("x") else {
+        panic!();
+    };
+    assert_ne!(index, x_index);
+    assert_eq!(program.definitions.num_global_variables, 2);
+    let [backend::Statement::Expr(backend::Expression::Function { calls, .. })] =
+        &program.global_statements[1][..]
+    else {
+        panic!();
+    };
+    let backend::Expression::Function { candidates, calls } = &calls[0].arguments[1] else {
+        panic!();
+    };
+    assert!(candidates == &[backend::Function::Deref]);
+    let [backend::Expression::GlobalVariable(value_index)] = calls[0].arguments[..] else {
+        panic!();
+    };
+    assert_eq!(value_index, index);
+}
+
+#[test]
+fn documentation() {
+    let mut files = InMemory::default();
+    files.insert(
+        "/project/main.sysc",
+        "
+        import util(\"sub/util\")
+        --- A point
+        ---in 2D.
+        struct Point[T]
+            x: T
+        end
+        -- Not a doc comment.
+        func f()
+        end
+        --- Adds.
+        method add(a: Point[int], b: Point[int]): Point[int]
+            a
+        end
+        var lo, hi = f, f
+        ",
+    );
+    files.insert(
+        "/project/sub/util.sysc",
+        "import main(\"../main\")\n--- The answer.\nconst N = 42\n",
+    );
+    let modules = read_documentation(
+        Path::new("/project/main"),
+        &files,
+        &mut log::Collector::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        modules,
+        [
+            ModuleDocumentation {
+                path: PathBuf::from("/project/main.sysc"),
+                items: vec![
+                    DocumentedItem {
+                        kind: ItemKind::Structure,
+                        name: String::from("Point"),
+                        signature: String::from("struct Point[T]\n    x: T\nend"),
+                        parameters: Vec::new(),
+                        doc_comment: Some(String::from("A point\nin 2D.")),
+                        line: 4,
+                    },
+                    DocumentedItem {
+                        kind: ItemKind::Function,
+                        name: String::from("f"),
+                        signature: String::from("func f()"),
+                        parameters: Vec::new(),
+                        doc_comment: None,
+                        line: 8,
+                    },
+                    DocumentedItem {
+                        kind: ItemKind::Method,
+                        name: String::from("add"),
+                        signature: String::from(
+                            "method add(a: Point[int], b: Point[int]): Point[int]"
+                        ),
+                        parameters: vec![
+                            String::from("a: Point[int]"),
+                            String::from("b: Point[int]")
+                        ],
+                        doc_comment: Some(String::from("Adds.")),
+                        line: 11,
+                    },
+                    DocumentedItem {
+                        kind: ItemKind::Variable,
+                        name: String::from("lo"),
+                        signature: String::from("var lo"),
+                        parameters: Vec::new(),
+                        doc_comment: None,
+                        line: 14,
+                    },
+                    DocumentedItem {
+                        kind: ItemKind::Variable,
+                        name: String::from("hi"),
+                        signature: String::from("var hi"),
+                        parameters: Vec::new(),
+                        doc_comment: None,
+                        line: 14,
+                    },
+                ],
+            },
+            ModuleDocumentation {
+                path: PathBuf::from("/project/sub/util.sysc"),
+                items: vec![DocumentedItem {
+                    kind: ItemKind::Constant,
+                    name: String::from("N"),
+                    signature: String::from("const N = 42"),
+                    parameters: Vec::new(),
+                    doc_comment: Some(String::from("The answer.")),
+                    line: 2,
+                }],
+            },
+        ]
+    );
+    assert_eq!(
+        render_markdown(&modules[1], "sub/util"),
+        "# sub/util\n\n## `const N`\n\n```\nconst N = 42\n```\n\nThe answer.\n"
+    );
+    assert!(render_html(&modules[0], "main")
+        .contains("<pre><code>method add(a: Point[int], b: Point[int]): Point[int]</code></pre>"));
+}
+
+#[test]
+fn signature_help_in_call() {
+    let mut files = InMemory::default();
+    files.insert("/project/main.sysc", "func add(x: int, y: int): int\nend\n");
+    let help = |source, cursor| {
+        signature_help(
+            source,
+            cursor,
+            Path::new("/project/main"),
+            &files,
+            &mut log::Collector::default(),
+        )
+    };
+    // The edited source is incomplete.
+    assert_eq!(
+        help("add(1, ", log::Index { line: 0, column: 7 }),
+        Some(SignatureHelp {
+            signature: String::from("func add(x: int, y: int): int"),
+            parameters: vec![String::from("x: int"), String::from("y: int")],
+            active_parameter: 1,
+        })
+    );
+    assert_eq!(help("sub(1, ", log::Index { line: 0, column: 7 }), None);
+}
+
+#[test]
+fn folding_and_outline() {
+    let source = "\
+struct S
+    x: int
+end
+func f()
+    while x
+        x
+    end
+end
+const N = 1
+test \"t\"
+end
+";
+    assert_eq!(
+        folding_ranges(source).unwrap(),
+        [(0, 2), (4, 6), (3, 7), (9, 10)]
+    );
+    assert_eq!(
+        outline(source).unwrap(),
+        [
+            OutlineItem {
+                kind: ItemKind::Structure,
+                name: String::from("S"),
+                start_line: 0,
+                end_line: 2,
+            },
+            OutlineItem {
+                kind: ItemKind::Function,
+                name: String::from("f"),
+                start_line: 3,
+                end_line: 7,
+            },
+            OutlineItem {
+                kind: ItemKind::Constant,
+                name: String::from("N"),
+                start_line: 8,
+                end_line: 8,
+            },
+        ]
+    );
+}
+
+#[test]
+fn go_to_definition() {
+    let mut files = InMemory::default();
+    files.insert(
+        "/project/util.sysc",
+        "struct S\nend\n-- The answer.\nconst N = 42\nvar v\n",
+    );
+    files.insert(
+        "/project/main.sysc",
+        "import util\nconst M = util.N\nconst L = M\n",
+    );
+    let mut sink = log::Collector::default();
+    let program = read_input_with(Path::new("/project/main"), &files, &mut sink).unwrap();
+    let definition = |line, column| {
+        find_definition(&program, 1, log::Index { line, column })
+            .map(|(file_index, pos)| (file_index, pos.to_string()))
+    };
+    assert_eq!(definition(1, 15), Some((0, String::from("4:1-4:5"))));
+    // Right after the name.
+    assert_eq!(definition(2, 11), Some((1, String::from("2:1-2:5"))));
+    // `util` itself and `=` are not items.
+    assert_eq!(definition(1, 11), None);
+    assert_eq!(definition(1, 8), None);
+    assert_eq!(program.item_positions[0]["v"].to_string(), "5:5-5:5");
+    assert_eq!(program.item_positions[0]["S"].to_string(), "1:1-1:6");
+}
+
+#[test]
+fn end_after_block_start() {
+    let source = "func f()\n    while x\n        \n    x\nend\n";
+    let edit = end_on_new_line(source, 1).unwrap();
+    assert_eq!(
+        crate::fix::apply_edits(source, vec![edit]),
+        "func f()\n    while x\n        \n    end\n    x\nend\n"
+    );
+    // At the end of the file.
+    let source = "while x\n    ";
+    let edit = end_on_new_line(source, 0).unwrap();
+    assert_eq!(
+        crate::fix::apply_edits(source, vec![edit]),
+        "while x\n    \nend"
+    );
+    // Already closed.
+    assert!(end_on_new_line("while x\n\nend\n", 0).is_none());
+    // Not the start of a block.
+    assert!(end_on_new_line("func f()\n    x\n\n", 1).is_none());
+}
+
+#[test]
+fn fuzzy_matching() {
+    assert!(fuzzy_match("pt", "Point"));
+    assert!(fuzzy_match("", "f"));
+    assert!(fuzzy_match("ADD", "add"));
+    assert!(!fuzzy_match("tp", "Point"));
+    assert!(!fuzzy_match("ff", "f"));
+}
+
+#[test]
+fn read_from_memory() {
+    let mut files = InMemory::default();
+    files.insert(
+        "/project/main.sysc",
+        r#"
+        import lib
+        import util("./sub/../util")
+        import other("sub/other")
+        "#,
+    );
+    files.insert("/project/lib.sysc", "import util\nfunc f()\nend\n");
+    files.insert("/project/util.sysc", "func g()\nend\n");
+    files.insert("/project/sub/other.sysc", "import util(\"../util\")\n");
+    // `util` is imported three times but read only once.
+    let mut sink = log::Collector::default();
+    let program = read_input_with(Path::new("/project/main"), &files, &mut sink).unwrap();
+    assert!(sink.diagnostics.is_empty());
+    assert_eq!(program.definitions.functions.len(), 2);
+}
+
+#[test]
+fn canonicalization_is_cached() {
+    struct Counting {
+        files: InMemory,
+        num_calls: std::cell::Cell<usize>,
+    }
+    impl SourceProvider for Counting {
+        fn canonicalize(&self, path: &Path) -> std::io::Result<PathBuf> {
+            self.num_calls.set(self.num_calls.get() + 1);
+            self.files.canonicalize(path)
+        }
+        fn read_to_string(&self, path: &Path) -> std::io::Result<String> {
+            self.files.read_to_string(path)
+        }
+    }
+    let mut files = InMemory::default();
+    files.insert("/project/main.sysc", "import a\nimport b\nimport c\n");
+    files.insert("/project/a.sysc", "import c\n");
+    files.insert("/project/b.sysc", "import c\n");
+    files.insert("/project/c.sysc", "");
+    let provider = Counting {
+        files,
+        num_calls: std::cell::Cell::new(0),
+    };
+    let mut sink = log::Collector::default();
+    read_input_with(Path::new("/project/main"), &provider, &mut sink).unwrap();
+    // Once for each of the four files.
+    assert_eq!(provider.num_calls.get(), 4);
+}
+
+#[test]
+fn circular_imports_in_memory() {
+    let mut files = InMemory::default();
+    files.insert("/project/a.sysc", "import b\n");
+    files.insert("/project/b.sysc", "import a\n");
+    let mut sink = log::Collector::default();
+    assert!(read_input_with(Path::new("/project/a"), &files, &mut sink).is_err());
+    let [(path, diagnostic)] = &sink.diagnostics[..] else {
+        panic!("{:#?}", sink.diagnostics);
+    };
+    assert_eq!(path.as_deref(), Some(Path::new("/project/b.sysc")));
+    assert_eq!(diagnostic.message, "Circular imports of `/project/a.sysc`.");
+}
+
+#[test]
+fn missing_import_in_memory() {
+    let mut files = InMemory::default();
+    files.insert("/project/main.sysc", "import missing\n");
+    let mut sink = log::Collector::default();
+    assert!(read_input_with(Path::new("/project/main"), &files, &mut sink).is_err());
+    assert_eq!(sink.diagnostics.len(), 1);
+}
+
+#[test]
+fn human_readable_diagnostics() {
+    let mut sink = log::HumanReadable::new(Vec::new());
+    assert!(read_input_from_string(
+        "foo = (1]\n",
+        Path::new("main.sysc"),
+        &InMemory::default(),
+        &mut sink,
+    )
+    .is_err());
+    assert_eq!(
+        String::from_utf8(sink.into_inner()).unwrap(),
+        "Unexpected token at 1:9-1:9.\n\
+         main.sysc\n\
+         L1: foo = (1 !-> ] <-! \n\
+         \n\
+         Note: Opening parenthesis at 1:7-1:7.\n\
+         main.sysc\n\
+         L1: foo =  !-> ( <-! 1]\n\
+         \n\
+         Aborting due to 1 previous errors.\n"
+    );
+}
+
+#[test]
+fn human_readable_continuation() {
+    let mut sink = log::HumanReadable::new(Vec::new());
+    assert!(read_input_from_string(
+        "struct 1\n",
+        Path::new("main.sysc"),
+        &InMemory::default(),
+        &mut sink,
+    )
+    .is_err());
+    let output = String::from_utf8(sink.into_inner()).unwrap();
+    assert!(output.contains("\nExpected an identifier after `struct` at 1:1-1:6.\n"));
+    assert!(!output.contains("Note:"));
+}
+
+#[test]
+fn json_diagnostics() {
+    let mut sink = log::JsonLines::new(Vec::new());
+    assert!(read_input_from_string(
+        "foo = \"price: $5\"\n",
+        Path::new("main.sysc"),
+        &InMemory::default(),
+        &mut sink,
+    )
+    .is_err());
+    assert_eq!(
+        String::from_utf8(sink.into_inner()).unwrap(),
+        "{\"path\":\"main.sysc\",\"severity\":\"error\",\"message\":\"Missing `{` after `$` at 1:15.\",\"quotes\":[{\"kind\":\
+         \"index\",\"line\":0,\"column\":14}],\"notes\":[]}\n"
+    );
+}
+
+#[test]
+fn timings() {
+    let mut files = InMemory::default();
+    files.insert("/project/main.sysc", "import lib\nconst A = 1\nvar x\n");
+    files.insert("/project/lib.sysc", "struct S\nend\n");
+    let program = read_input_with(
+        Path::new("/project/main"),
+        &files,
+        &mut log::Collector::default(),
+    )
+    .unwrap();
+    let [lib, main] = &program.timings[..] else {
+        panic!();
+    };
+    assert_eq!(lib.path, Path::new("/project/lib.sysc"));
+    assert_eq!((lib.bytes, lib.lines, lib.tokens, lib.items), (13, 2, 3, 1));
+    assert_eq!(main.path, Path::new("/project/main.sysc"));
+    assert_eq!((main.lines, main.tokens, main.items), (3, 8, 3));
+    let mut table = Vec::new();
+    write_timings(&mut table, &program.timings, Path::new("/project")).unwrap();
+    let table = String::from_utf8(table).unwrap();
+    let rows: Vec<_> = table
+        .lines()
+        .map(|line| line.split_whitespace().next().unwrap())
+        .collect();
+    assert_eq!(rows, ["file", "lib.sysc", "main.sysc", "total"]);
+    let mut json = Vec::new();
+    write_timings_json(&mut json, &program.timings).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.starts_with("{\"path\":\"/project/lib.sysc\",\"read_us\":"));
+    assert_eq!(json.lines().count(), 2);
+}
+
+#[test]
+fn timings_with_errors() {
+    let mut files = InMemory::default();
+    files.insert("/project/main.sysc", "import lib\nvar x = 1\n");
+    files.insert("/project/lib.sysc", "x = (\n");
+    let Err(errors) = read_input_with(
+        Path::new("/project/main"),
+        &files,
+        &mut log::Collector::default(),
+    ) else {
+        panic!();
+    };
+    assert_eq!(errors.num_errors, 1);
+    let paths: Vec<_> = errors.timings.iter().map(|timings| &timings.path).collect();
+    assert_eq!(
+        paths,
+        [
+            Path::new("/project/lib.sysc"),
+            Path::new("/project/main.sysc")
+        ]
+    );
+}
+
+#[test]
+fn relative_paths() {
+    let mut files = InMemory::default();
+    files.insert("/project/main.sysc", "import lib(\"sub/lib\")\n");
+    files.insert("/project/sub/lib.sysc", "x = (\n");
+    let mut sink = log::HumanReadable::new(Vec::new()).relative_to("/project");
+    assert!(read_input_with(Path::new("/project/main"), &files, &mut sink).is_err());
+    let output = String::from_utf8(sink.into_inner()).unwrap();
+    let path = Path::new("sub").join("lib.sysc");
+    assert!(output.contains(&format!("\n{}\n", path.display())));
+    assert!(!output.contains("/project"));
+}
+
+#[test]
+fn allowed_warnings() {
+    let file = log::File {
+        path: PathBuf::from("main.sysc"),
+        content: String::from("-- syscraws:allow(lint)\nx\ny\n"),
+        lines: vec![0..24, 24..26, 26..28],
+        allowed_lints: vec![(1, String::from("lint"))],
+    };
+    let mut sink = log::Collector::default();
+    let mut logger = log::Logger::new(&mut sink);
+    for line in 0..3 {
+        logger.warning(&file, "lint", line, log::Diagnostic::new("w"));
+        logger.warning(&file, "other", line, log::Diagnostic::new("w"));
+    }
+    assert_eq!(logger.num_errors(), 0);
+    assert_eq!(sink.diagnostics.len(), 5);
+    let (_, diagnostic) = &sink.diagnostics[0];
+    assert_eq!(diagnostic.severity, log::Severity::Warning);
+    assert_eq!(diagnostic.message, "Warning: w");
+    assert_eq!(
+        diagnostic.notes[0].message,
+        "Write `-- syscraws:allow(lint)` on the line before to allow this."
+    );
+}
+
+#[test]
+fn missing_end() {
+    let notes = |source| {
+        let mut sink = log::Collector::default();
+        assert!(read_input_from_string(
+            source,
+            Path::new("main.sysc"),
+            &InMemory::default(),
+            &mut sink,
+        )
+        .is_err());
+        let [(_, diagnostic)] = &sink.diagnostics[..] else {
+            panic!();
+        };
+        diagnostic
+            .notes
+            .iter()
+            .map(|note| note.message.clone())
+            .collect::<Vec<_>>()
+    };
+    // The `end` on line 6 closes `while`, but is indented for `func`.
+    assert_eq!(
+        notes("func f()\n    while x\n        x\n\n-- comment\nend\nexport func g()\nend\n"),
+        [
+            "Blocks opened at:",
+            "Judging from the indentation, `end` of the block at line 2 is likely missing before line 6."
+        ]
+    );
+    assert_eq!(
+        notes("func f()\n    while x\n        x\n    end\n"),
+        [
+            "Judging from the indentation, `end` of the block at line 1 is likely missing at the end of the file."
+        ]
+    );
+    // `else` continues the block of `if` rather than closing it.
+    assert_eq!(
+        notes("func f()\n    if x\n        x\n    else\n        x\nend\n"),
+        [
+            "Judging from the indentation, `end` of the block at line 2 is likely missing before line 6."
+        ]
+    );
+}
+
+#[test]
+fn program_is_send_and_sync() {
+    // Hosts may translate on one thread and use the result on others.
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Program>();
+}
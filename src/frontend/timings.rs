@@ -0,0 +1,152 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/*!
+ * Time spent on each file, for finding out what makes reading slow.
+ *
+ * The stages are the ones of the frontend. The lexer is driven by the
+ * parser, so tokenizing is measured as a part of parsing, and names are
+ * resolved while translating. Nothing after the translation is done per
+ * file, so there is no stage for checking the types or generating code.
+ */
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::log;
+
+/**
+ * Measurements of a file. The time spent on the files it imports is not
+ * included.
+ */
+#[derive(Clone, Debug)]
+pub struct FileTimings {
+    pub path: PathBuf,
+    /**
+     * Reading the content, which is zero for the source given as a string.
+     */
+    pub read: Duration,
+    /**
+     * Lexing and parsing, which are done together.
+     */
+    pub parse: Duration,
+    /**
+     * Resolving names and translating for `backend`.
+     */
+    pub translate: Duration,
+    pub bytes: usize,
+    pub lines: usize,
+    /**
+     * The number of tokens read, including the ones in placeholders of
+     * string literals. If parsing fails, the ones before the error.
+     */
+    pub tokens: usize,
+    /**
+     * The number of imports, definitions and top-level statements, which
+     * stands in for the size of the AST.
+     */
+    pub items: usize,
+}
+
+/**
+ * Writes a table of `timings` with a row of the total, with the paths
+ * relative to `base_directory`.
+ */
+pub fn write_timings(
+    writer: &mut impl Write,
+    timings: &[FileTimings],
+    base_directory: &Path,
+) -> io::Result<()> {
+    let paths: Vec<_> = timings
+        .iter()
+        .map(|timings| {
+            let path = timings.path.strip_prefix(base_directory);
+            path.unwrap_or(&timings.path).display().to_string()
+        })
+        .collect();
+    let width = paths.iter().map(String::len).max().unwrap_or(0).max(5);
+    writeln!(
+        writer,
+        "{:width$} {:>10} {:>10} {:>10} {:>8} {:>8} {:>6}",
+        "file", "read", "parse", "translate", "lines", "tokens", "items"
+    )?;
+    let mut total = FileTimings {
+        path: PathBuf::new(),
+        read: Duration::ZERO,
+        parse: Duration::ZERO,
+        translate: Duration::ZERO,
+        bytes: 0,
+        lines: 0,
+        tokens: 0,
+        items: 0,
+    };
+    for (path, timings) in paths.iter().zip(timings) {
+        write_row(writer, path, timings, width)?;
+        total.read += timings.read;
+        total.parse += timings.parse;
+        total.translate += timings.translate;
+        total.bytes += timings.bytes;
+        total.lines += timings.lines;
+        total.tokens += timings.tokens;
+        total.items += timings.items;
+    }
+    write_row(writer, "total", &total, width)
+}
+
+fn write_row(
+    writer: &mut impl Write,
+    path: &str,
+    timings: &FileTimings,
+    width: usize,
+) -> io::Result<()> {
+    let milliseconds = |duration: Duration| format!("{:.3}ms", duration.as_secs_f64() * 1000.);
+    writeln!(
+        writer,
+        "{path:width$} {:>10} {:>10} {:>10} {:>8} {:>8} {:>6}",
+        milliseconds(timings.read),
+        milliseconds(timings.parse),
+        milliseconds(timings.translate),
+        timings.lines,
+        timings.tokens,
+        timings.items
+    )
+}
+
+/**
+ * Writes `timings` as one JSON object per file, on its own line. Durations
+ * are in microseconds.
+ */
+pub fn write_timings_json(writer: &mut impl Write, timings: &[FileTimings]) -> io::Result<()> {
+    for timings in timings {
+        write!(writer, "{{\"path\":")?;
+        log::write_json_string(writer, &timings.path.to_string_lossy())?;
+        writeln!(
+            writer,
+            ",\"read_us\":{},\"parse_us\":{},\"translate_us\":{},\"bytes\":{},\"lines\":{},\"tokens\":{},\"items\":{}}}",
+            timings.read.as_micros(),
+            timings.parse.as_micros(),
+            timings.translate.as_micros(),
+            timings.bytes,
+            timings.lines,
+            timings.tokens,
+            timings.items
+        )?;
+    }
+    Ok(())
+}
@@ -16,124 +16,726 @@
  * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::fmt::{self, Display, Formatter};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::io::{self, Write};
 use std::ops::Range;
-use std::path::{Path, PathBuf};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR, MAIN_SEPARATOR_STR};
 
 /**
- * Called by [`frontend::read_input`](crate::frontend::read_input).
+ * Called by [`frontend::read_input_with`](crate::frontend::read_input_with).
  */
-pub fn root_file_not_found(path: &Path, err: std::io::Error) {
-    eprintln!("ERROR: File `{}` not found. {}", path.display(), err);
+pub fn root_file_not_found(path: &Path, err: std::io::Error) -> Diagnostic {
+    Diagnostic::new(format!(
+        "ERROR: File `{}` not found. {}",
+        path.display(),
+        err
+    ))
 }
 
 /**
- * Called by [`frontend::read_input`](crate::frontend::read_input).
+ * Called by [`frontend::read_input_with`](crate::frontend::read_input_with).
  */
-pub fn cannot_read_root_file(path: &Path, err: std::io::Error) {
-    eprintln!("ERROR: Cannot read file `{}`. {}", path.display(), err);
+pub fn cannot_read_root_file(path: &Path, err: std::io::Error) -> Diagnostic {
+    Diagnostic::new(format!(
+        "ERROR: Cannot read file `{}`. {}",
+        path.display(),
+        err
+    ))
 }
 
 /**
- * Prints a final message before exiting.
+ * Called by [`frontend::read_input_with_options`](crate::frontend::read_input_with_options).
  */
-pub fn aborting(num_errors: u32) {
-    eprintln!("Aborting due to {num_errors} previous errors.");
+pub fn cannot_access_import_root(path: &Path, err: std::io::Error) -> Diagnostic {
+    Diagnostic::new(format!(
+        "ERROR: Cannot access import root `{}`. {}",
+        path.display(),
+        err
+    ))
+}
+
+/**
+ * A message reported to a [`DiagnosticSink`], quoting parts of the file
+ * it is about, optionally followed by notes.
+ */
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub quotes: Vec<Quote>,
+    pub notes: Vec<Note>,
+    /**
+     * Edits of the file which fix the problem when applied together. See
+     * [`crate::fix`].
+     */
+    pub edits: Vec<Edit>,
+}
+
+/**
+ * A replacement of the text in `pos` with `replacement`. An empty `pos`
+ * inserts text.
+ */
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub pos: Pos,
+    pub replacement: String,
+}
+
+/**
+ * Whether a [`Diagnostic`] makes reading fail.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    /**
+     * Reported by [`Logger::warning`], which does not make reading fail.
+     */
+    Warning,
+}
+
+/**
+ * A supplementary message in a [`Diagnostic`].
+ */
+#[derive(Debug, Clone)]
+pub struct Note {
+    pub message: String,
+    pub quotes: Vec<Quote>,
+    /**
+     * Whether the note continues the message, such as "Blocks opened at:"
+     * followed by the lines, rather than adding to it. It is printed
+     * without `Note:` then.
+     */
+    pub is_continuation: bool,
+}
+
+/**
+ * A part of a file quoted in a [`Diagnostic`] or a [`Note`].
+ */
+#[derive(Debug, Clone)]
+pub enum Quote {
+    /**
+     * A whole line, specified by its zero-based number.
+     */
+    Line(usize),
+    /**
+     * A point between two characters.
+     */
+    Index(Index),
+    /**
+     * A range of characters.
+     */
+    Pos(Pos),
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            message: message.into(),
+            quotes: Vec::new(),
+            notes: Vec::new(),
+            edits: Vec::new(),
+        }
+    }
+    /**
+     * Adds a note. The quotes added after this belong to the note.
+     */
+    pub fn note(mut self, message: impl Into<String>) -> Diagnostic {
+        self.notes.push(Note {
+            message: message.into(),
+            quotes: Vec::new(),
+            is_continuation: false,
+        });
+        self
+    }
+    /**
+     * Adds a note continuing the message, printed without `Note:`. The
+     * quotes added after this belong to the note.
+     */
+    pub fn continuation(mut self, message: impl Into<String>) -> Diagnostic {
+        self.notes.push(Note {
+            message: message.into(),
+            quotes: Vec::new(),
+            is_continuation: true,
+        });
+        self
+    }
+    pub fn quote_line(self, line: usize) -> Diagnostic {
+        self.quote(Quote::Line(line))
+    }
+    pub fn quote_index(self, index: Index) -> Diagnostic {
+        self.quote(Quote::Index(index))
+    }
+    pub fn quote_pos(self, pos: Pos) -> Diagnostic {
+        self.quote(Quote::Pos(pos))
+    }
+    /**
+     * Suggests replacing the text in `pos` with `replacement`.
+     */
+    pub fn edit(mut self, pos: Pos, replacement: impl Into<String>) -> Diagnostic {
+        self.edits.push(Edit {
+            pos,
+            replacement: replacement.into(),
+        });
+        self
+    }
+    fn quote(mut self, quote: Quote) -> Diagnostic {
+        match self.notes.last_mut() {
+            Some(note) => note.quotes.push(quote),
+            None => self.quotes.push(quote),
+        }
+        self
+    }
+}
+
+/**
+ * Receives the diagnostics produced while reading files.
+ */
+pub trait DiagnosticSink {
+    /**
+     * Reports `diagnostic`. `file` is the file which the quotes refer to,
+     * or `None` if the diagnostic is not about the content of a file.
+     */
+    fn emit(&mut self, file: Option<&File>, diagnostic: Diagnostic);
+    /**
+     * Called once before giving up because of the errors reported so far.
+     */
+    fn aborting(&mut self, _num_errors: u32) {}
+}
+
+/**
+ * A [`DiagnosticSink`] writing diagnostics for humans to read.
+ */
+pub struct HumanReadable<W> {
+    writer: W,
+    /**
+     * If set, paths under this directory are shown relative to it, so that
+     * the output does not depend on where the project is placed.
+     */
+    base_directory: Option<PathBuf>,
+}
+
+impl HumanReadable<io::Stderr> {
+    pub fn stderr() -> Self {
+        HumanReadable::new(io::stderr())
+    }
+}
+
+impl<W: Write> HumanReadable<W> {
+    pub fn new(writer: W) -> Self {
+        HumanReadable {
+            writer,
+            base_directory: None,
+        }
+    }
+    /**
+     * Shows paths relative to `base_directory`, both in quotes and in
+     * messages.
+     */
+    pub fn relative_to(mut self, base_directory: impl Into<PathBuf>) -> Self {
+        self.base_directory = Some(base_directory.into());
+        self
+    }
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> DiagnosticSink for HumanReadable<W> {
+    fn emit(&mut self, file: Option<&File>, diagnostic: Diagnostic) {
+        // There is nowhere to report a failure to write a diagnostic.
+        let _ = write_human_readable(
+            &mut self.writer,
+            file,
+            &diagnostic,
+            self.base_directory.as_deref(),
+        );
+    }
+    fn aborting(&mut self, num_errors: u32) {
+        let _ = writeln!(self.writer, "Aborting due to {num_errors} previous errors.");
+    }
+}
+
+fn write_human_readable(
+    writer: &mut impl Write,
+    file: Option<&File>,
+    diagnostic: &Diagnostic,
+    base_directory: Option<&Path>,
+) -> io::Result<()> {
+    let display = |path: &Path| match base_directory.and_then(|base| path.strip_prefix(base).ok()) {
+        Some(relative_path) => relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(MAIN_SEPARATOR_STR),
+        None => path.display().to_string(),
+    };
+    // Paths in messages are not known as such, so the base directory is removed textually.
+    let relativize = |message: &str| match base_directory {
+        Some(base) => message.replace(&format!("{}{}", base.display(), MAIN_SEPARATOR), ""),
+        None => message.to_owned(),
+    };
+    writeln!(writer, "{}", relativize(&diagnostic.message))?;
+    if let Some(file) = file {
+        for quote in &diagnostic.quotes {
+            writeln!(writer, "{}", display(&file.path))?;
+            file.quote_source(writer, quote)?;
+        }
+    }
+    for note in &diagnostic.notes {
+        if note.is_continuation {
+            writeln!(writer, "{}", relativize(&note.message))?;
+        } else {
+            writeln!(writer, "Note: {}", relativize(&note.message))?;
+        }
+        if let Some(file) = file {
+            for quote in &note.quotes {
+                writeln!(writer, "{}", display(&file.path))?;
+                file.quote_source(writer, quote)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/**
+ * A [`DiagnosticSink`] writing each diagnostic as a JSON object on its
+ * own line, for tools to read. Line and column numbers are zero-based.
+ */
+pub struct JsonLines<W> {
+    writer: W,
+}
+
+impl<W: Write> JsonLines<W> {
+    pub fn new(writer: W) -> Self {
+        JsonLines { writer }
+    }
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> DiagnosticSink for JsonLines<W> {
+    fn emit(&mut self, file: Option<&File>, diagnostic: Diagnostic) {
+        // There is nowhere to report a failure to write a diagnostic.
+        let _ = write_json(&mut self.writer, file, &diagnostic);
+    }
+}
+
+fn write_json(
+    writer: &mut impl Write,
+    file: Option<&File>,
+    diagnostic: &Diagnostic,
+) -> io::Result<()> {
+    write!(writer, "{{\"path\":")?;
+    match file {
+        Some(file) => write_json_string(writer, &file.path.to_string_lossy())?,
+        None => write!(writer, "null")?,
+    }
+    match diagnostic.severity {
+        Severity::Error => write!(writer, ",\"severity\":\"error\"")?,
+        Severity::Warning => write!(writer, ",\"severity\":\"warning\"")?,
+    }
+    write!(writer, ",\"message\":")?;
+    write_json_string(writer, &diagnostic.message)?;
+    write!(writer, ",\"quotes\":")?;
+    write_json_quotes(writer, &diagnostic.quotes)?;
+    write!(writer, ",\"notes\":[")?;
+    for (i, note) in diagnostic.notes.iter().enumerate() {
+        if i > 0 {
+            write!(writer, ",")?;
+        }
+        write!(writer, "{{\"message\":")?;
+        write_json_string(writer, &note.message)?;
+        write!(writer, ",\"quotes\":")?;
+        write_json_quotes(writer, &note.quotes)?;
+        write!(writer, "}}")?;
+    }
+    writeln!(writer, "]}}")
+}
+
+fn write_json_quotes(writer: &mut impl Write, quotes: &[Quote]) -> io::Result<()> {
+    write!(writer, "[")?;
+    for (i, quote) in quotes.iter().enumerate() {
+        if i > 0 {
+            write!(writer, ",")?;
+        }
+        match quote {
+            Quote::Line(line) => write!(writer, "{{\"kind\":\"line\",\"line\":{line}}}")?,
+            Quote::Index(Index { line, column }) => write!(
+                writer,
+                "{{\"kind\":\"index\",\"line\":{line},\"column\":{column}}}"
+            )?,
+            Quote::Pos(Pos { start, end }) => write!(
+                writer,
+                "{{\"kind\":\"range\",\"start\":{{\"line\":{},\"column\":{}}},\"end\":{{\"line\":{},\"column\":{}}}}}",
+                start.line, start.column, end.line, end.column
+            )?,
+        }
+    }
+    write!(writer, "]")
+}
+
+pub(crate) fn write_json_string(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    write!(writer, "\"")?;
+    for ch in value.chars() {
+        match ch {
+            '"' => write!(writer, "\\\"")?,
+            '\\' => write!(writer, "\\\\")?,
+            '\n' => write!(writer, "\\n")?,
+            '\r' => write!(writer, "\\r")?,
+            '\t' => write!(writer, "\\t")?,
+            ch if ch.is_control() => write!(writer, "\\u{:04x}", ch as u32)?,
+            ch => write!(writer, "{ch}")?,
+        }
+    }
+    write!(writer, "\"")
+}
+
+/**
+ * A [`DiagnosticSink`] keeping diagnostics in memory, together with the
+ * path of the file each one is about.
+ */
+#[derive(Default)]
+pub struct Collector {
+    pub diagnostics: Vec<(Option<PathBuf>, Diagnostic)>,
+}
+
+impl DiagnosticSink for Collector {
+    fn emit(&mut self, file: Option<&File>, diagnostic: Diagnostic) {
+        self.diagnostics
+            .push((file.map(|file| file.path.clone()), diagnostic));
+    }
+}
+
+/**
+ * Lints whose warnings are reported only if enabled by
+ * [`Logger::enable_lint`].
+ */
+pub const OPT_IN_LINTS: &[&str] = &["shadowing"];
+
+/**
+ * Passes errors to a [`DiagnosticSink`], counting them.
+ */
+pub struct Logger<'sink> {
+    sink: &'sink mut dyn DiagnosticSink,
+    num_errors: u32,
+    /**
+     * The lints in [`OPT_IN_LINTS`] which are enabled.
+     */
+    enabled_lints: Vec<String>,
+}
+
+impl<'sink> Logger<'sink> {
+    pub fn new(sink: &'sink mut dyn DiagnosticSink) -> Self {
+        Logger {
+            sink,
+            num_errors: 0,
+            enabled_lints: Vec::new(),
+        }
+    }
+    pub fn enable_lint(&mut self, lint: impl Into<String>) {
+        self.enabled_lints.push(lint.into());
+    }
+    /**
+     * Reports an error in `file`.
+     */
+    pub fn error(&mut self, file: &File, diagnostic: Diagnostic) {
+        self.num_errors += 1;
+        self.sink.emit(Some(file), diagnostic);
+    }
+    /**
+     * Reports a warning from the lint `lint` about the zero-based `line` of
+     * `file`, unless the lint is allowed there or is an opt-in lint not
+     * enabled. Warnings do not make reading fail.
+     */
+    pub fn warning(&mut self, file: &File, lint: &str, line: usize, mut diagnostic: Diagnostic) {
+        if OPT_IN_LINTS.contains(&lint) && !self.enabled_lints.iter().any(|enabled| enabled == lint)
+        {
+            return;
+        }
+        let is_allowed = file
+            .allowed_lints
+            .iter()
+            .any(|(allowed_line, allowed_lint)| *allowed_line == line && allowed_lint == lint);
+        if is_allowed {
+            return;
+        }
+        diagnostic.severity = Severity::Warning;
+        diagnostic.message = format!("Warning: {}", diagnostic.message);
+        diagnostic = diagnostic.note(format!(
+            "Write `-- syscraws:allow({lint})` on the line before to allow this."
+        ));
+        self.sink.emit(Some(file), diagnostic);
+    }
+    /**
+     * Reports an error which is not about the content of a file.
+     */
+    pub fn error_without_file(&mut self, diagnostic: Diagnostic) {
+        self.num_errors += 1;
+        self.sink.emit(None, diagnostic);
+    }
+    pub fn num_errors(&self) -> u32 {
+        self.num_errors
+    }
+    /**
+     * Tells the sink that the errors so far are fatal.
+     */
+    pub fn aborting(&mut self) {
+        self.sink.aborting(self.num_errors);
+    }
 }
 
 pub struct File {
     pub path: PathBuf,
     pub content: String,
     pub lines: Vec<Range<usize>>,
+    /**
+     * Lints allowed on each zero-based line by a comment
+     * `-- syscraws:allow(...)` on the line before. See
+     * [`Logger::warning`].
+     */
+    pub allowed_lints: Vec<(usize, String)>,
 }
 
 impl File {
-    pub fn quote_line(&self, line: usize) {
-        eprintln!("{}", self.path.display());
-        eprintln!(
+    /**
+     * Writes the part of this file specified by `quote`, preceded by the
+     * path.
+     */
+    pub fn quote(&self, writer: &mut impl Write, quote: &Quote) -> io::Result<()> {
+        writeln!(writer, "{}", self.path.display())?;
+        self.quote_source(writer, quote)
+    }
+    /**
+     * Same as [`File::quote`], without the path.
+     */
+    fn quote_source(&self, writer: &mut impl Write, quote: &Quote) -> io::Result<()> {
+        match *quote {
+            Quote::Line(line) => self.quote_line(writer, line),
+            Quote::Index(index) => self.quote_index(writer, index),
+            Quote::Pos(ref pos) => self.quote_pos(writer, pos.clone()),
+        }
+    }
+    /**
+     * Returns the source text in `pos`.
+     */
+    pub fn source(&self, Pos { start, end }: &Pos) -> &str {
+        let start = self.lines[start.line].start + start.column;
+        let end = self.lines[end.line].start + end.column;
+        &self.content[start..end]
+    }
+    fn quote_line(&self, writer: &mut impl Write, line: usize) -> io::Result<()> {
+        writeln!(
+            writer,
             "L{}: !-> {}",
             line + 1,
             &self.content[self.lines[line].clone()]
-        );
-        eprintln!();
+        )?;
+        writeln!(writer)
     }
-    pub fn quote_index(&self, Index { line, column }: Index) {
-        eprintln!("{}", self.path.display());
+    fn quote_index(
+        &self,
+        writer: &mut impl Write,
+        Index { line, column }: Index,
+    ) -> io::Result<()> {
         let start_line = &self.content[self.lines[line].clone()];
-        eprintln!(
+        writeln!(
+            writer,
             "L{}: {} !-> {}",
             line + 1,
             &start_line[..column],
             &start_line[column..],
-        );
-        eprintln!();
+        )?;
+        writeln!(writer)
     }
-    pub fn quote_pos(&self, Pos { start, end }: Pos) {
-        eprintln!("{}", self.path.display());
+    fn quote_pos(&self, writer: &mut impl Write, Pos { start, end }: Pos) -> io::Result<()> {
         match end.line - start.line {
             0 => {
                 let line = &self.content[self.lines[start.line].clone()];
-                eprintln!(
+                writeln!(
+                    writer,
                     "L{}: {} !-> {} <-! {}",
                     start.line + 1,
                     &line[..start.column],
                     &line[start.column..end.column],
                     &line[end.column..],
-                );
+                )?;
             }
             1 => {
                 let start_line = &self.content[self.lines[start.line].clone()];
                 let end_line = &self.content[self.lines[end.line].clone()];
-                eprintln!(
+                writeln!(
+                    writer,
                     "L{}: {} !-> {}",
                     start.line + 1,
                     &start_line[..start.column],
                     &start_line[start.column..],
-                );
-                eprintln!(
+                )?;
+                writeln!(
+                    writer,
                     "L{}: {} <-! {}",
                     end.line + 1,
                     &end_line[..end.column],
                     &end_line[end.column..],
-                );
+                )?;
             }
             2 => {
                 let start_line = &self.content[self.lines[start.line].clone()];
                 let mid_line = &self.content[self.lines[start.line + 1].clone()];
                 let end_line = &self.content[self.lines[end.line].clone()];
-                eprintln!(
+                writeln!(
+                    writer,
                     "L{}: {} !-> {}",
                     start.line + 1,
                     &start_line[..start.column],
                     &start_line[start.column..],
-                );
-                eprintln!("L{}: {}", start.line + 2, mid_line);
-                eprintln!(
+                )?;
+                writeln!(writer, "L{}: {}", start.line + 2, mid_line)?;
+                writeln!(
+                    writer,
                     "L{}: {} <-! {}",
                     end.line + 1,
                     &end_line[..end.column],
                     &end_line[end.column..],
-                );
+                )?;
             }
             num_lines => {
                 let start_line = &self.content[self.lines[start.line].clone()];
                 let end_line = &self.content[self.lines[end.line].clone()];
-                eprintln!(
+                writeln!(
+                    writer,
                     "L{}: {} !-> {}",
                     start.line + 1,
                     &start_line[..start.column],
                     &start_line[start.column..],
-                );
-                eprintln!("({} lines)", num_lines - 1);
-                eprintln!(
+                )?;
+                writeln!(writer, "({} lines)", num_lines - 1)?;
+                writeln!(
+                    writer,
                     "L{}: {} <-! {}",
                     end.line + 1,
                     &end_line[..end.column],
                     &end_line[end.column..],
-                );
+                )?;
+            }
+        }
+        writeln!(writer)
+    }
+    /**
+     * Guesses from the indentation where `end` is missing, looking at the
+     * lines from `first_line`. Returns the line opening the block which
+     * lacks `end`, and the line before which `end` should be, or `None` if
+     * it should be at the end of the file.
+     *
+     * A block is opened by a line starting with a keyword such as `func` or
+     * `while`, and should be closed by `end` indented as deep as that line.
+     * `else` at that depth continues the block.
+     */
+    pub fn missing_end(&self, first_line: usize) -> Option<(usize, Option<usize>)> {
+        const OPENING_KEYWORDS: [&str; 8] = [
+            "func", "method", "struct", "enum", "test", "bench", "while", "if",
+        ];
+        let mut open_blocks: Vec<(usize, usize)> = Vec::new();
+        for line in first_line..self.lines.len() {
+            let text = &self.content[self.lines[line].clone()];
+            let code = text.trim_start();
+            if code.is_empty() || code.starts_with("--") {
+                continue;
+            }
+            let indentation = text.len() - code.len();
+            let mut words = code.split(|c: char| !c.is_alphanumeric() && c != '_');
+            let first_word = match words.next() {
+                Some("export") => words.find(|word| !word.is_empty()).unwrap_or(""),
+                Some(word) => word,
+                None => "",
+            };
+            if let Some(&(open_line, open_indentation)) = open_blocks.last() {
+                if indentation < open_indentation
+                    || indentation == open_indentation
+                        && first_word != "end"
+                        && first_word != "else"
+                {
+                    return Some((open_line, Some(line)));
+                }
+            }
+            if first_word == "end" {
+                open_blocks.pop();
+            } else if OPENING_KEYWORDS.contains(&first_word) {
+                open_blocks.push((line, indentation));
+            }
+        }
+        open_blocks.last().map(|&(open_line, _)| (open_line, None))
+    }
+    /**
+     * The edit inserting `end` for the block opened at `open_line`, indented
+     * as deep as that line, before `line` or at the end of the file if
+     * `None`. See [`File::missing_end`].
+     */
+    pub fn insert_end(&self, open_line: usize, line: Option<usize>) -> Edit {
+        let open_line_text = &self.content[self.lines[open_line].clone()];
+        let indentation =
+            &open_line_text[..open_line_text.len() - open_line_text.trim_start().len()];
+        let (index, replacement) = match line {
+            Some(line) => (Index { line, column: 0 }, format!("{indentation}end\n")),
+            None => {
+                let last_line = self.lines.len() - 1;
+                let last_line_len = self.lines[last_line].len();
+                let index = Index {
+                    line: last_line,
+                    column: last_line_len,
+                };
+                if last_line_len == 0 {
+                    (index, format!("{indentation}end\n"))
+                } else {
+                    (index, format!("\n{indentation}end"))
+                }
             }
+        };
+        Edit {
+            pos: Pos {
+                start: index,
+                end: index,
+            },
+            replacement,
         }
-        eprintln!();
+    }
+}
+
+/**
+ * Converts `error` into a diagnostic, with hints taken from the content of
+ * `file`.
+ */
+pub fn parse_error(error: ParseError, file: &File) -> Diagnostic {
+    let missing_end = match &error {
+        ParseError::UnclosedBlock { start_line_indices }
+        | ParseError::UnexpectedTokenInBlock {
+            start_line_indices, ..
+        } => start_line_indices
+            .first()
+            .and_then(|&first_line| file.missing_end(first_line)),
+        _ => None,
+    };
+    let diagnostic = Diagnostic::from(error);
+    let Some((open_line, line)) = missing_end else {
+        return diagnostic;
+    };
+    let edit = file.insert_end(open_line, line);
+    match line {
+        Some(line) => diagnostic
+            .note(format!(
+                "Judging from the indentation, `end` of the block at line {} is likely missing before line {}.",
+                open_line + 1,
+                line + 1
+            ))
+            .quote_line(line)
+            .edit(edit.pos, edit.replacement),
+        None => diagnostic
+            .note(format!(
+                "Judging from the indentation, `end` of the block at line {} is likely missing at the end of the file.",
+                open_line + 1
+            ))
+            .edit(edit.pos, edit.replacement),
     }
 }
 
@@ -160,6 +762,14 @@ pub enum ParseError {
         dollar_index: Index,
     },
     /// Returned by [`read_token`](../frontend/ast/fn.read_token.html).
+    MissingBraceInPlaceholder {
+        dollar_index: Index,
+    },
+    /// Returned by [`read_token`](../frontend/ast/fn.read_token.html).
+    InvalidFormatSpec {
+        spec_pos: Pos,
+    },
+    /// Returned by [`read_token`](../frontend/ast/fn.read_token.html).
     InvalidBlockComment {
         start_index: Index,
     },
@@ -168,10 +778,18 @@ pub enum ParseError {
         unexpected_token_pos: Pos,
         keyword_func_pos: Pos,
     },
+    UnexpectedTokenAfterKeywordMethod {
+        unexpected_token_pos: Pos,
+        keyword_method_pos: Pos,
+    },
     UnexpectedTokenAfterKeywordStruct {
         unexpected_token_pos: Pos,
         keyword_struct_pos: Pos,
     },
+    UnexpectedTokenAfterKeywordEnum {
+        unexpected_token_pos: Pos,
+        keyword_enum_pos: Pos,
+    },
     /// Returned by [`parse_block`](../frontend/ast/fn.parse_block.html).
     UnclosedBlock {
         start_line_indices: Vec<usize>,
@@ -206,150 +824,194 @@ pub enum ParseError {
     UnclosedBracket {
         opening_bracket_pos: Pos,
     },
+    TooDeeplyNested {
+        pos: Pos,
+        max_nesting_depth: usize,
+    },
+    MissingEqualAfterTargets {
+        targets_pos: Pos,
+    },
+    EqualAfterCondition {
+        equal_pos: Pos,
+        condition_pos: Pos,
+    },
 }
 
-impl ParseError {
-    pub fn eprint(self, file: &File) {
-        match self {
+impl From<ParseError> for Diagnostic {
+    fn from(error: ParseError) -> Diagnostic {
+        match error {
             ParseError::UnexpectedCharacter(index) => {
-                eprintln!("Unexpected character at {}.", index);
-                file.quote_index(index);
-            }
-            ParseError::UnterminatedStringLiteral { start_index } => {
-                eprintln!("Unterminated string literal started at {start_index}.");
-                file.quote_index(start_index);
+                Diagnostic::new(format!("Unexpected character at {}.", index)).quote_index(index)
             }
+            ParseError::UnterminatedStringLiteral { start_index } => Diagnostic::new(format!(
+                "Unterminated string literal started at {start_index}."
+            ))
+            .quote_index(start_index),
             ParseError::InvalidEscapeSequence { backslash_index } => {
-                eprintln!("Invalid escape squence at {backslash_index}.");
-                file.quote_index(backslash_index);
+                Diagnostic::new(format!("Invalid escape squence at {backslash_index}."))
+                    .quote_index(backslash_index)
             }
             ParseError::UnexpectedTokenInStringLiteral {
                 unexpected_token_pos,
                 dollar_index,
-            } => {
-                eprintln!("Unexpected token at {unexpected_token_pos}.");
-                file.quote_pos(unexpected_token_pos);
-                eprintln!("Note: A placeholder in string literal started at {dollar_index}.");
-                file.quote_index(dollar_index);
+            } => Diagnostic::new(format!("Unexpected token at {unexpected_token_pos}."))
+                .quote_pos(unexpected_token_pos)
+                .note(format!(
+                    "A placeholder in string literal started at {dollar_index}."
+                ))
+                .quote_index(dollar_index),
+            ParseError::InvalidFormatSpec { spec_pos } => {
+                Diagnostic::new(format!("Invalid format spec at {spec_pos}.")).quote_pos(spec_pos)
+            }
+            ParseError::MissingBraceInPlaceholder { dollar_index } => {
+                Diagnostic::new(format!("Missing `{{` after `$` at {dollar_index}."))
+                    .quote_index(dollar_index)
             }
             ParseError::UnterminatedComment {
                 start_indices: starts_index,
-            } => {
-                eprintln!("Unterminated comment started at:");
-                for start_index in starts_index {
-                    file.quote_index(start_index);
-                }
-            }
-            ParseError::InvalidBlockComment { start_index } => {
-                eprintln!(
-                    "A block comment must start at the beginning of the line, allowing only \
-                     leading whitespaces."
-                );
-                file.quote_index(start_index);
-            }
+            } => starts_index.into_iter().fold(
+                Diagnostic::new("Unterminated comment started at:"),
+                Diagnostic::quote_index,
+            ),
+            ParseError::InvalidBlockComment { start_index } => Diagnostic::new(
+                "A block comment must start at the beginning of the line, allowing only leading \
+                 whitespaces.",
+            )
+            .quote_index(start_index),
             ParseError::UnexpectedToken(unexpected_token_pos) => {
-                eprintln!("Unexpected token at {}.", unexpected_token_pos);
-                file.quote_pos(unexpected_token_pos);
+                Diagnostic::new(format!("Unexpected token at {}.", unexpected_token_pos))
+                    .quote_pos(unexpected_token_pos)
             }
             ParseError::UnexpectedTokenAfterKeywordStruct {
                 unexpected_token_pos,
                 keyword_struct_pos,
-            } => {
-                eprintln!("Unexpected token at {}.", unexpected_token_pos);
-                file.quote_pos(unexpected_token_pos);
-                eprintln!(
+            } => Diagnostic::new(format!("Unexpected token at {}.", unexpected_token_pos))
+                .quote_pos(unexpected_token_pos)
+                .continuation(format!(
                     "Expected an identifier after `struct` at {}.",
                     keyword_struct_pos
-                );
-                file.quote_pos(keyword_struct_pos);
-            }
+                ))
+                .quote_pos(keyword_struct_pos),
+            ParseError::UnexpectedTokenAfterKeywordMethod {
+                unexpected_token_pos,
+                keyword_method_pos,
+            } => Diagnostic::new(format!("Unexpected token at {}.", unexpected_token_pos))
+                .quote_pos(unexpected_token_pos)
+                .continuation(format!(
+                    "Expected an identifier after `method` at {}.",
+                    keyword_method_pos
+                ))
+                .quote_pos(keyword_method_pos),
+            ParseError::UnexpectedTokenAfterKeywordEnum {
+                unexpected_token_pos,
+                keyword_enum_pos,
+            } => Diagnostic::new(format!("Unexpected token at {}.", unexpected_token_pos))
+                .quote_pos(unexpected_token_pos)
+                .continuation(format!(
+                    "Expected an identifier after `enum` at {}.",
+                    keyword_enum_pos
+                ))
+                .quote_pos(keyword_enum_pos),
             ParseError::UnexpectedTokenAfterKeywordFunc {
                 unexpected_token_pos,
                 keyword_func_pos,
-            } => {
-                eprintln!("Unexpected token at {}.", unexpected_token_pos);
-                file.quote_pos(unexpected_token_pos);
-                eprintln!(
+            } => Diagnostic::new(format!("Unexpected token at {}.", unexpected_token_pos))
+                .quote_pos(unexpected_token_pos)
+                .continuation(format!(
                     "Expected an identifier after `func` at {}.",
                     keyword_func_pos
-                );
-                file.quote_pos(keyword_func_pos);
-            }
+                ))
+                .quote_pos(keyword_func_pos),
             ParseError::ExtraTokenAfterLine {
                 extra_token_pos,
                 line_pos: _,
-            } => {
-                eprintln!("An extra token at {}.", extra_token_pos);
-                file.quote_pos(extra_token_pos);
-            }
+            } => Diagnostic::new(format!("An extra token at {}.", extra_token_pos))
+                .quote_pos(extra_token_pos),
             ParseError::UnclosedBlock { start_line_indices } => {
-                eprintln!("Unexpected end of file. Blocks opened at:");
-                for &line_index in &start_line_indices {
-                    file.quote_line(line_index);
-                }
+                start_line_indices.into_iter().fold(
+                    Diagnostic::new("Unexpected end of file. Blocks opened at:"),
+                    Diagnostic::quote_line,
+                )
             }
             ParseError::UnexpectedTokenInBlock {
                 unexpected_token_pos,
                 start_line_indices,
-            } => {
-                eprintln!("Unexpected token at {}.", unexpected_token_pos);
-                file.quote_pos(unexpected_token_pos);
-                eprintln!("Blocks opened at:");
-                for &line_index in &start_line_indices {
-                    file.quote_line(line_index);
-                }
-            }
-            ParseError::MissingFieldAfterDot { dot_pos } => {
-                eprintln!("Missing field name or number after `.` at {dot_pos}.");
-                file.quote_pos(dot_pos);
-            }
+            } => start_line_indices.into_iter().fold(
+                Diagnostic::new(format!("Unexpected token at {}.", unexpected_token_pos))
+                    .quote_pos(unexpected_token_pos)
+                    .continuation("Blocks opened at:"),
+                Diagnostic::quote_line,
+            ),
+            ParseError::MissingFieldAfterDot { dot_pos } => Diagnostic::new(format!(
+                "Missing field name or number after `.` at {dot_pos}."
+            ))
+            .quote_pos(dot_pos),
             ParseError::UnexpectedTokenAfterDot {
                 unexpected_token_pos,
                 dot_pos,
-            } => {
-                eprintln!("Unexpected token at {}.", unexpected_token_pos);
-                file.quote_pos(unexpected_token_pos);
-                eprintln!("Note: expected a field name or number after `.` at {dot_pos}.");
-                file.quote_pos(dot_pos);
-            }
+            } => Diagnostic::new(format!("Unexpected token at {}.", unexpected_token_pos))
+                .quote_pos(unexpected_token_pos)
+                .note(format!(
+                    "Expected a field name or number after `.` at {dot_pos}."
+                ))
+                .quote_pos(dot_pos),
             ParseError::UnexpectedTokenInParentheses {
                 unexpected_token_pos,
                 opening_parenthesis_pos,
-            } => {
-                eprintln!("Unexpected token at {}.", unexpected_token_pos);
-                file.quote_pos(unexpected_token_pos);
-                eprintln!("Note: opening parenthesis at {}.", opening_parenthesis_pos);
-                file.quote_pos(opening_parenthesis_pos);
-            }
+            } => Diagnostic::new(format!("Unexpected token at {}.", unexpected_token_pos))
+                .quote_pos(unexpected_token_pos)
+                .note(format!(
+                    "Opening parenthesis at {}.",
+                    opening_parenthesis_pos
+                ))
+                .quote_pos(opening_parenthesis_pos),
             ParseError::UnclosedParenthesis {
                 opening_parenthesis_pos,
-            } => {
-                eprintln!(
-                    "Unclosed parenthesis opened at {}.",
-                    opening_parenthesis_pos
-                );
-                file.quote_pos(opening_parenthesis_pos);
-            }
+            } => Diagnostic::new(format!(
+                "Unclosed parenthesis opened at {}.",
+                opening_parenthesis_pos
+            ))
+            .quote_pos(opening_parenthesis_pos),
             ParseError::UnexpectedTokenInBrackets {
                 unexpected_token_pos,
                 opening_bracket_pos,
-            } => {
-                eprintln!("Unexpected token at {}.", unexpected_token_pos);
-                file.quote_pos(unexpected_token_pos);
-                eprintln!("Note: opening bracket at {}.", opening_bracket_pos);
-                file.quote_pos(opening_bracket_pos);
-            }
+            } => Diagnostic::new(format!("Unexpected token at {}.", unexpected_token_pos))
+                .quote_pos(unexpected_token_pos)
+                .note(format!("Opening bracket at {}.", opening_bracket_pos))
+                .quote_pos(opening_bracket_pos),
             ParseError::UnclosedBracket {
                 opening_bracket_pos,
-            } => {
-                eprintln!("Unclosed bracket opened at {}.", opening_bracket_pos);
-                file.quote_pos(opening_bracket_pos);
+            } => Diagnostic::new(format!(
+                "Unclosed bracket opened at {}.",
+                opening_bracket_pos
+            ))
+            .quote_pos(opening_bracket_pos),
+            ParseError::TooDeeplyNested {
+                pos,
+                max_nesting_depth,
+            } => Diagnostic::new(format!("The program is too deeply nested at {pos}."))
+                .quote_pos(pos)
+                .note(format!(
+                    "Blocks and terms can be nested up to {max_nesting_depth} levels."
+                )),
+            ParseError::MissingEqualAfterTargets { targets_pos } => {
+                Diagnostic::new(format!("Missing `=` after the targets at {targets_pos}."))
+                    .quote_pos(targets_pos)
             }
+            ParseError::EqualAfterCondition {
+                equal_pos,
+                condition_pos,
+            } => Diagnostic::new(format!(
+                "Unexpected `=` at {equal_pos} after the condition at {condition_pos}."
+            ))
+            .quote_pos(equal_pos.clone())
+            .note("Use `==` to compare values.")
+            .edit(equal_pos, "=="),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(PartialEq, Eq, Clone)]
 pub struct Pos {
     pub start: Index,
     pub end: Index,
@@ -361,6 +1023,16 @@ impl Pos {
     }
 }
 
+/**
+ * Formats as `start-end` with zero-based lines and columns, the same
+ * notation as the `pos!` macro in the tests.
+ */
+impl Debug for Pos {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:?}-{:?}", self.start, self.end)
+    }
+}
+
 impl Display for Pos {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         self.start.fmt_start(f)?;
@@ -369,7 +1041,7 @@ impl Display for Pos {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Index {
     pub line: usize,
     pub column: usize,
@@ -384,6 +1056,16 @@ impl Index {
     }
 }
 
+/**
+ * Formats as `line:column` with zero-based numbers, the same notation as
+ * the `index!` macro in the tests.
+ */
+impl Debug for Index {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 impl Display for Index {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         self.fmt_start(f)
@@ -16,24 +16,268 @@
  * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
  */
 
-mod backend;
-mod frontend;
-mod log;
-
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
-use clap::Parser;
+use clap::builder::PossibleValuesParser;
+use clap::{Parser, Subcommand, ValueEnum};
+use syscraws::driver::{Artifact, Driver};
+use syscraws::{fix, frontend, log, reduce};
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct CommandLineArguments {
-    filename: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[arg(required = true)]
+    filename: Option<String>,
+    /// How to print errors.
+    #[arg(long, value_enum, default_value_t = ErrorFormat::Human)]
+    error_format: ErrorFormat,
+    /// Shows absolute paths in errors, instead of paths relative to the
+    /// current directory.
+    #[arg(long)]
+    absolute_paths: bool,
+    /// Enables warnings which are off by default.
+    #[arg(long = "warn", value_name = "LINT", value_parser = PossibleValuesParser::new(log::OPT_IN_LINTS))]
+    enabled_lints: Vec<String>,
+    /// Rejects imports of files outside this directory.
+    #[arg(long)]
+    import_root: Option<PathBuf>,
+    /// Prints the time spent on each file to stderr, even if there are
+    /// errors.
+    #[arg(long, value_enum, num_args = 0..=1, require_equals = true, default_missing_value = "human")]
+    timings: Option<TimingsFormat>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates documentation of the items defined in a file and the files
+    /// it imports.
+    Doc {
+        filename: String,
+        #[arg(long, value_enum, default_value_t = DocFormat::Markdown)]
+        format: DocFormat,
+        /// The directory to write one page per file into. The pages are
+        /// printed if omitted.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Lists the items defined in a file and the files it imports whose
+    /// names match a pattern, with their signatures and locations.
+    Symbols {
+        filename: String,
+        /// Matches the names containing its characters in order, ignoring
+        /// case.
+        pattern: String,
+    },
+    /// Minimizes a file which makes the compiler panic, and prints the
+    /// result.
+    Reduce { filename: String },
+    /// Applies the fixes suggested by errors and warnings, such as a missing
+    /// `end`, to a file.
+    Fix {
+        filename: String,
+        /// Prints the changes as a diff instead of writing them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Prints the grammar accepted by the parser in EBNF.
+    #[command(hide = true)]
+    Grammar,
+}
+
+#[derive(Clone, ValueEnum)]
+enum DocFormat {
+    Markdown,
+    Html,
+}
+
+#[derive(Clone, ValueEnum)]
+enum TimingsFormat {
+    /// A table with the total.
+    Human,
+    /// One JSON object per file per line.
+    Json,
+}
+
+#[derive(Clone, ValueEnum)]
+enum ErrorFormat {
+    /// Messages with the relevant parts of the source quoted.
+    Human,
+    /// One JSON object per line.
+    Json,
 }
 
 fn main() -> ExitCode {
     let command_line_arguments = CommandLineArguments::parse();
-    let Ok(_) = frontend::read_input(std::path::Path::new(&command_line_arguments.filename)) else {
+    if let Some(Command::Doc {
+        filename,
+        format,
+        output,
+    }) = &command_line_arguments.command
+    {
+        return generate_documentation(Path::new(filename), format, output.as_deref());
+    }
+    if let Some(Command::Symbols { filename, pattern }) = &command_line_arguments.command {
+        return search_symbols(Path::new(filename), pattern);
+    }
+    if let Some(Command::Reduce { filename }) = &command_line_arguments.command {
+        return reduce_input(Path::new(filename));
+    }
+    if let Some(Command::Fix { filename, dry_run }) = &command_line_arguments.command {
+        return fix_file(Path::new(filename), *dry_run);
+    }
+    if let Some(Command::Grammar) = &command_line_arguments.command {
+        print!("{}", frontend::ast::grammar::ebnf());
+        return ExitCode::SUCCESS;
+    }
+    let path = Path::new(command_line_arguments.filename.as_ref().unwrap());
+    let mut sink: Box<dyn log::DiagnosticSink> = match command_line_arguments.error_format {
+        ErrorFormat::Human => {
+            let sink = log::HumanReadable::stderr();
+            // The paths of files are canonicalized, and so is the base directory.
+            match std::env::current_dir().and_then(std::fs::canonicalize) {
+                Ok(current_directory) if !command_line_arguments.absolute_paths => {
+                    Box::new(sink.relative_to(current_directory))
+                }
+                _ => Box::new(sink),
+            }
+        }
+        ErrorFormat::Json => Box::new(log::JsonLines::new(std::io::stderr())),
+    };
+    let mut driver = Driver::new(path).sink(sink.as_mut());
+    if let Some(import_root) = &command_line_arguments.import_root {
+        driver = driver.import_root(import_root);
+    }
+    for lint in &command_line_arguments.enabled_lints {
+        driver = driver.enable_lint(lint);
+    }
+    let (timings, exit_code) = match driver.run() {
+        Ok(Artifact::Program(program)) => (program.timings, ExitCode::SUCCESS),
+        Ok(_) => unreachable!(),
+        Err(errors) => (errors.timings, ExitCode::FAILURE),
+    };
+    if let Some(format) = &command_line_arguments.timings {
+        // The files read before the errors are also measured.
+        let stderr = &mut std::io::stderr();
+        let _ = match format {
+            TimingsFormat::Human => {
+                let root_file_path = std::fs::canonicalize(path.with_extension("sysc"));
+                let base_directory = match &root_file_path {
+                    Ok(root_file_path) => root_file_path.parent().unwrap(),
+                    Err(_) => Path::new(""),
+                };
+                frontend::write_timings(stderr, &timings, base_directory)
+            }
+            TimingsFormat::Json => frontend::write_timings_json(stderr, &timings),
+        };
+    }
+
+    exit_code
+}
+
+fn generate_documentation(path: &Path, format: &DocFormat, output: Option<&Path>) -> ExitCode {
+    let Ok(modules) = frontend::read_documentation(
+        path,
+        &frontend::FileSystem,
+        &mut log::HumanReadable::stderr(),
+    ) else {
         return ExitCode::FAILURE;
     };
+    let base_directory = modules[0].path.parent().unwrap().to_path_buf();
+    for module in &modules {
+        // Pages are named after the paths relative to the root file.
+        let relative_path = module
+            .path
+            .strip_prefix(&base_directory)
+            .unwrap_or(Path::new(module.path.file_name().unwrap()))
+            .with_extension("");
+        let title = relative_path.display().to_string();
+        let (page, extension) = match format {
+            DocFormat::Markdown => (frontend::render_markdown(module, &title), "md"),
+            DocFormat::Html => (frontend::render_html(module, &title), "html"),
+        };
+        let Some(output) = output else {
+            println!("{page}");
+            continue;
+        };
+        let page_path = output.join(relative_path).with_extension(extension);
+        let result = std::fs::create_dir_all(page_path.parent().unwrap())
+            .and_then(|()| std::fs::write(&page_path, page));
+        if let Err(err) = result {
+            eprintln!("ERROR: Cannot write `{}`. {}", page_path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn search_symbols(path: &Path, pattern: &str) -> ExitCode {
+    let Ok(modules) = frontend::read_documentation(
+        path,
+        &frontend::FileSystem,
+        &mut log::HumanReadable::stderr(),
+    ) else {
+        return ExitCode::FAILURE;
+    };
+    let base_directory = modules[0].path.parent().unwrap().to_path_buf();
+    for module in &modules {
+        let path = module
+            .path
+            .strip_prefix(&base_directory)
+            .unwrap_or(&module.path);
+        for item in &module.items {
+            if frontend::fuzzy_match(pattern, &item.name) {
+                // Only the first line of the signature of a structure or an enumeration.
+                let signature = item.signature.lines().next().unwrap();
+                println!("{}:{}: {signature}", path.display(), item.line + 1);
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
 
+fn reduce_input(path: &Path) -> ExitCode {
+    let path = path.with_extension("sysc");
+    let source = match std::fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("ERROR: Cannot read `{}`. {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let is_failing = |source: &str| reduce::panics(source, &path, &frontend::FileSystem);
+    // The panic messages of the attempts are not printed.
+    std::panic::set_hook(Box::new(|_| {}));
+    if !is_failing(&source) {
+        eprintln!(
+            "ERROR: `{}` does not make the compiler panic.",
+            path.display()
+        );
+        return ExitCode::FAILURE;
+    }
+    print!("{}", reduce::reduce(&source, is_failing));
+    ExitCode::SUCCESS
+}
+
+fn fix_file(path: &Path, dry_run: bool) -> ExitCode {
+    let path = path.with_extension("sysc");
+    let source = match std::fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("ERROR: Cannot read `{}`. {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let fixed = fix::fix(&source, &path, &frontend::FileSystem);
+    if dry_run {
+        print!("{}", fix::diff(&source, &fixed));
+    } else if fixed != source {
+        if let Err(err) = std::fs::write(&path, fixed) {
+            eprintln!("ERROR: Cannot write `{}`. {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    }
     ExitCode::SUCCESS
 }
@@ -0,0 +1,102 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/*!
+ * Minimization of inputs which make the compiler panic, for reporting bugs
+ * of the compiler itself.
+ */
+
+mod tests;
+
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+use crate::frontend::{self, SourceProvider};
+use crate::log;
+
+/**
+ * Whether translating `source` as the root file makes the compiler panic.
+ * The files it imports are read through `source_provider`, relative to
+ * `root_file_path`. Errors in `source` are ignored.
+ */
+pub fn panics(source: &str, root_file_path: &Path, source_provider: &dyn SourceProvider) -> bool {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        frontend::read_input_from_string(
+            source,
+            root_file_path,
+            source_provider,
+            &mut log::Collector::default(),
+        )
+    }))
+    .is_err()
+}
+
+/**
+ * Removes as many lines of `source` as possible, and then as many words of
+ * each remaining line, while `is_failing` holds. `is_failing(source)` should
+ * hold at first.
+ */
+pub fn reduce(source: &str, mut is_failing: impl FnMut(&str) -> bool) -> String {
+    let lines: Vec<String> = source.lines().map(str::to_owned).collect();
+    let mut lines = remove_chunks(lines, |lines| is_failing(&lines.join("\n")));
+    for i in 0..lines.len() {
+        let words = lines[i].split_whitespace().map(str::to_owned).collect();
+        let words = remove_chunks(words, |words| {
+            let mut candidate = lines.clone();
+            candidate[i] = words.join(" ");
+            is_failing(&candidate.join("\n"))
+        });
+        lines[i] = words.join(" ");
+    }
+    lines.retain(|line| !line.is_empty());
+    lines.join("\n") + "\n"
+}
+
+/**
+ * Delta debugging: removes chunks of `units` while `is_failing` holds,
+ * halving the size of the chunks until single units cannot be removed.
+ */
+fn remove_chunks(
+    mut units: Vec<String>,
+    mut is_failing: impl FnMut(&[String]) -> bool,
+) -> Vec<String> {
+    let mut chunk_size = units.len().div_ceil(2).max(1);
+    loop {
+        let mut removed = false;
+        let mut start = 0;
+        while start < units.len() {
+            let end = (start + chunk_size).min(units.len());
+            let candidate: Vec<_> = units[..start]
+                .iter()
+                .chain(&units[end..])
+                .cloned()
+                .collect();
+            if is_failing(&candidate) {
+                units = candidate;
+                removed = true;
+            } else {
+                start = end;
+            }
+        }
+        if chunk_size > 1 {
+            chunk_size = chunk_size.div_ceil(2);
+        } else if !removed {
+            return units;
+        }
+    }
+}
@@ -0,0 +1,67 @@
+/*
+ * Copyright (c) 2023-2025 Atsushi Komaba
+ *
+ * This file is part of Syscraws.
+ * Syscraws is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License
+ * as published by the Free Software Foundation, either version 3
+ * of the License, or any later version.
+ *
+ * Syscraws is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Syscraws. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::*;
+use crate::frontend::{InMemory, SourceProvider};
+
+#[test]
+fn reduce_lines_and_words() {
+    let source = "a\nb x\nc y d\ne\n";
+    let reduced = reduce(source, |source| {
+        source.contains('x') && source.contains('y')
+    });
+    assert_eq!(reduced, "x\ny\n");
+}
+
+/**
+ * A [`SourceProvider`] which panics when a file is read, standing in for a
+ * bug in the frontend.
+ */
+struct Panicking;
+
+impl SourceProvider for Panicking {
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        Ok(path.to_path_buf())
+    }
+    fn read_to_string(&self, _: &Path) -> io::Result<String> {
+        panic!();
+    }
+}
+
+#[test]
+fn reduce_panicking_input() {
+    let source = "\
+struct S
+end
+var x
+import lib
+func f()
+end
+x = (1)
+";
+    let path = Path::new("main.sysc");
+    assert!(panics(source, path, &Panicking));
+    let reduced = reduce(source, |source| panics(source, path, &Panicking));
+    assert_eq!(reduced, "import lib\n");
+    assert!(!panics(source, path, &InMemory::default()));
+}